@@ -0,0 +1,10 @@
+/* Linker script for the nRF52833 on the micro:bit v2, without a SoftDevice.
+   Replaces the one nrf52833-hal provides (see build.rs). */
+MEMORY
+{
+  /* NOTE K = KiBi = 1024 bytes */
+  /* 512K less the last 4K page, which is reserved for the saved board (see PAGE_ADDR in
+     src/persist.rs) so the program image can never grow into it */
+  FLASH : ORIGIN = 0x00000000, LENGTH = 508K
+  RAM : ORIGIN = 0x20000000, LENGTH = 128K
+}
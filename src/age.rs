@@ -0,0 +1,151 @@
+//! Brightness by age: cells that have been alive longer are shown brighter (or, the other
+//! way round, newborn cells start bright and fade as they age).
+//!
+//! An age is kept for every cell. A newborn cell has age 1, each generation it survives adds
+//! one more (saturating at MAX_AGE so long-lived still lifes cannot overflow), and a dead cell
+//! has age 0. The Game keeps the ages of its board up to date (see Game::ages), so they are
+//! available for statistics as well as rendering. With the `age` cargo feature they are spread
+//! over the greyscale levels up to a configurable ceiling, so a cell that has just been born is
+//! dim and a settled block glows steadily. With the `age-fade` feature they are spread the other
+//! way, from full brightness down to a configurable floor, so births flare up and a settled block
+//! sinks into the background.
+
+use crate::fade::{self, Levels};
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// Age at which a cell stops getting older (and so brighter)
+pub const MAX_AGE: u8 = 16;
+
+/// Per-cell ages parallel to the board
+pub type Ages = [[u8; COL_COUNT]; ROW_COUNT];
+
+/// Age every cell by one generation given the board `next` it now shows.
+///
+/// A live cell with a non-zero age survived and gets one older (up to MAX_AGE), a live cell
+/// with age 0 was just born and becomes 1, and a dead cell is reset to 0.
+pub fn update(ages: &Ages, next: &LEDState) -> Ages {
+    let mut aged = [[0u8; COL_COUNT]; ROW_COUNT];
+
+    for (row, line) in aged.iter_mut().enumerate() {
+        for (col, age) in line.iter_mut().enumerate() {
+            *age = if next[row][col] == 0 {
+                0
+            } else {
+                ages[row][col].saturating_add(1).min(MAX_AGE)
+            };
+        }
+    }
+    aged
+}
+
+/// How far `age` (1..=MAX_AGE) is between newborn and MAX_AGE, scaled to 0..=`steps` and
+/// rounded to nearest so every step is reachable.
+fn scaled_age(age: u8, steps: u16) -> u16 {
+    let age = age.clamp(1, MAX_AGE) as u16 - 1;
+    let span = MAX_AGE as u16 - 1;
+    (age * steps + span / 2) / span
+}
+
+/// Brightness levels for `ages`: dead cells off, live cells from 1 (newborn) up to
+/// `max_brightness` (MAX_AGE and older), which is capped at fade::MAX_BRIGHTNESS.
+pub fn levels(ages: &Ages, max_brightness: u8) -> Levels {
+    let ceiling = max_brightness.clamp(1, fade::MAX_BRIGHTNESS) as u16;
+    let mut levels = [[0u8; COL_COUNT]; ROW_COUNT];
+
+    for (line, age_line) in levels.iter_mut().zip(ages.iter()) {
+        for (level, age) in line.iter_mut().zip(age_line.iter()) {
+            *level = match *age {
+                0 => 0,
+                age => (1 + scaled_age(age, ceiling - 1)) as u8,
+            };
+        }
+    }
+    levels
+}
+
+/// Brightness levels for `ages` fading with age: dead cells off, live cells from
+/// fade::MAX_BRIGHTNESS (newborn) down to `min_brightness` (MAX_AGE and older), which is kept
+/// between 1 and fade::MAX_BRIGHTNESS so old cells stay visible.
+pub fn fading_levels(ages: &Ages, min_brightness: u8) -> Levels {
+    let floor = min_brightness.clamp(1, fade::MAX_BRIGHTNESS) as u16;
+    let span = fade::MAX_BRIGHTNESS as u16 - floor;
+    let mut levels = [[0u8; COL_COUNT]; ROW_COUNT];
+
+    for (line, age_line) in levels.iter_mut().zip(ages.iter()) {
+        for (level, age) in line.iter_mut().zip(age_line.iter()) {
+            *level = match *age {
+                0 => 0,
+                age => (fade::MAX_BRIGHTNESS as u16 - scaled_age(age, span)) as u8,
+            };
+        }
+    }
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_born_at_1_survive_older_and_die_at_0() {
+        let mut board = [[0; COL_COUNT]; ROW_COUNT];
+        board[2][2] = 1;
+        let born = update(&[[0; COL_COUNT]; ROW_COUNT], &board);
+        assert_eq!(born[2][2], 1);
+        assert_eq!(born.iter().flatten().filter(|age| **age != 0).count(), 1);
+
+        let survived = update(&update(&born, &board), &board);
+        assert_eq!(survived[2][2], 3);
+
+        let died = update(&survived, &[[0; COL_COUNT]; ROW_COUNT]);
+        assert_eq!(died, [[0; COL_COUNT]; ROW_COUNT]);
+        // a cell born again where one died starts over
+        assert_eq!(update(&died, &board)[2][2], 1);
+    }
+
+    #[test]
+    fn ages_saturate_at_max_age() {
+        let board = [[1; COL_COUNT]; ROW_COUNT];
+        let mut ages = [[0; COL_COUNT]; ROW_COUNT];
+        for _ in 0..1_000 {
+            ages = update(&ages, &board);
+        }
+        assert_eq!(ages, [[MAX_AGE; COL_COUNT]; ROW_COUNT]);
+        assert_eq!(
+            update(&[[u8::MAX; COL_COUNT]; ROW_COUNT], &board)[0][0],
+            MAX_AGE
+        );
+    }
+
+    #[test]
+    fn levels_brighten_from_1_up_to_the_ceiling() {
+        let mut ages = [[0; COL_COUNT]; ROW_COUNT];
+        ages[0][0] = 1;
+        ages[0][1] = MAX_AGE / 2;
+        ages[0][2] = MAX_AGE;
+        assert_eq!(levels(&ages, 6)[0][..4], [1, 3, 6, 0]);
+
+        // the ceiling is kept between 1 and fade::MAX_BRIGHTNESS
+        assert_eq!(levels(&ages, u8::MAX)[0][2], fade::MAX_BRIGHTNESS);
+        assert_eq!(levels(&ages, 0)[0][..3], [1, 1, 1]);
+        // every level up to the ceiling is reached by some age
+        let mut shown: Vec<u8> = (1..=MAX_AGE)
+            .map(|age| levels(&[[age; COL_COUNT]; ROW_COUNT], fade::MAX_BRIGHTNESS)[0][0])
+            .collect();
+        shown.dedup();
+        assert_eq!(shown, (1..=fade::MAX_BRIGHTNESS).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn fading_levels_dim_from_full_down_to_the_floor() {
+        let mut ages = [[0; COL_COUNT]; ROW_COUNT];
+        ages[0][0] = 1;
+        ages[0][1] = MAX_AGE;
+        assert_eq!(
+            fading_levels(&ages, 3)[0][..3],
+            [fade::MAX_BRIGHTNESS, 3, 0]
+        );
+        // old cells never go dark
+        assert_eq!(fading_levels(&ages, 0)[0][1], 1);
+    }
+}
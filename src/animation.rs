@@ -0,0 +1,49 @@
+//! A player for short fixed animations, such as the power-up intro and self-test.
+//!
+//! An `Animation` is a sequence of frames, each computed from its index by a plain function and
+//! shown for the same fixed time. Playing one blocks on its own delay (or on the renderer
+//! scanning the matrix, for a backend that has to) rather than the game's frame grid, and checks an optional skip condition every few milliseconds while a frame is
+//! shown, so any animation can be cut short by the btns.
+
+use embedded_hal::delay::DelayNs;
+
+use crate::LEDState;
+use crate::render::Renderer;
+
+/// How often the skip condition is checked while a frame is shown
+const SKIP_POLL_MS: u32 = 10;
+
+/// A fixed sequence of frames
+pub struct Animation {
+    /// Number of frames
+    pub frames: usize,
+    /// How long each frame is shown
+    pub frame_ms: u32,
+    /// Frame `index` of the animation, for `index` in 0..frames
+    pub frame: fn(usize) -> LEDState,
+}
+
+impl Animation {
+    /// Play every frame on `renderer`, returning early once `skip` returns `true`.
+    pub fn play<D: DelayNs>(
+        &self,
+        renderer: &mut impl Renderer,
+        delay: &mut D,
+        mut skip: impl FnMut() -> bool,
+    ) {
+        for index in 0..self.frames {
+            renderer.render(&(self.frame)(index));
+            let mut shown_ms = 0;
+            while shown_ms < self.frame_ms {
+                if skip() {
+                    return;
+                }
+                let wait_ms = SKIP_POLL_MS.min(self.frame_ms - shown_ms);
+                if !renderer.hold(wait_ms) {
+                    delay.delay_ms(wait_ms);
+                }
+                shown_ms += wait_ms;
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Langton's Ant: ants walking a toroidal grid of cells, flipping them as they go.
+//!
+//! Each generation every ant in turn looks at the cell it stands on: on an unlit cell it turns
+//! right, on a lit cell it turns left, and then it flips the cell and steps forwards one cell,
+//! wrapping around the edges of the grid. Up to MAX_ANTS ants can walk the grid at once; the
+//! cells are shown dimmed with the ants drawn over them at full brightness.
+
+use crate::fade::{Levels, MAX_BRIGHTNESS};
+use crate::life;
+use crate::random::RandomSource;
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// Most ants walking the grid at once
+pub const MAX_ANTS: usize = 4;
+/// Brightness of a lit cell, dim enough for the ants to stand out on it
+const CELL_BRIGHTNESS: u8 = 3;
+
+/// The direction an ant faces
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Heading {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Heading {
+    /// The heading after a quarter turn clockwise.
+    fn right(self) -> Self {
+        match self {
+            Heading::Up => Heading::Right,
+            Heading::Right => Heading::Down,
+            Heading::Down => Heading::Left,
+            Heading::Left => Heading::Up,
+        }
+    }
+
+    /// The heading after a quarter turn anticlockwise.
+    fn left(self) -> Self {
+        self.right().right().right()
+    }
+}
+
+/// One ant: where it stands and which way it faces
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Ant {
+    pub row: usize,
+    pub col: usize,
+    pub heading: Heading,
+}
+
+impl Ant {
+    /// Turn by the cell under the ant, flip it, and step forwards.
+    fn step(&mut self, grid: &mut LEDState) {
+        let cell = &mut grid[self.row][self.col];
+        self.heading = if *cell == 0 {
+            self.heading.right()
+        } else {
+            self.heading.left()
+        };
+        *cell ^= 1;
+
+        // adding ROW_COUNT - 1 / COL_COUNT - 1 rather than subtracting 1 keeps the indices from
+        // going negative
+        let (row, col) = match self.heading {
+            Heading::Up => (self.row + ROW_COUNT - 1, self.col),
+            Heading::Right => (self.row, self.col + 1),
+            Heading::Down => (self.row + 1, self.col),
+            Heading::Left => (self.row, self.col + COL_COUNT - 1),
+        };
+        (self.row, self.col) = life::wrap::<COL_COUNT, ROW_COUNT>(row, col);
+    }
+}
+
+/// The grid and the ants walking it
+pub struct Colony {
+    grid: LEDState,
+    ants: [Option<Ant>; MAX_ANTS],
+}
+
+impl Colony {
+    /// An unlit grid with a single ant in the middle, facing up.
+    pub fn new() -> Self {
+        let mut ants = [None; MAX_ANTS];
+        ants[0] = Some(Ant {
+            row: ROW_COUNT / 2,
+            col: COL_COUNT / 2,
+            heading: Heading::Up,
+        });
+        Colony {
+            grid: [[0; COL_COUNT]; ROW_COUNT],
+            ants,
+        }
+    }
+
+    /// The cells, lit or not, without the ants.
+    pub fn grid(&self) -> &LEDState {
+        &self.grid
+    }
+
+    /// Add an ant on a random cell facing a random way, unless MAX_ANTS are already walking.
+    pub fn add_random<R: RandomSource>(&mut self, random_gen: &mut R) {
+        if let Some(slot) = self.ants.iter_mut().find(|ant| ant.is_none()) {
+            let bits = random_gen.random_u32();
+            let index = (bits as usize >> 2) % (ROW_COUNT * COL_COUNT);
+            *slot = Some(Ant {
+                row: index / COL_COUNT,
+                col: index % COL_COUNT,
+                heading: [Heading::Up, Heading::Right, Heading::Down, Heading::Left]
+                    [bits as usize & 3],
+            });
+        }
+    }
+
+    /// Remove the most recently added ant, if any are left.
+    pub fn remove_last(&mut self) {
+        if let Some(slot) = self.ants.iter_mut().rev().find(|ant| ant.is_some()) {
+            *slot = None;
+        }
+    }
+
+    /// Move every ant one step, in the order they were added.
+    pub fn step(&mut self) {
+        for ant in self.ants.iter_mut().flatten() {
+            ant.step(&mut self.grid);
+        }
+    }
+
+    /// Brightness levels: lit cells at CELL_BRIGHTNESS and the ants at MAX_BRIGHTNESS.
+    pub fn levels(&self) -> Levels {
+        let mut levels = [[0u8; COL_COUNT]; ROW_COUNT];
+        for (line, grid_line) in levels.iter_mut().zip(self.grid.iter()) {
+            for (level, cell) in line.iter_mut().zip(grid_line.iter()) {
+                *level = *cell * CELL_BRIGHTNESS;
+            }
+        }
+        for ant in self.ants.iter().flatten() {
+            levels[ant.row][ant.col] = MAX_BRIGHTNESS;
+        }
+        levels
+    }
+}
+
+impl Default for Colony {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,98 @@
+//! Headless generation-throughput runner for host-side benchmarking.
+//!
+//! Only compiled when not building for the bare-metal MB2 target, so that the pure stepping
+//! logic in life.rs can be driven from a host benchmark (e.g. criterion) or a timing test.
+//! The starting board is generated from `seed` with a xorshift32 PRNG so every run with the
+//! same seed, rule, and generation count produces the same final board.
+//!
+//! The ignored `throughput` test times each stepping method. Measured baseline (release build,
+//! HighLife, 10,000,000 generations, on a single-core virtualized x86-64 Xeon): about 70ns per
+//! generation with `run`, 85ns with `run_lut`, and 47ns with `run_bits`. Expect other hosts to
+//! differ. On-device throughput has not been measured.
+
+use crate::random::XorShift32;
+use crate::{COL_COUNT, LEDState, ROW_COUNT, bitboard, life, pack_board, unpack_board};
+
+/// Seed a board from `seed`, step it `generations` times under `rule`, and return the
+/// final board.
+pub fn run(seed: u32, generations: u32, rule: life::Rule) -> LEDState {
+    let mut fb = seeded_board(seed);
+    let mut scratch: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+    for _ in 0..generations {
+        life::step_with_rule(&mut fb, &mut scratch, rule, life::EdgeMode::Toroidal);
+    }
+    fb
+}
+
+/// As `run`, but stepping with the lookup table of `life::build_rule_table`, so the two
+/// stepping methods can be compared (they produce the same final board).
+pub fn run_lut(seed: u32, generations: u32, rule: life::Rule) -> LEDState {
+    let mut fb = seeded_board(seed);
+    let table = life::build_rule_table(rule);
+    let mut scratch: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+    for _ in 0..generations {
+        life::step_lut(&mut fb, &mut scratch, &table, life::EdgeMode::Toroidal);
+    }
+    fb
+}
+
+/// As `run`, but stepping the packed bitboard of bitboard.rs (with the same final board).
+pub fn run_bits(seed: u32, generations: u32, rule: life::Rule) -> LEDState {
+    let mut bits = pack_board(&seeded_board(seed));
+    for _ in 0..generations {
+        bits = bitboard::step(bits, rule);
+    }
+    unpack_board(bits)
+}
+
+/// The starting board for `seed`: its first xorshift32 output, unpacked like a random board.
+fn seeded_board(seed: u32) -> LEDState {
+    unpack_board(XorShift32::new(seed).next_u32())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    #[test]
+    fn runs_are_deterministic_given_a_seed() {
+        let rule = life::Rule::conway();
+        assert_eq!(run(7, 100, rule), run(7, 100, rule));
+        assert_eq!(seeded_board(7), unpack_board(XorShift32::new(7).next_u32()));
+        assert_eq!(run(7, 0, rule), seeded_board(7));
+    }
+
+    #[test]
+    fn every_stepping_method_ends_on_the_same_board() {
+        for rule in life::PRESETS {
+            for seed in 1..20 {
+                let board = run(seed, 50, rule);
+                assert_eq!(run_lut(seed, 50, rule), board, "{rule} seed {seed}");
+                assert_eq!(run_bits(seed, 50, rule), board, "{rule} seed {seed}");
+            }
+        }
+    }
+
+    /// Prints the generations per second of each stepping method. Ignored by default, run it in
+    /// a release build: `cargo test-host --release -- --ignored --nocapture throughput`
+    #[test]
+    #[ignore]
+    fn throughput() {
+        const GENERATIONS: u32 = 10_000_000;
+        type Runner = fn(u32, u32, life::Rule) -> LEDState;
+        let methods: [(&str, Runner); 3] =
+            [("run", run), ("run_lut", run_lut), ("run_bits", run_bits)];
+        for (name, method) in methods {
+            let start = Instant::now();
+            let board = method(1, GENERATIONS, life::Rule::highlife());
+            let elapsed = start.elapsed();
+            println!(
+                "{name}: {:.1}ns per generation ({} live cells at the end)",
+                elapsed.as_nanos() as f64 / GENERATIONS as f64,
+                board.iter().flatten().filter(|cell| **cell != 0).count()
+            );
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Life on a packed bitboard: the whole board held in the low ROW_COUNT * COL_COUNT bits of a
+//! u32, in the bit order of `pack_board` (bit `row * COL_COUNT + col`).
+//!
+//! Each of the 8 neighbor directions is a single shift of the whole board, with the row or column
+//! that wraps around patched in from the opposite edge, and the 8 shifted boards are summed for
+//! every cell at once by a bit-sliced adder. A generation then takes a few dozen integer
+//! operations rather than 200 array lookups. Only the toroidal edges are supported.
+
+use crate::life::Rule;
+use crate::{COL_COUNT, ROW_COUNT};
+
+/// Bits of the cells on the board
+const FULL: u32 = u32::MAX >> (32 - ROW_COUNT * COL_COUNT);
+/// Bits of the leftmost column
+const LEFT_COL: u32 = column_mask(0);
+/// Bits of the rightmost column
+const RIGHT_COL: u32 = column_mask(COL_COUNT - 1);
+
+/// The bits of every cell in column `col`.
+const fn column_mask(col: usize) -> u32 {
+    let mut mask = 0;
+    let mut row = 0;
+    while row < ROW_COUNT {
+        mask |= 1 << (row * COL_COUNT + col);
+        row += 1;
+    }
+    mask
+}
+
+/// The board with each cell replaced by its neighbor above.
+fn from_above(bits: u32) -> u32 {
+    ((bits << COL_COUNT) | (bits >> (COL_COUNT * (ROW_COUNT - 1)))) & FULL
+}
+
+/// The board with each cell replaced by its neighbor below.
+fn from_below(bits: u32) -> u32 {
+    ((bits >> COL_COUNT) | (bits << (COL_COUNT * (ROW_COUNT - 1)))) & FULL
+}
+
+/// The board with each cell replaced by its neighbor to the left.
+fn from_left(bits: u32) -> u32 {
+    ((bits << 1) & !LEFT_COL & FULL) | ((bits >> (COL_COUNT - 1)) & LEFT_COL)
+}
+
+/// The board with each cell replaced by its neighbor to the right.
+fn from_right(bits: u32) -> u32 {
+    ((bits >> 1) & !RIGHT_COL) | ((bits << (COL_COUNT - 1)) & RIGHT_COL)
+}
+
+/// The generation following the packed board `bits` under `rule`, on the torus.
+pub fn step(bits: u32, rule: Rule) -> u32 {
+    let bits = bits & FULL;
+    let (above, below) = (from_above(bits), from_below(bits));
+    let neighbors = [
+        from_left(above),
+        above,
+        from_right(above),
+        from_left(bits),
+        from_right(bits),
+        from_left(below),
+        below,
+        from_right(below),
+    ];
+
+    // bit-sliced count of the live neighbors: bit i of planes[k] is bit k of cell i's count,
+    // and 4 planes hold counts up to 8
+    let mut planes = [0u32; 4];
+    for neighbor in neighbors {
+        let mut carry = neighbor;
+        for plane in planes.iter_mut() {
+            let sum = *plane ^ carry;
+            carry &= *plane;
+            *plane = sum;
+        }
+    }
+
+    let mut next = 0;
+    for count in 0..=8u8 {
+        let with_count = planes.iter().enumerate().fold(FULL, |mask, (k, plane)| {
+            mask & if count >> k & 1 != 0 { *plane } else { !*plane }
+        });
+        if rule.survives(count) {
+            next |= with_count & bits;
+        }
+        if rule.births(count) {
+            next |= with_count & !bits;
+        }
+    }
+    next
+}
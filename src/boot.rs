@@ -0,0 +1,107 @@
+//! The boot menu: the btns held while the MB2 powers on (or is reset) select how it starts.
+//!
+//! Holding only A starts the demo (attract) mode, in which the game shows off its step modes on
+//! its own; holding only B restores the board saved to flash; and holding both runs the self-test
+//! of the LED matrix. With neither held the intro plays and the game starts on a random board as
+//! usual.
+//!
+//! The selected mode's icon is shown for at least BOOT_ICON_MS and then until both btns are
+//! released, so a btn held for the boot menu does not also act on the first board.
+
+use embedded_hal::delay::DelayNs;
+
+use crate::LEDState;
+use crate::animation::Animation;
+use crate::render::Renderer;
+
+/// How long a selected mode's icon is shown at least
+const BOOT_ICON_MS: u32 = 500;
+
+/// A play triangle
+const DEMO_ICON: LEDState = [
+    [1, 0, 0, 0, 0],
+    [1, 1, 0, 0, 0],
+    [1, 1, 1, 0, 0],
+    [1, 1, 0, 0, 0],
+    [1, 0, 0, 0, 0],
+];
+/// An arrow down onto a line
+const SAVED_ICON: LEDState = [
+    [0, 0, 1, 0, 0],
+    [1, 0, 1, 0, 1],
+    [0, 1, 1, 1, 0],
+    [0, 0, 1, 0, 0],
+    [1, 1, 1, 1, 1],
+];
+/// A check mark
+const SELF_TEST_ICON: LEDState = [
+    [0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 1],
+    [0, 0, 0, 1, 0],
+    [1, 0, 1, 0, 0],
+    [0, 1, 0, 0, 0],
+];
+
+/// How the MB2 starts
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BootMode {
+    /// The intro, then a random board
+    Normal,
+    /// The game cycles through its step modes until any input (Spec 28)
+    Demo,
+    /// The board saved to flash (Spec 12), or a random board if none was saved
+    Saved,
+    /// The LED matrix self-test (Spec 15), then a random board
+    SelfTest,
+}
+
+impl BootMode {
+    /// The mode selected by the btns held at power-up.
+    pub fn select(a_held: bool, b_held: bool) -> Self {
+        match (a_held, b_held) {
+            (false, false) => BootMode::Normal,
+            (true, false) => BootMode::Demo,
+            (false, true) => BootMode::Saved,
+            (true, true) => BootMode::SelfTest,
+        }
+    }
+
+    /// Show the mode's icon on `renderer` for BOOT_ICON_MS, and then until `released` returns
+    /// `true` (checked every few milliseconds). The Normal mode has no icon and returns at once.
+    pub fn show<D: DelayNs>(
+        self,
+        renderer: &mut impl Renderer,
+        delay: &mut D,
+        released: impl FnMut() -> bool,
+    ) {
+        let frame = match self {
+            BootMode::Normal => return,
+            BootMode::Demo => demo_frame,
+            BootMode::Saved => saved_frame,
+            BootMode::SelfTest => self_test_frame,
+        };
+        let icon = Animation {
+            frames: 1,
+            frame_ms: BOOT_ICON_MS,
+            frame,
+        };
+        icon.play(renderer, delay, || false);
+        Animation {
+            frames: usize::MAX,
+            ..icon
+        }
+        .play(renderer, delay, released);
+    }
+}
+
+fn demo_frame(_index: usize) -> LEDState {
+    DEMO_ICON
+}
+
+fn saved_frame(_index: usize) -> LEDState {
+    SAVED_ICON
+}
+
+fn self_test_frame(_index: usize) -> LEDState {
+    SELF_TEST_ICON
+}
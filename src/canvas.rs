@@ -0,0 +1,168 @@
+//! An "infinite canvas": Life on a board larger than the LED matrix.
+//!
+//! The `VirtualBoard` is stepped as a torus of W x H cells held in RAM (a 32x32 board of u8
+//! and its scratch copy take 2KB) by the generic toroidal step of life.rs (life::life), and
+//! only a 5x5 window of it is shown at a time. The window can be panned across the board (see
+//! the Canvas step mode in game.rs), wrapping around its edges, so larger patterns keep
+//! evolving off-screen.
+
+use crate::life::{self, Board, Rule};
+use crate::random::{RandomSource, XorShift32};
+use crate::{
+    CANVAS_COL_COUNT, CANVAS_ROW_COUNT, COL_COUNT, LEDState, RANDOM_DENSITY_PERCENT, ROW_COUNT,
+};
+
+/// The board of the Canvas step mode: dense, or with the `sparse` cargo feature a sparse one
+/// (sparse.rs) of the same interface, big enough that a dense one would not fit comfortably
+#[cfg(not(feature = "sparse"))]
+pub type Canvas = VirtualBoard<CANVAS_COL_COUNT, CANVAS_ROW_COUNT>;
+#[cfg(feature = "sparse")]
+pub type Canvas =
+    crate::sparse::SparseBoard<CANVAS_COL_COUNT, CANVAS_ROW_COUNT, { crate::SPARSE_CAPACITY }>;
+
+/// A W x H torus of cells
+pub struct VirtualBoard<const W: usize, const H: usize> {
+    cells: Board<W, H>,
+    scratch: Board<W, H>,
+}
+
+impl<const W: usize, const H: usize> VirtualBoard<W, H> {
+    /// Create an empty board.
+    pub const fn new() -> Self {
+        VirtualBoard {
+            cells: [[0; W]; H],
+            scratch: [[0; W]; H],
+        }
+    }
+
+    /// Light each cell with a RANDOM_DENSITY_PERCENT chance.
+    ///
+    /// Only one u32 is drawn from `random_gen`, used to seed a XorShift32 for the per-cell
+    /// decisions, since a large board would otherwise need hundreds of slow hardware draws.
+    pub fn randomize<R: RandomSource>(&mut self, random_gen: &mut R) {
+        let mut rng = XorShift32::new(random_gen.random_u32());
+        for cell in self.cells.iter_mut().flatten() {
+            *cell = (rng.next_u32() % 100 < RANDOM_DENSITY_PERCENT as u32) as u8;
+        }
+    }
+
+    /// `true` if no cell is lit.
+    pub fn done(&self) -> bool {
+        life::done(&self.cells)
+    }
+
+    /// Every cell of the board.
+    pub fn cells(&self) -> &Board<W, H> {
+        &self.cells
+    }
+
+    /// Advance the whole board by one generation of `rule`, wrapping around its edges.
+    pub fn step(&mut self, rule: Rule) {
+        life::life(&mut self.cells, &mut self.scratch, rule);
+    }
+
+    /// The 5x5 window whose top-left cell is `origin` (row, col), wrapping around the
+    /// right and bottom edges of the board.
+    pub fn view(&self, origin: (usize, usize)) -> LEDState {
+        let mut state: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+        for (row, line) in state.iter_mut().enumerate() {
+            for (col, cell) in line.iter_mut().enumerate() {
+                *cell = self.cells[(origin.0 + row) % H][(origin.1 + col) % W];
+            }
+        }
+        state
+    }
+}
+
+impl<const W: usize, const H: usize> Default for VirtualBoard<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{life, live_cells};
+
+    /// A 16x16 board with a glider's (0, 0) offset at `origin`
+    fn glider_at(origin: (usize, usize)) -> VirtualBoard<16, 16> {
+        let mut board = VirtualBoard::new();
+        for (row, col) in life::GLIDER {
+            board.cells[(origin.0 + row) % 16][(origin.1 + col) % 16] = 1;
+        }
+        board
+    }
+
+    #[test]
+    fn a_glider_moves_one_cell_diagonally_every_4_generations() {
+        let mut board = glider_at((3, 5));
+        for _ in 0..4 {
+            board.step(Rule::conway());
+        }
+        assert_eq!(board.cells, glider_at((4, 6)).cells);
+    }
+
+    #[test]
+    fn a_glider_crosses_the_whole_torus_back_to_its_start() {
+        // it passes the bottom right corner, off the 5x5 window, on the way
+        let start = glider_at((12, 12));
+        let mut board = glider_at((12, 12));
+        for _ in 0..4 * 16 {
+            board.step(Rule::conway());
+            assert_eq!(
+                board
+                    .cells
+                    .iter()
+                    .flatten()
+                    .filter(|cell| **cell != 0)
+                    .count(),
+                5
+            );
+        }
+        assert_eq!(board.cells, start.cells);
+        assert!(!board.done());
+    }
+
+    #[test]
+    fn a_blinker_across_the_corner_oscillates() {
+        let mut board = VirtualBoard::<16, 16>::new();
+        board.cells[0][15] = 1;
+        board.cells[0][0] = 1;
+        board.cells[0][1] = 1;
+        board.step(Rule::conway());
+        let lit: Vec<(usize, usize)> = (0..16)
+            .flat_map(|row| (0..16).map(move |col| (row, col)))
+            .filter(|(row, col)| board.cells[*row][*col] != 0)
+            .collect();
+        assert_eq!(lit, [(0, 0), (1, 0), (15, 0)]);
+    }
+
+    #[test]
+    fn the_view_wraps_around_the_right_and_bottom_edges() {
+        let mut board = VirtualBoard::<16, 16>::new();
+        board.cells[15][15] = 1;
+        board.cells[0][0] = 1;
+        board.cells[2][14] = 1;
+
+        // the top left of the board is the only lit cell of the window at the origin
+        assert_eq!(
+            live_cells(&board.view((0, 0))).collect::<Vec<_>>(),
+            [(0, 0)]
+        );
+        // a window over the bottom right corner shows the cells past it from the top and left
+        assert_eq!(
+            live_cells(&board.view((14, 14))).collect::<Vec<_>>(),
+            [(1, 1), (2, 2), (4, 0)]
+        );
+        // a window that is past the edge on one axis only
+        assert_eq!(
+            live_cells(&board.view((15, 3))).collect::<Vec<_>>(),
+            Vec::<(usize, usize)>::new()
+        );
+        assert_eq!(
+            live_cells(&board.view((0, 12))).collect::<Vec<_>>(),
+            [(0, 4), (2, 2)]
+        );
+    }
+}
@@ -0,0 +1,46 @@
+//! A short "collapse" animation played when a dead (or stuck) board is restarted.
+//!
+//! A sparkling square ring closes in from the edge of the grid to the center LED, which then
+//! goes out, so the new random board appearing reads as a deliberate restart rather than a
+//! glitch. It is played by the animation player (animation.rs) and takes 400ms.
+
+use crate::animation::Animation;
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// How long each frame of the collapse is shown
+const COLLAPSE_FRAME_MS: u32 = 80;
+/// Radius of the outermost ring, which touches the nearest edge of the grid
+const MAX_RADIUS: usize = (if ROW_COUNT < COL_COUNT {
+    ROW_COUNT
+} else {
+    COL_COUNT
+} - 1)
+    / 2;
+/// Number of frames: one per ring from MAX_RADIUS in to the center, then a blank one
+const COLLAPSE_FRAMES: usize = MAX_RADIUS + 2;
+
+/// Frame `index` of the collapse: every other LED of the ring MAX_RADIUS - index from the
+/// center, alternating between frames so the ring sparkles as it shrinks, and then nothing.
+pub fn frame(index: usize) -> LEDState {
+    let mut state: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+    let Some(radius) = MAX_RADIUS.checked_sub(index) else {
+        return state;
+    };
+
+    for (row, line) in state.iter_mut().enumerate() {
+        for (col, cell) in line.iter_mut().enumerate() {
+            let distance = row.abs_diff(ROW_COUNT / 2).max(col.abs_diff(COL_COUNT / 2));
+            // the center is a ring of one LED, which always lights
+            let sparkle = radius == 0 || (row + col + index).is_multiple_of(2);
+            *cell = (distance == radius && sparkle) as u8;
+        }
+    }
+    state
+}
+
+/// The collapse animation.
+pub const COLLAPSE: Animation = Animation {
+    frames: COLLAPSE_FRAMES,
+    frame_ms: COLLAPSE_FRAME_MS,
+    frame,
+};
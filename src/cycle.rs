@@ -0,0 +1,157 @@
+//! Detection of boards caught in a cycle of generations.
+//!
+//! Every generation is hashed into a u32. On the 5x5 board the 25-bit packing of `pack_board`
+//! is a perfect hash, so two boards are equal exactly when their hashes are and a comparison is
+//! a single integer equality. A short history of recent hashes then reveals any oscillator (or
+//! still life, period 1) whose period fits in the history.
+
+/// Return the period of the cycle `current` completes, or `None` if it matches none of the hashes
+/// in `history` (oldest first, so the last hash is the previous generation's). A match with the
+/// previous generation is period 1 (a still life), the one before that period 2, and so on.
+pub fn detect_cycle(history: &[u32], current: u32) -> Option<u32> {
+    history
+        .iter()
+        .rev()
+        .position(|hash| *hash == current)
+        .map(|age| age as u32 + 1)
+}
+
+/// The hashes of the last N generations, oldest first.
+pub struct History<const N: usize> {
+    hashes: [u32; N],
+    len: usize,
+}
+
+impl<const N: usize> History<N> {
+    /// Create an empty history.
+    pub const fn new() -> Self {
+        History {
+            hashes: [0; N],
+            len: 0,
+        }
+    }
+
+    /// The recorded hashes, oldest first.
+    pub fn as_slice(&self) -> &[u32] {
+        &self.hashes[..self.len]
+    }
+
+    /// Record the hash of the newest generation, forgetting the oldest once N are held.
+    pub fn push(&mut self, hash: u32) {
+        if self.len < N {
+            self.hashes[self.len] = hash;
+            self.len += 1;
+        } else if N > 0 {
+            self.hashes.copy_within(1.., 0);
+            self.hashes[N - 1] = hash;
+        }
+    }
+
+    /// Forget every hash, e.g. when the board is replaced rather than stepped.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for History<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{COL_COUNT, LEDState, ROW_COUNT, life, pack_board};
+
+    #[test]
+    fn history_keeps_the_last_n_hashes_oldest_first() {
+        let mut history = History::<3>::new();
+        assert_eq!(history.as_slice(), &[] as &[u32]);
+        for hash in 1..=5 {
+            history.push(hash);
+        }
+        assert_eq!(history.as_slice(), &[3, 4, 5]);
+        history.clear();
+        assert_eq!(history.as_slice(), &[] as &[u32]);
+    }
+
+    #[test]
+    fn a_repeat_of_the_previous_generation_is_a_still_life() {
+        assert_eq!(detect_cycle(&[7, 9], 9), Some(1));
+        assert_eq!(detect_cycle(&[], 9), None);
+    }
+
+    /// Step `board` with Conway's rules on the torus for `generations`, returning the period
+    /// detect_cycle reported at each generation with an 8-hash history
+    fn periods(board: LEDState, generations: usize) -> Vec<Option<u32>> {
+        let mut fb = board;
+        let mut scratch = [[0; COL_COUNT]; ROW_COUNT];
+        let mut history = History::<8>::new();
+        history.push(pack_board(&fb));
+        (0..generations)
+            .map(|_| {
+                life::step_with_rule(
+                    &mut fb,
+                    &mut scratch,
+                    life::Rule::conway(),
+                    life::EdgeMode::Toroidal,
+                );
+                let hash = pack_board(&fb);
+                let period = detect_cycle(history.as_slice(), hash);
+                history.push(hash);
+                period
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_blinker_has_period_2() {
+        let mut blinker = [[0; COL_COUNT]; ROW_COUNT];
+        blinker[2] = [0, 1, 1, 1, 0];
+        // its first step only reaches the other phase; from the second on it repeats
+        assert_eq!(
+            periods(blinker, 5),
+            [None, Some(2), Some(2), Some(2), Some(2)]
+        );
+    }
+
+    #[test]
+    fn a_sequence_repeating_every_third_hash_has_period_3() {
+        let hashes = [1, 2, 3, 1, 2, 3, 1];
+        let mut history = History::<8>::new();
+        let mut found = Vec::new();
+        for hash in hashes {
+            found.push(detect_cycle(history.as_slice(), hash));
+            history.push(hash);
+        }
+        assert_eq!(
+            found,
+            [None, None, None, Some(3), Some(3), Some(3), Some(3)]
+        );
+    }
+
+    #[test]
+    fn the_most_recent_match_gives_the_period() {
+        // 5 was also seen 5 generations ago, but the repeat 2 generations ago is the period
+        assert_eq!(detect_cycle(&[5, 8, 9, 5, 6], 5), Some(2));
+    }
+
+    #[test]
+    fn a_glider_is_no_cycle_within_the_history() {
+        // it only comes back to its start after 20 generations, more than the history holds
+        let mut glider = [[0; COL_COUNT]; ROW_COUNT];
+        life::place_pattern(&mut glider, &life::GLIDER, (0, 0), life::OverflowMode::Wrap);
+        assert!(periods(glider, 40).iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn a_period_longer_than_the_history_is_not_found() {
+        let mut history = History::<3>::new();
+        for hash in [1, 2, 3, 4] {
+            history.push(hash);
+        }
+        assert_eq!(detect_cycle(history.as_slice(), 1), None);
+        assert_eq!(detect_cycle(history.as_slice(), 2), Some(3));
+    }
+}
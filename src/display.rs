@@ -0,0 +1,156 @@
+//! The MB2's onboard LED matrix as a render::Renderer.
+//!
+//! `Greyscale`, the default backend, is the microbit nonblocking display. It refreshes the
+//! matrix one row at a time from the TIMER2 interrupt, so the display is shared between the
+//! interrupt handler and main behind a critical section Mutex.
+//!
+//! `Dithered`, built with the `blocking-display` feature instead, is the microbit blocking
+//! display. It only lights the matrix while `Renderer::hold` is scanning it, and fakes 4
+//! brightness levels by temporal dithering: every scan shows one of DITHER_PHASES binary
+//! images, a dim cell being lit in fewer of them than a bright one.
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{Mutex, free};
+#[cfg(feature = "blocking-display")]
+use microbit::display::blocking;
+use microbit::display::nonblocking::{Display, GreyscaleImage};
+use microbit::gpio::DisplayPins;
+#[cfg(feature = "blocking-display")]
+use microbit::hal::timer::Timer;
+use microbit::pac::{self, NVIC, TIMER2, interrupt};
+
+use crate::fade::Levels;
+use crate::render::Renderer;
+#[cfg(feature = "blocking-display")]
+use crate::render::SCAN_MS;
+#[cfg(feature = "blocking-display")]
+use crate::{COL_COUNT, LEDState, ROW_COUNT, fade};
+
+/// Number of scans one dithering cycle is spread over. 3 phases at SCAN_MS give the 4 levels off,
+/// 1/3, 2/3, and full, and repeat every 15ms (~67Hz), fast enough not to flicker noticeably
+#[cfg(feature = "blocking-display")]
+const DITHER_PHASES: u8 = 3;
+
+/// NVIC priority of the display refresh interrupt. The nRF52833 implements 3 priority bits
+/// (levels 0..=7, stored in the top bits of the byte). Level 2 keeps the refresh above any
+/// future sensor or input interrupts, so their handlers cannot make the matrix flicker, while
+/// leaving levels 0 and 1 free for anything hard real-time. It is currently the only interrupt.
+const DISPLAY_PRIORITY: u8 = 2 << 5;
+
+/// The nonblocking display, shared with the TIMER2 interrupt handler
+static DISPLAY: Mutex<RefCell<Option<Display<TIMER2>>>> = Mutex::new(RefCell::new(None));
+
+/// The MB2's onboard 5x5 LED matrix with 10 brightness levels per LED.
+#[cfg_attr(
+    any(
+        feature = "blocking-display",
+        feature = "ppi-display",
+        feature = "neopixel"
+    ),
+    allow(dead_code)
+)]
+pub struct Greyscale;
+
+#[cfg_attr(
+    any(
+        feature = "blocking-display",
+        feature = "ppi-display",
+        feature = "neopixel"
+    ),
+    allow(dead_code)
+)]
+impl Greyscale {
+    /// Hand the display pins and TIMER2 to the nonblocking display and start its interrupt.
+    pub fn new(timer: TIMER2, pins: DisplayPins, nvic: &mut NVIC) -> Self {
+        let display = Display::new(timer, pins);
+        free(|cs| DISPLAY.borrow(cs).replace(Some(display)));
+
+        // SAFETY: the handler only touches DISPLAY inside a critical section, and DISPLAY has been
+        // set up before the interrupt is unmasked
+        unsafe {
+            nvic.set_priority(pac::Interrupt::TIMER2, DISPLAY_PRIORITY);
+            NVIC::unmask(pac::Interrupt::TIMER2);
+        }
+        Greyscale
+    }
+}
+
+impl Renderer for Greyscale {
+    fn render_levels(&mut self, levels: &Levels) {
+        let image = GreyscaleImage::new(levels);
+        free(|cs| {
+            if let Some(display) = DISPLAY.borrow(cs).borrow_mut().as_mut() {
+                display.show(&image);
+            }
+        });
+    }
+}
+
+/// The MB2's onboard 5x5 LED matrix scanned from the main loop, dithered to 4 brightness levels.
+#[cfg(feature = "blocking-display")]
+#[cfg_attr(feature = "neopixel", allow(dead_code))]
+pub struct Dithered {
+    display: blocking::Display,
+    /// The delay the rows are scanned with
+    timer: Timer<TIMER2>,
+    /// Dithered level (0..=DITHER_PHASES) of every cell of the image being shown
+    levels: Levels,
+    /// The binary image to show on the next scan, 0..DITHER_PHASES
+    phase: u8,
+}
+
+#[cfg(feature = "blocking-display")]
+#[cfg_attr(feature = "neopixel", allow(dead_code))]
+impl Dithered {
+    /// Hand the display pins and TIMER2 (as the row delay) to the blocking display.
+    pub fn new(timer: TIMER2, pins: DisplayPins) -> Self {
+        let mut display = blocking::Display::new(pins);
+        display.set_delay_ms(SCAN_MS / ROW_COUNT as u32);
+        Dithered {
+            display,
+            timer: Timer::new(timer),
+            levels: [[0; COL_COUNT]; ROW_COUNT],
+            phase: 0,
+        }
+    }
+}
+
+#[cfg(feature = "blocking-display")]
+impl Renderer for Dithered {
+    fn render_levels(&mut self, levels: &Levels) {
+        for (dithered, level) in self
+            .levels
+            .iter_mut()
+            .flatten()
+            .zip(levels.iter().flatten())
+        {
+            // round up so that a dim cell is lit in at least one phase
+            *dithered =
+                (*level as u16 * DITHER_PHASES as u16).div_ceil(fade::MAX_BRIGHTNESS as u16) as u8;
+        }
+    }
+
+    fn hold(&mut self, ms: u32) -> bool {
+        for _ in 0..ms / SCAN_MS {
+            let mut image: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+            for (cell, level) in image.iter_mut().flatten().zip(self.levels.iter().flatten()) {
+                *cell = (*level > self.phase) as u8;
+            }
+            self.display.show(&mut self.timer, image, SCAN_MS);
+            self.phase = (self.phase + 1) % DITHER_PHASES;
+        }
+        self.display.clear();
+        true
+    }
+}
+
+/// Refresh the next row of the matrix.
+#[interrupt]
+fn TIMER2() {
+    free(|cs| {
+        if let Some(display) = DISPLAY.borrow(cs).borrow_mut().as_mut() {
+            display.handle_display_event();
+        }
+    });
+}
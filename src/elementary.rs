@@ -0,0 +1,88 @@
+//! Elementary (1D) cellular automata shown as a waterfall.
+//!
+//! An elementary automaton steps a single row of cells: each cell's next state is looked up from
+//! its own state and its left and right neighbors' (wrapping around the ends of the row) in the 8
+//! bits of a Wolfram rule number, bit `left << 2 | center << 1 | right` for each neighborhood.
+//! Each generation is computed from the top row of the grid and pushed in above it, scrolling the
+//! older generations down and off the bottom edge.
+
+use crate::random::RandomSource;
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// The rule numbers that can be cycled through on the device: Rule 30 (chaotic), 45, 90 (the
+/// Sierpinski triangle), 110 (Turing complete), 150, and 184 (traffic flow)
+pub const RULES: [u8; 6] = [30, 45, 90, 110, 150, 184];
+
+/// A row of cells, one u8 (0 or 1) per cell
+pub type Row = [u8; COL_COUNT];
+
+/// The generation following `row` under Wolfram rule `rule`.
+pub fn next_row(row: &Row, rule: u8) -> Row {
+    let mut next = [0; COL_COUNT];
+    for (col, cell) in next.iter_mut().enumerate() {
+        let left = row[(col + COL_COUNT - 1) % COL_COUNT];
+        let right = row[(col + 1) % COL_COUNT];
+        let neighborhood = left << 2 | row[col] << 1 | right;
+        *cell = (rule >> neighborhood) & 1;
+    }
+    next
+}
+
+/// The grid of generations, newest on the top row, and the selected rule
+pub struct Waterfall {
+    rows: LEDState,
+    rule_index: usize,
+}
+
+impl Waterfall {
+    /// An otherwise empty grid with a single lit cell in the middle of the top row, under the
+    /// first of RULES.
+    pub fn new() -> Self {
+        let mut rows = [[0; COL_COUNT]; ROW_COUNT];
+        rows[0][COL_COUNT / 2] = 1;
+        Waterfall {
+            rows,
+            rule_index: 0,
+        }
+    }
+
+    /// The grid, newest generation on the top row.
+    pub fn rows(&self) -> &LEDState {
+        &self.rows
+    }
+
+    /// The selected Wolfram rule number.
+    pub fn rule(&self) -> u8 {
+        RULES[self.rule_index]
+    }
+
+    /// Select the next of RULES, wrapping round to the first.
+    pub fn next_rule(&mut self) {
+        self.rule_index = (self.rule_index + 1) % RULES.len();
+    }
+
+    /// Clear the grid and start again from a random top row, with at least one cell lit.
+    pub fn seed<R: RandomSource>(&mut self, random_gen: &mut R) {
+        let bits = random_gen.random_u32();
+        self.rows = [[0; COL_COUNT]; ROW_COUNT];
+        for (col, cell) in self.rows[0].iter_mut().enumerate() {
+            *cell = (bits >> col) as u8 & 1;
+        }
+        if self.rows[0].iter().all(|cell| *cell == 0) {
+            self.rows[0][COL_COUNT / 2] = 1;
+        }
+    }
+
+    /// Compute the next generation from the top row and scroll it in, dropping the oldest.
+    pub fn step(&mut self) {
+        let next = next_row(&self.rows[0], self.rule());
+        self.rows.copy_within(0..ROW_COUNT - 1, 1);
+        self.rows[0] = next;
+    }
+}
+
+impl Default for Waterfall {
+    fn default() -> Self {
+        Self::new()
+    }
+}
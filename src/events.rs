@@ -0,0 +1,254 @@
+//! Typed input events queued between the input pollers and the game loop.
+//!
+//! The pollers (the btn and logo samplers at every sub-frame, and the shake detector once per
+//! frame) turn what they see into `Event`s on a heapless single-producer single-consumer queue,
+//! timed by whenever they run. The game loop drains the queue once per frame into a `Frame` of
+//! inputs: a btn counts as pressed in a frame if it was held at any point of it, so a tap shorter
+//! than a frame still reaches the game (this replaces latching the btns). The queue is lock-free,
+//! so a producer can as well be moved into an interrupt handler later.
+//!
+//! Short, long, and double presses are still told apart by the game from the frames
+//! (input::Gestures), since their timing is counted in frames.
+
+use embedded_hal::digital::InputPin;
+use heapless::spsc::{Consumer, Producer};
+
+use crate::input::Debounced;
+
+/// Slots of the event queue. heapless keeps one slot free, so 15 events fit, far more than the
+/// btns can produce in one frame
+pub const EVENT_QUEUE_LEN: usize = 16;
+
+/// An input that can be pressed
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Button {
+    A,
+    B,
+    /// The touch logo (touch.rs)
+    Logo,
+}
+
+impl Button {
+    /// Index into per-button arrays.
+    fn index(self) -> usize {
+        match self {
+            Button::A => 0,
+            Button::B => 1,
+            Button::Logo => 2,
+        }
+    }
+}
+
+/// Something that happened to the inputs
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Event {
+    /// The btn's debounced state went from released to pressed
+    Pressed(Button),
+    /// The btn's debounced state went from pressed to released
+    Released(Button),
+    /// The MB2 was being shaken when the frame read the accelerometer
+    Shake,
+}
+
+/// Sample `debounced` once and queue an event if its debounced state changed. A full queue drops
+/// the event (the game would see a missed press or release for one frame at most).
+pub fn poll<P: InputPin>(
+    producer: &mut Producer<'_, Event, EVENT_QUEUE_LEN>,
+    button: Button,
+    debounced: &mut Debounced<P>,
+) {
+    if let Some(pressed) = debounced.sample() {
+        let event = if pressed {
+            Event::Pressed(button)
+        } else {
+            Event::Released(button)
+        };
+        producer.enqueue(event).ok();
+    }
+}
+
+/// As `poll`, but only queue releases. A press still moves the debounced state, so the caller can
+/// act on it (e.g. to skip an animation), but never reaches the game, which would otherwise act
+/// on it again in the next frame. The release that follows it is queued as usual.
+pub fn poll_releases<P: InputPin>(
+    producer: &mut Producer<'_, Event, EVENT_QUEUE_LEN>,
+    button: Button,
+    debounced: &mut Debounced<P>,
+) {
+    if debounced.sample() == Some(false) {
+        producer.enqueue(Event::Released(button)).ok();
+    }
+}
+
+/// The inputs of one frame, folded from the events drained since the last one
+pub struct Frame {
+    /// Debounced state of each btn after the last event
+    held: [bool; 3],
+    /// Each btn was pressed at some point of the frame
+    pressed: [bool; 3],
+    shaken: bool,
+}
+
+impl Frame {
+    /// Start with every btn released.
+    pub const fn new() -> Self {
+        Frame {
+            held: [false; 3],
+            pressed: [false; 3],
+            shaken: false,
+        }
+    }
+
+    /// Start the next frame from the btns still held, and fold in every event queued since.
+    pub fn drain(&mut self, consumer: &mut Consumer<'_, Event, EVENT_QUEUE_LEN>) {
+        self.pressed = self.held;
+        self.shaken = false;
+        while let Some(event) = consumer.dequeue() {
+            match event {
+                Event::Pressed(button) => {
+                    self.held[button.index()] = true;
+                    self.pressed[button.index()] = true;
+                }
+                Event::Released(button) => self.held[button.index()] = false,
+                Event::Shake => self.shaken = true,
+            }
+        }
+    }
+
+    /// `true` if `button` was held at any point of the frame.
+    pub fn pressed(&self, button: Button) -> bool {
+        self.pressed[button.index()]
+    }
+
+    /// `true` if the MB2 was shaken this frame.
+    pub fn shaken(&self) -> bool {
+        self.shaken
+    }
+
+    /// `true` if anything was pressed or shaken this frame.
+    pub fn any(&self) -> bool {
+        self.shaken || self.pressed.iter().any(|pressed| *pressed)
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::DEBOUNCE_SAMPLES;
+    use crate::{ButtonPress, FakeButton};
+    use heapless::spsc::Queue;
+
+    /// Sub-frames sampled per frame
+    const SAMPLES: usize = 10;
+
+    #[test]
+    fn a_press_release_press_reaches_the_frames() {
+        // after the read taken by Debounced::new: pressed for the first half of frame 0, released
+        // through frame 1, and pressed again (a tap) in the middle of frame 2
+        const SCRIPT: [bool; 1 + 3 * SAMPLES] = {
+            let mut script = [false; 1 + 3 * SAMPLES];
+            let mut sample = 1;
+            while sample <= 5 {
+                script[sample] = true;
+                sample += 1;
+            }
+            script[1 + 2 * SAMPLES + 4] = true;
+            script[1 + 2 * SAMPLES + 5] = true;
+            script
+        };
+        let mut queue: Queue<Event, EVENT_QUEUE_LEN> = Queue::new();
+        let (mut producer, mut consumer) = queue.split();
+        let mut button = Debounced::new(FakeButton::new(&SCRIPT));
+        let mut frame = Frame::new();
+        let mut pressed = Vec::new();
+        for _ in 0..4 {
+            for _ in 0..SAMPLES {
+                poll(&mut producer, Button::A, &mut button);
+            }
+            frame.drain(&mut consumer);
+            pressed.push(frame.pressed(Button::A));
+        }
+        // the 2-sample tap only gets through a debounce of at most 2 samples
+        const { assert!(DEBOUNCE_SAMPLES <= 2) };
+        assert_eq!(pressed, [true, false, true, false]);
+        assert!(!frame.pressed(Button::B));
+    }
+
+    #[test]
+    fn a_press_polled_for_releases_only_never_reaches_the_frames() {
+        let mut queue: Queue<Event, EVENT_QUEUE_LEN> = Queue::new();
+        let (mut producer, mut consumer) = queue.split();
+        // after the read taken by Debounced::new: a press through the first frame, then released
+        const SCRIPT: [bool; 1 + 2 * SAMPLES] = {
+            let mut script = [false; 1 + 2 * SAMPLES];
+            let mut sample = 1;
+            while sample <= SAMPLES {
+                script[sample] = true;
+                sample += 1;
+            }
+            script
+        };
+        let mut button = Debounced::new(FakeButton::new(&SCRIPT));
+        let mut frame = Frame::new();
+
+        for _ in 0..SAMPLES {
+            poll_releases(&mut producer, Button::A, &mut button);
+        }
+        // the caller sees the press, the game does not
+        assert!(button.pressed());
+        frame.drain(&mut consumer);
+        assert!(!frame.pressed(Button::A));
+
+        for _ in 0..SAMPLES {
+            poll(&mut producer, Button::A, &mut button);
+        }
+        frame.drain(&mut consumer);
+        assert!(!button.pressed());
+        assert!(!frame.pressed(Button::A));
+        assert!(!frame.any());
+    }
+
+    #[test]
+    fn a_release_polled_for_releases_only_still_reaches_the_frames() {
+        let mut queue: Queue<Event, EVENT_QUEUE_LEN> = Queue::new();
+        let (mut producer, mut consumer) = queue.split();
+        let mut button = Debounced::new(FakeButton::new(&[false, true, true, false]));
+        let mut frame = Frame::new();
+        // a press the game has seen, released while only releases are polled
+        for _ in 0..2 {
+            poll(&mut producer, Button::B, &mut button);
+        }
+        frame.drain(&mut consumer);
+        assert!(frame.pressed(Button::B));
+        for _ in 0..SAMPLES {
+            poll_releases(&mut producer, Button::B, &mut button);
+        }
+        // still pressed in the frame it was released in, and not in the one after
+        frame.drain(&mut consumer);
+        assert!(frame.pressed(Button::B));
+        frame.drain(&mut consumer);
+        assert!(!frame.pressed(Button::B));
+    }
+
+    #[test]
+    fn a_held_btn_stays_pressed_in_every_frame() {
+        let mut queue: Queue<Event, EVENT_QUEUE_LEN> = Queue::new();
+        let (mut producer, mut consumer) = queue.split();
+        let mut button = Debounced::new(FakeButton::new(&[false, true]));
+        let mut frame = Frame::new();
+        for _ in 0..3 {
+            for _ in 0..SAMPLES {
+                poll(&mut producer, Button::B, &mut button);
+            }
+            frame.drain(&mut consumer);
+            assert!(frame.pressed(Button::B));
+            assert!(frame.any());
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Brightness levels and crossfading between consecutive generations.
+//!
+//! Rather than swapping instantly from one generation to the next, dying cells fade out and
+//! newly born cells fade in over the refresh window (or, as a dissolve, flip one at a time in a
+//! scattered order). Brightness uses the 0..=9 scale of the
+//! microbit greyscale images, which the nonblocking display shows directly. The loop splits
+//! each frame into SUB_FRAMES sub-frames and shows the `Frame` levels for each one in turn.
+
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// Brightness of a fully lit cell
+pub const MAX_BRIGHTNESS: u8 = 9;
+/// Number of sub-frames each frame is divided into (10ms each at 100ms)
+pub const SUB_FRAMES: u32 = 10;
+/// Peak brightness of the center pixel pulsing on an empty board as the restart countdown starts.
+/// It rises to MAX_BRIGHTNESS as the restart approaches
+const EMPTY_PULSE_BRIGHTNESS: u8 = 3;
+/// Cells of a dissolve flip in the order of their index times this stride (mod the cell count),
+/// which scatters them across the grid. It must share no factor with ROW_COUNT * COL_COUNT so
+/// every cell gets its own turn
+const DISSOLVE_STRIDE: usize = 7;
+
+/// Per-cell brightness levels in 0..=MAX_BRIGHTNESS
+pub type Levels = [[u8; COL_COUNT]; ROW_COUNT];
+
+/// What to show over the course of one frame.
+pub enum Frame {
+    /// A plain on/off board
+    Board(LEDState),
+    /// Fixed brightness levels (trail, highlight, ...)
+    Levels(Levels),
+    /// A transition from `prev` to `next`
+    Crossfade { prev: LEDState, next: LEDState },
+    /// A transition from `prev` to `next` with each changing cell flipping fully at its own
+    /// point of the frame
+    Dissolve { prev: LEDState, next: LEDState },
+    /// An empty board, shown as a dim center pixel pulsing once per frame so the screen is not
+    /// simply dark while the dead-board restart counts down. `countdown` is how far it has
+    /// counted, 0.0..=1.0 (see ResetTimer::progress), and the pulse brightens with it
+    Empty { countdown: f32 },
+}
+
+impl Frame {
+    /// `true` if the levels change from one sub-frame to the next.
+    pub fn animated(&self) -> bool {
+        matches!(
+            self,
+            Frame::Crossfade { .. } | Frame::Dissolve { .. } | Frame::Empty { .. }
+        )
+    }
+
+    /// The brightness levels at position t in 0.0..=1.0 through the frame.
+    pub fn levels_at(&self, t: f32) -> Levels {
+        match self {
+            Frame::Board(state) => levels(state),
+            Frame::Levels(levels) => *levels,
+            Frame::Crossfade { prev, next } => interpolate(prev, next, t),
+            Frame::Dissolve { prev, next } => dissolve(prev, next, t),
+            Frame::Empty { countdown } => pulse(t, *countdown),
+        }
+    }
+}
+
+/// Lit cells at MAX_BRIGHTNESS, unlit cells off.
+pub fn levels(state: &LEDState) -> Levels {
+    interpolate(state, state, 1.0)
+}
+
+/// Only the center pixel lit, rising from off (at t = 0.0) to its peak (at t = 0.5) and back to
+/// off (at t = 1.0). The peak goes from EMPTY_PULSE_BRIGHTNESS to MAX_BRIGHTNESS as `countdown`
+/// goes from 0.0 to 1.0.
+fn pulse(t: f32, countdown: f32) -> Levels {
+    let mut levels = [[0u8; COL_COUNT]; ROW_COUNT];
+    let rise = 1.0 - (2.0 * t.clamp(0.0, 1.0) - 1.0).abs();
+    let peak = EMPTY_PULSE_BRIGHTNESS as f32
+        + (MAX_BRIGHTNESS - EMPTY_PULSE_BRIGHTNESS) as f32 * countdown.clamp(0.0, 1.0);
+    levels[ROW_COUNT / 2][COL_COUNT / 2] = (rise * peak + 0.5) as u8;
+    levels
+}
+
+/// Show each cell as in `prev` until its turn in the dissolve order comes up part way through
+/// the frame, and as in `next` after it, so the cells that change flip in a scattered order
+/// between t = 0.0 (all of `prev`) and t = 1.0 (all of `next`).
+fn dissolve(prev: &LEDState, next: &LEDState, t: f32) -> Levels {
+    const LED_COUNT: usize = ROW_COUNT * COL_COUNT;
+    let mut state = *prev;
+
+    for (i, cell) in state.iter_mut().flatten().enumerate() {
+        let turn = ((i * DISSOLVE_STRIDE) % LED_COUNT) as f32 / LED_COUNT as f32;
+        if t > turn {
+            *cell = next[i / COL_COUNT][i % COL_COUNT];
+        }
+    }
+    levels(&state)
+}
+
+/// Linearly interpolate each cell from `prev` (at t = 0.0) to `next` (at t = 1.0), returning
+/// brightness levels in 0..=MAX_BRIGHTNESS. Values of `t` outside 0.0..=1.0 are clamped.
+pub fn interpolate(prev: &LEDState, next: &LEDState, t: f32) -> Levels {
+    let t = t.clamp(0.0, 1.0);
+    let mut levels = [[0u8; COL_COUNT]; ROW_COUNT];
+
+    for (row, line) in levels.iter_mut().enumerate() {
+        for (col, level) in line.iter_mut().enumerate() {
+            let from = (prev[row][col] != 0) as u8 as f32 * MAX_BRIGHTNESS as f32;
+            let to = (next[row][col] != 0) as u8 as f32 * MAX_BRIGHTNESS as f32;
+            // round to nearest (values are never negative)
+            *level = (from + (to - from) * t + 0.5) as u8;
+        }
+    }
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pair of boards where the top-left cell is born, the one right of it dies, the next one
+    /// stays lit and every other cell stays off
+    fn boards() -> (LEDState, LEDState) {
+        let mut prev = [[0; COL_COUNT]; ROW_COUNT];
+        let mut next = prev;
+        next[0][0] = 1;
+        prev[0][1] = 1;
+        (prev[0][2], next[0][2]) = (1, 1);
+        (prev, next)
+    }
+
+    #[test]
+    fn interpolation_starts_at_prev_and_ends_at_next() {
+        let (prev, next) = boards();
+        assert_eq!(interpolate(&prev, &next, 0.0), levels(&prev));
+        assert_eq!(interpolate(&prev, &next, 1.0), levels(&next));
+        // t is clamped
+        assert_eq!(interpolate(&prev, &next, -1.0), levels(&prev));
+        assert_eq!(interpolate(&prev, &next, 2.0), levels(&next));
+    }
+
+    #[test]
+    fn interpolation_meets_half_way_for_births_and_deaths() {
+        let (prev, next) = boards();
+        let half = MAX_BRIGHTNESS.div_ceil(2);
+        let mid = interpolate(&prev, &next, 0.5);
+        // birth, death and steady lit
+        assert_eq!(mid[0][..3], [half, half, MAX_BRIGHTNESS]);
+        // steady off
+        assert!(
+            mid[0][3..]
+                .iter()
+                .chain(mid[1..].iter().flatten())
+                .all(|&level| level == 0)
+        );
+    }
+
+    #[test]
+    fn empty_pulse_brightens_as_the_restart_approaches() {
+        let center = |countdown: f32, t: f32| {
+            Frame::Empty { countdown }.levels_at(t)[ROW_COUNT / 2][COL_COUNT / 2]
+        };
+        assert_eq!(center(0.0, 0.5), EMPTY_PULSE_BRIGHTNESS);
+        assert_eq!(center(1.0, 0.5), MAX_BRIGHTNESS);
+        assert_eq!(center(0.5, 0.5), 6);
+        assert_eq!(center(1.0, 0.0), 0);
+        assert_eq!(center(1.0, 1.0), 0);
+    }
+}
@@ -0,0 +1,99 @@
+//! Reporting of start-up failures.
+//!
+//! If the MB2's peripherals cannot be acquired or set up, the reason is logged over RTT and,
+//! when the LED matrix is already available, a distinctive X glyph is shown on it before the
+//! program halts. The watchdog is already running by then (except when the board itself could
+//! not be taken), so the MB2 resets after 2s and tries again.
+
+#[cfg(target_os = "none")]
+use crate::render::{Renderer, SCAN_MS};
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// What went wrong during start-up
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InitError {
+    /// `Board::take` found the peripherals already taken
+    BoardTaken,
+    /// The LSM303AGR accelerometer did not respond on the internal I2C bus
+    Accelerometer,
+}
+
+impl InitError {
+    /// A short human-readable explanation for the RTT log.
+    pub fn message(self) -> &'static str {
+        match self {
+            InitError::BoardTaken => "the MB2 peripherals were already taken",
+            InitError::Accelerometer => "the accelerometer did not respond on the internal I2C bus",
+        }
+    }
+}
+
+/// An X across the grid: both diagonals lit (on a non-square grid, their nearest cells).
+pub fn glyph() -> LEDState {
+    let mut state: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+    for (row, line) in state.iter_mut().enumerate() {
+        let col = row * (COL_COUNT - 1) / (ROW_COUNT - 1).max(1);
+        line[col] = 1;
+        line[COL_COUNT - 1 - col] = 1;
+    }
+    state
+}
+
+/// Log `error` over RTT, show the error glyph on `renderer` if there is one, and halt.
+#[cfg(target_os = "none")]
+pub fn halt(error: InitError, mut renderer: Option<&mut dyn Renderer>) -> ! {
+    rtt_target::rprintln!("start-up failed: {}, halting", error.message());
+    if let Some(renderer) = renderer.as_mut() {
+        renderer.render(&glyph());
+    }
+
+    // the display keeps refreshing the glyph from its interrupt while the CPU sleeps, unless
+    // the renderer has to scan it itself
+    loop {
+        if !renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.hold(SCAN_MS))
+        {
+            cortex_m::asm::wfi();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_glyph_is_an_x_across_the_grid() {
+        assert_eq!(
+            glyph(),
+            [
+                [1, 0, 0, 0, 1],
+                [0, 1, 0, 1, 0],
+                [0, 0, 1, 0, 0],
+                [0, 1, 0, 1, 0],
+                [1, 0, 0, 0, 1],
+            ]
+        );
+    }
+
+    #[test]
+    fn the_glyph_is_its_own_mirror_image() {
+        let state = glyph();
+        for (row, line) in state.iter().enumerate() {
+            let mut mirrored = *line;
+            mirrored.reverse();
+            assert_eq!(mirrored, *line, "row {row}");
+            assert_eq!(state[ROW_COUNT - 1 - row], *line, "row {row}");
+        }
+    }
+
+    #[test]
+    fn every_error_has_its_own_message() {
+        let errors = [InitError::BoardTaken, InitError::Accelerometer];
+        for error in errors {
+            assert!(!error.message().is_empty());
+        }
+        assert_ne!(errors[0].message(), errors[1].message());
+    }
+}
@@ -0,0 +1,68 @@
+//! Measurement of the real frame rate of the main loop.
+//!
+//! The display refreshes itself from an interrupt, but reading the accelerometer, polling the
+//! host, and updating the Game all happen within each frame before the loop waits for the next
+//! frame boundary. The loop reads a free-running 1MHz timer when that work is done and at the
+//! end of each frame, and a `FrameMeter` averages the results over roughly one second.
+
+/// Averages of the frames measured since the previous report, in microseconds.
+pub struct Report {
+    pub frame_us: u32,
+    pub logic_us: u32,
+}
+
+impl Report {
+    /// Frames per second implied by the average frame time.
+    pub fn fps(&self) -> f32 {
+        1_000_000.0 / self.frame_us.max(1) as f32
+    }
+}
+
+/// Accumulates per-frame timings and produces a `Report` every `report_frames` frames.
+pub struct FrameMeter {
+    report_frames: u32,
+    frames: u32,
+    frame_us: u32,
+    logic_us: u32,
+    last_end: Option<u32>,
+}
+
+impl FrameMeter {
+    /// Create a meter that reports once every `report_frames` measured frames.
+    pub const fn new(report_frames: u32) -> Self {
+        FrameMeter {
+            report_frames,
+            frames: 0,
+            frame_us: 0,
+            logic_us: 0,
+            last_end: None,
+        }
+    }
+
+    /// Record the end of a frame at timer reading `now`, of which `idle_us` was spent
+    /// waiting for the frame boundary. Readings may wrap around; the very
+    /// first call only starts the clock.
+    pub fn record(&mut self, now: u32, idle_us: u32) -> Option<Report> {
+        let last_end = self.last_end.replace(now)?;
+        let frame_us = now.wrapping_sub(last_end);
+
+        self.frames += 1;
+        self.frame_us = self.frame_us.saturating_add(frame_us);
+        self.logic_us = self
+            .logic_us
+            .saturating_add(frame_us.saturating_sub(idle_us));
+
+        if self.frames < self.report_frames {
+            return None;
+        }
+
+        let report = Report {
+            frame_us: self.frame_us / self.frames,
+            logic_us: self.logic_us / self.frames,
+        };
+        self.frames = 0;
+        self.frame_us = 0;
+        self.logic_us = 0;
+        Some(report)
+    }
+}
@@ -0,0 +1,2240 @@
+//! Game.rs
+//! Copyright © 2026 Sean Springer
+//! [This program is licensed under the "MIT License"]
+//! Please see the file LICENSE in the source distribution of this software for license terms.
+//!
+//! Owns all of the mutable GOL state that used to live as loose locals in main(): the board,
+//! the reset and complement timers, the random source, and the selected modes. main() reads the
+//! hardware (btns and accelerometer), calls Game::update once per frame, and renders the result,
+//! which keeps the game logic itself free of any MB2 peripherals.
+
+use crate::canvas::Canvas;
+use crate::random::RandomSource;
+use crate::{
+    A_RELEASE_MS, B_REPEAT_ENABLED, B_REPEAT_FLOOR_MS, BANNER_QUEUE_LEN, BORDER_MASK,
+    CANVAS_COL_COUNT, CANVAS_PAN_MS, CANVAS_ROW_COUNT, CENTER_MASK, CHORD_HOLD_MS, COL_COUNT,
+    COMPLEMENT_RESET_RATE_MS, CYCLE_HISTORY, CYCLE_RESTART_ENABLED, DEATH_RESET_RATE_MS,
+    DEFAULT_BRIGHTNESS, DEMO_MODE_MS, DOUBLE_PRESS_MS, EDGE_MODE, EVOLVE_GENERATIONS,
+    HEATMAP_ENABLED, INJECT_ENABLED, INJECT_THRESHOLD, LEDState, MAX_STEPS_PER_FRAME,
+    MUTATION_ENABLED, MUTATION_RATE_PERMILLE, POUR_CELLS, QUADRANT_MASK, RANDOM_DENSITY_PERCENT,
+    REFRESH_RATE_MS, REWIND_DEPTH, REWIND_ENABLED, REWIND_HOLD_MS, ROW_COUNT, ResetTimer,
+    SINGLE_STEP_HOLD_MS, STEP_INTERVAL_MS, STEPS_PER_FRAME, WALLS, age, ant, complement_masked,
+    complement_neighborhood, complement_state, cycle, elementary, generations, gravity,
+    inject_if_sparse, input, life, menu, ms_to_frames, mutate, pace, pack_board, pour_edge,
+    randomize_symmetric, render, rewind, spawn_cells, stats, supersample, symmetry, teams, text,
+    unpack_board, wireworld,
+};
+
+/// Game Struct
+///
+/// The complete game state advanced once per frame by update()
+pub struct Game<R: RandomSource> {
+    state: LEDState,
+    scratch: LEDState,
+    random_gen: R,
+    reset_timer: ResetTimer,
+    complement_timer: ResetTimer,
+    complement_policy: ComplementPolicy,
+    /// The demo mode selected at power-up (Spec 28) is running
+    demo: bool,
+    demo_timer: ResetTimer,
+    /// The name of the mode the demo mode has reached, while it scrolls across the grid, and the board
+    /// kept out from under it
+    demo_banner: Option<text::Scroller>,
+    demo_board: LEDState,
+    /// With ComplementPolicy::Preview, the board from before the complement shown while B is held
+    preview: Option<LEDState>,
+    step_mode: StepMode,
+    symmetry_mode: symmetry::SymmetryMode,
+    chord: Chord,
+    a_release: ReleaseFilter,
+    generation: u32,
+    mutation_rate: u16,
+    pace: pace::Pace,
+    steps_due: u32,
+    ages: age::Ages,
+    stats: stats::Stats,
+    aged: (u32, LEDState),
+    stepped_from: Option<LEDState>,
+    restarted: bool,
+    classified: Option<life::StepResult>,
+    shaken: bool,
+    tilt: gravity::Direction,
+    complement_mode: ComplementMode,
+    history: cycle::History<CYCLE_HISTORY>,
+    rewind: rewind::Rewind<REWIND_DEPTH>,
+    rewind_hold_frames: u32,
+    cycle_period: Option<u32>,
+    canvas: Canvas,
+    teams: teams::Teams,
+    fine: supersample::Fine,
+    gen_cells: generations::Cells,
+    wireworld: wireworld::Wireworld,
+    colony: ant::Colony,
+    waterfall: elementary::Waterfall,
+    banner: text::Queue<BANNER_QUEUE_LEN>,
+    rule_tables: [[u8; 512]; life::PRESETS.len()],
+    evolving_rule: life::Rule,
+    evolving_board: LEDState,
+    evolve_steps: u32,
+    view_origin: (usize, usize),
+    pan_timer: ResetTimer,
+    single_step: bool,
+    paused: bool,
+    logo: bool,
+    logo_was_touched: bool,
+    burst: u8,
+    menu: menu::Menu,
+    menu_board: LEDState,
+    menu_mode: StepMode,
+    density: u8,
+    edge_mode: life::EdgeMode,
+    brightness: u8,
+    heatmap: bool,
+    a_gestures: input::Gestures,
+    b_repeat: AutoRepeat,
+    b_held_frames: u32,
+    a_was_pressed: bool,
+    b_was_pressed: bool,
+}
+
+/// Impl Game
+impl<R: RandomSource> Game<R> {
+    /// fn new(R) -> Self
+    ///
+    /// Returns a new Game drawing its randomness from random_gen, starting on a random board (Spec 2)
+    /// with Conway's rules, no symmetry, and both timers in their finished() == true state
+    pub fn new(random_gen: R) -> Self {
+        let mut game = Game {
+            state: [[0; COL_COUNT]; ROW_COUNT],
+            scratch: [[0; COL_COUNT]; ROW_COUNT],
+            random_gen,
+            reset_timer: ResetTimer::new(ms_to_frames(DEATH_RESET_RATE_MS, REFRESH_RATE_MS)),
+            complement_timer: ResetTimer::new(ms_to_frames(
+                COMPLEMENT_RESET_RATE_MS,
+                REFRESH_RATE_MS,
+            )),
+            complement_policy: if B_REPEAT_ENABLED {
+                ComplementPolicy::Repeat
+            } else {
+                ComplementPolicy::Once
+            },
+            preview: None,
+            demo: false,
+            demo_timer: ResetTimer::new(ms_to_frames(DEMO_MODE_MS, REFRESH_RATE_MS)),
+            demo_banner: None,
+            demo_board: [[0; COL_COUNT]; ROW_COUNT],
+            step_mode: StepMode::Life(0),
+            symmetry_mode: symmetry::SymmetryMode::None,
+            chord: Chord::new(ms_to_frames(CHORD_HOLD_MS, REFRESH_RATE_MS)),
+            a_release: ReleaseFilter::new(ms_to_frames(A_RELEASE_MS, REFRESH_RATE_MS)),
+            generation: 0,
+            mutation_rate: MUTATION_RATE_PERMILLE,
+            pace: pace::Pace::new(STEP_INTERVAL_MS),
+            steps_due: 0,
+            ages: [[0; COL_COUNT]; ROW_COUNT],
+            stats: stats::Stats::new(),
+            aged: (0, [[0; COL_COUNT]; ROW_COUNT]),
+            stepped_from: None,
+            restarted: false,
+            classified: None,
+            shaken: false,
+            tilt: gravity::Direction::None,
+            complement_mode: ComplementMode::Full,
+            history: cycle::History::new(),
+            rewind: rewind::Rewind::new(),
+            rewind_hold_frames: ms_to_frames(REWIND_HOLD_MS, REFRESH_RATE_MS),
+            cycle_period: None,
+            canvas: Canvas::new(),
+            teams: [[teams::Team::Dead; COL_COUNT]; ROW_COUNT],
+            fine: supersample::Fine::new(),
+            gen_cells: [[0; COL_COUNT]; ROW_COUNT],
+            wireworld: wireworld::Wireworld::new(),
+            colony: ant::Colony::new(),
+            waterfall: elementary::Waterfall::new(),
+            banner: text::Queue::new(),
+            rule_tables: life::PRESETS.map(life::build_rule_table),
+            evolving_rule: life::Rule::conway(),
+            evolving_board: [[0; COL_COUNT]; ROW_COUNT],
+            evolve_steps: 0,
+            view_origin: (0, 0),
+            pan_timer: ResetTimer::new(ms_to_frames(CANVAS_PAN_MS, REFRESH_RATE_MS)),
+            single_step: false,
+            paused: false,
+            logo: false,
+            logo_was_touched: false,
+            burst: 0,
+            menu: menu::Menu::new(),
+            menu_board: [[0; COL_COUNT]; ROW_COUNT],
+            menu_mode: StepMode::Life(0),
+            density: RANDOM_DENSITY_PERCENT,
+            edge_mode: EDGE_MODE,
+            brightness: DEFAULT_BRIGHTNESS,
+            heatmap: HEATMAP_ENABLED,
+            a_gestures: input::Gestures::new(
+                ms_to_frames(SINGLE_STEP_HOLD_MS, REFRESH_RATE_MS),
+                ms_to_frames(DOUBLE_PRESS_MS, REFRESH_RATE_MS),
+            ),
+            b_repeat: AutoRepeat::new(
+                ms_to_frames(COMPLEMENT_RESET_RATE_MS, REFRESH_RATE_MS),
+                ms_to_frames(B_REPEAT_FLOOR_MS, REFRESH_RATE_MS),
+            ),
+            b_held_frames: 0,
+            a_was_pressed: false,
+            b_was_pressed: false,
+        };
+
+        game.randomize();
+        game.age_cells();
+        game.stats.record_board(&game.state, game.generation);
+        game
+    }
+
+    /// fn state(&self) -> &LEDState
+    ///
+    /// Returns the current board
+    pub fn state(&self) -> &LEDState {
+        &self.state
+    }
+
+    /// fn generation(&self) -> u32
+    ///
+    /// Returns the number of generations the current board has survived: the steps taken since it was
+    /// last randomized (by the A btn, shaking, or the dead-board restart), complemented, or loaded.
+    /// Logged over RTT with every frame by the `rtt-log` feature
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// fn restart_progress(&self) -> f32
+    ///
+    /// Returns how far the dead-board restart (Spec 5) has counted down, from 0.0 while it is not
+    /// counting to 1.0 once it is due. Used to brighten the pulse shown on an empty board (fade.rs)
+    pub fn restart_progress(&self) -> f32 {
+        self.reset_timer.progress()
+    }
+
+    /// fn stats(&self) -> &stats::Stats
+    ///
+    /// Returns the population, births, and deaths of the current board and their rolling averages over
+    /// the last stats::WINDOW steps (stats.rs)
+    pub fn stats(&self) -> &stats::Stats {
+        &self.stats
+    }
+
+    /// fn ages(&self) -> &age::Ages
+    ///
+    /// Returns how many generations each cell of the current board has been alive (0 for a dead cell,
+    /// saturating at age::MAX_AGE). Used to shade cells by age with the `age` feature (age.rs)
+    pub fn ages(&self) -> &age::Ages {
+        &self.ages
+    }
+
+    /// fn restarted(&self) -> bool
+    ///
+    /// Returns true if the last update() restarted a dead (or, with `cycle-restart`, stuck) board with a
+    /// random one (Spec 5). Used to play the collapse animation (collapse.rs) before the new board
+    pub fn restarted(&self) -> bool {
+        self.restarted
+    }
+
+    /// fn stepped_from(&self) -> Option<&LEDState>
+    ///
+    /// Returns the board as it was before the step taken by the last update(), or None if the last
+    /// update() did not take a step. Used to crossfade between generations
+    pub fn stepped_from(&self) -> Option<&LEDState> {
+        self.stepped_from.as_ref()
+    }
+
+    /// fn classified(&self) -> Option<&life::StepResult>
+    ///
+    /// Returns the born / died classification of the Life step taken by the last update(), or None
+    /// if the last update() did not take a Life step. Used to highlight births (highlight.rs)
+    pub fn classified(&self) -> Option<&life::StepResult> {
+        self.classified.as_ref()
+    }
+
+    /// fn teams(&self) -> Option<&teams::Teams>
+    ///
+    /// Returns the team of every cell while the Teams mode is selected, or None otherwise. Used to
+    /// draw the two teams at different brightness (teams.rs)
+    pub fn teams(&self) -> Option<&teams::Teams> {
+        (self.step_mode == StepMode::Teams).then_some(&self.teams)
+    }
+
+    /// fn supersampled(&self) -> Option<&supersample::Fine>
+    ///
+    /// Returns the fine board while the Supersample mode is selected, or None otherwise. Used to draw
+    /// each LED as bright as its block of the fine board is full (supersample.rs)
+    pub fn supersampled(&self) -> Option<&supersample::Fine> {
+        (self.step_mode == StepMode::Supersample).then_some(&self.fine)
+    }
+
+    /// fn generations(&self) -> Option<(&generations::Cells, generations::GenRule)>
+    ///
+    /// Returns the cell states and the rule while a Generations mode is selected, or None otherwise.
+    /// Used to draw dying cells dimmer (generations.rs)
+    pub fn generations(&self) -> Option<(&generations::Cells, generations::GenRule)> {
+        match self.step_mode {
+            StepMode::Generations(i) => Some((&self.gen_cells, generations::PRESETS[i])),
+            _ => None,
+        }
+    }
+
+    /// fn wireworld(&self) -> Option<&wireworld::Wireworld>
+    ///
+    /// Returns the circuit and its editor while the Wireworld mode is selected, or None otherwise.
+    /// Used to draw each kind of cell (and the cursor) at its own brightness (wireworld.rs)
+    pub fn wireworld(&self) -> Option<&wireworld::Wireworld> {
+        (self.step_mode == StepMode::Wireworld).then_some(&self.wireworld)
+    }
+
+    /// fn colony(&self) -> Option<&ant::Colony>
+    ///
+    /// Returns the grid and its ants while the Ants mode is selected, or None otherwise. Used to draw
+    /// the ants over the dimmed cells (ant.rs)
+    pub fn colony(&self) -> Option<&ant::Colony> {
+        (self.step_mode == StepMode::Ants).then_some(&self.colony)
+    }
+
+    /// fn show_hud(&self) -> bool
+    ///
+    /// Returns true while the A+B chord has been held long enough to request the population HUD
+    pub fn show_hud(&self) -> bool {
+        self.chord.long_held()
+    }
+
+    /// fn save_requested(&self) -> bool
+    ///
+    /// Returns true on the single frame an "A first" chord reaches the long hold duration, which asks
+    /// main() to save the board to flash (persist.rs)
+    pub fn save_requested(&self) -> bool {
+        // the board is hidden under the menu's text while the settings menu is open
+        self.chord.a_first() && self.chord.long_hold_started() && !self.menu.is_open()
+    }
+
+    /// fn load(&mut self, LEDState)
+    ///
+    /// Replaces the board (e.g. with one pushed by the host) as if it had just been randomized
+    pub fn load(&mut self, state: LEDState) {
+        self.reset_timer.reset();
+        self.state = state;
+        self.generation = 0;
+        self.forget_history();
+        self.rewind.clear();
+        self.age_cells();
+    }
+
+    /// fn set_mutation_rate(&mut self, u16)
+    ///
+    /// Sets the chance (in permille) of each LED flipping after a Life step with the `mutation` feature,
+    /// in place of MUTATION_RATE_PERMILLE
+    pub fn set_mutation_rate(&mut self, rate_permille: u16) {
+        self.mutation_rate = rate_permille;
+    }
+
+    /// fn set_rule(&mut self, life::Rule)
+    ///
+    /// Selects the Evolving mode (Spec 21) on a new random board, starting from rule (e.g. one pushed by the
+    /// host in B/S notation) in place of Conway's rules, and scrolls the rule across the grid
+    pub fn set_rule(&mut self, rule: life::Rule) {
+        self.step_mode = StepMode::Evolving;
+        self.banner.clear();
+        self.forget_history();
+        self.rewind.clear();
+        self.evolving_rule = rule;
+        self.evolve_steps = 0;
+        self.randomize_evolving();
+        self.banner
+            .show(text::Scroller::text(format_args!("{}", self.evolving_rule)));
+    }
+
+    /// fn set_step_interval(&mut self, u32)
+    ///
+    /// Sets the time between generations (Spec 24), in place of STEP_INTERVAL_MS
+    pub fn set_step_interval(&mut self, step_ms: u32) {
+        self.pace.set_step_ms(step_ms);
+    }
+
+    /// fn brightness(&self) -> u8
+    ///
+    /// Returns the display brightness setting (1..=render::BRIGHTNESS_LEVELS) chosen in the settings menu
+    /// or over RTT, which main() applies to the display (Spec 23)
+    pub fn brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    /// fn show_heatmap(&self) -> bool
+    ///
+    /// Returns true while the heatmap is switched on in the settings menu (on at power-up with the
+    /// `heatmap` feature), which main() then draws in place of the generation crossfade
+    pub fn show_heatmap(&self) -> bool {
+        self.heatmap
+    }
+
+    /// fn set_brightness(&mut self, u8)
+    ///
+    /// Sets the display brightness setting (clamped to 1..=render::BRIGHTNESS_LEVELS), e.g. from the host
+    pub fn set_brightness(&mut self, setting: u8) {
+        self.brightness = setting.clamp(1, render::BRIGHTNESS_LEVELS);
+    }
+
+    /// fn set_motion(&mut self, bool, gravity::Direction)
+    ///
+    /// Records the accelerometer derived inputs for the next update(): whether the MB2 is being
+    /// shaken (treated like the A btn) and which way it is tilted (used by the gravity and canvas modes)
+    pub fn set_motion(&mut self, shaken: bool, tilt: gravity::Direction) {
+        self.shaken = shaken;
+        self.tilt = tilt;
+    }
+
+    /// fn set_logo(&mut self, bool)
+    ///
+    /// Records whether the touch logo (touch.rs) is touched for the next update(). A new touch pauses or
+    /// resumes the board like a double A press (Spec 25)
+    pub fn set_logo(&mut self, touched: bool) {
+        self.logo = touched;
+    }
+
+    /// fn set_burst(&mut self, u8)
+    ///
+    /// Records how many random live cells a loud sound picked up by the microphone (sound.rs) spawns in
+    /// the next update() (Spec 27)
+    pub fn set_burst(&mut self, cells: u8) {
+        self.burst = cells;
+    }
+
+    /// fn start_demo(&mut self)
+    ///
+    /// Starts the demo mode (Spec 28): every DEMO_MODE_MS the next step mode is selected and its name
+    /// scrolled, until any input ends it
+    pub fn start_demo(&mut self) {
+        self.demo = true;
+        self.demo_timer.reset();
+        self.announce_demo_mode();
+    }
+
+    /// fn update(&mut self, bool, bool) -> &LEDState
+    ///
+    /// Advances the game by one frame given the current A and B btn states, returning the board to
+    /// display next. Wherever a mode steps, it takes the number of steps that have fallen due this
+    /// frame at the step interval of Spec 24: none on some frames when it is longer than a frame, several
+    /// when it is shorter. The A btn first passes through a ReleaseFilter so that a bouncing release is not
+    /// mistaken for another press, and its presses outside of a chord are classified as short, long, or
+    /// double presses (input::Gestures). In the demo mode (Spec 28) the next step mode is selected every
+    /// DEMO_MODE_MS and its name scrolled across the grid, the board waiting until it has scrolled by,
+    /// before any of:
+    /// 1. A+B chord: a tap opens or closes the settings menu (or advances the symmetry mode if A was held
+    ///    first, or the complement mode if B was held first), a long hold requests the population HUD
+    /// 2. Settings menu: the board is frozen under the menu, each new A press shows the next setting and
+    ///    each new B press changes the setting shown (Spec 7)
+    /// 3. Canvas mode: new A / B presses and tilting pan the window, otherwise the virtual board steps (Spec 13)
+    /// 4. Teams mode: held A / B btns re-seed their player's side, otherwise the teams step
+    /// 5. Supersample and Generations modes: a held A btn (or shaking) re-randomizes the board,
+    ///    otherwise it steps (Specs 16 and 17)
+    /// 6. Wireworld mode: a long A press switches between running and editing the circuit, otherwise a
+    ///    short A press and B edit it or B fires an electron into it (Spec 18)
+    /// 7. Ants mode: new A / B presses add or remove an ant, and the ants take a step (Spec 19)
+    /// 8. Elementary mode: a new A press selects the next rule and scrolls it across the grid,
+    ///    a new B press re-seeds the top row, otherwise the waterfall steps (Spec 20)
+    /// 9. Evolving mode: a held A btn (or shaking) re-randomizes the board, otherwise it steps and its
+    ///    rule mutates every EVOLVE_GENERATIONS generations, pausing while the new rule scrolls by (Spec 21)
+    ///    The Pour mode alike, except that it steps, pouring cells in at the lowest edge
+    ///    while the MB2 is tilted (Spec 26)
+    /// 10. Restart scroll: the generations survived by a board restarted by Spec 5 scroll across the grid
+    ///     before the restart, which any btn (or shaking) brings forward
+    /// 11. Single-step mode: frozen, each new A press takes one step and releasing B exits
+    /// 12. Paused: frozen until a double A press or a new logo touch resumes play (Spec 25)
+    /// 13. A long A press: enter single-step mode. A double A press or a new logo touch: pause (Spec 25)
+    /// 14. A btn (or shaking): re-randomize every frame (Spec 3)
+    /// 15. B btn: complement (fully, by neighborhood, or masked) once per press, every 500ms while held, or
+    ///     only while held, as the complement policy of the settings menu says (Spec 4). With
+    ///     the `rewind` feature, B held for REWIND_HOLD_MS steps back one Life generation per frame instead
+    /// 16. Terminal board (or, with the `cycle-restart` feature, one caught in a cycle): after 500ms without
+    ///     input, scroll its generation count and then restart (Spec 5)
+    /// 17. Otherwise: take a step of the selected rule / gravity mode (Spec 6), then mutate a Life board
+    ///     if the `mutation` feature is enabled and add a glider to a sparse one if `inject` is. With the
+    ///     `turbo` feature this is repeated STEPS_PER_FRAME times, stopping early at a terminal board
+    pub fn update(&mut self, btn_a: bool, btn_b: bool) -> &LEDState {
+        self.steps_due = self.pace.tick(REFRESH_RATE_MS, MAX_STEPS_PER_FRAME);
+        self.stepped_from = None;
+        self.classified = None;
+        self.restarted = false;
+        let btn_a = self.a_release.update(btn_a);
+        self.chord.update(btn_a, btn_b);
+        self.update_demo(btn_a || btn_b);
+        // Spec 4: a previewed complement lasts only as long as the B press that made it
+        if (!btn_b || self.chord.active())
+            && let Some(board) = self.preview.take()
+        {
+            self.state = board;
+        }
+        // a press that becomes part of a chord is not also a gesture of its own
+        let a_gesture = if self.chord.active() {
+            self.a_gestures.cancel();
+            None
+        } else {
+            self.a_gestures.update(btn_a)
+        };
+        let btns = Btns {
+            a: btn_a,
+            b: btn_b,
+            a_pressed: btn_a && !self.a_was_pressed,
+            b_pressed: btn_b && !self.b_was_pressed,
+            a_gesture,
+            // Spec 25: a double A press or a new touch of the logo pauses or resumes the board
+            pause_toggled: a_gesture == Some(input::Gesture::Double)
+                || (self.logo && !self.logo_was_touched),
+        };
+        let demo_frame = self.next_demo_frame();
+
+        if let Some(frame) = demo_frame {
+            // Spec 28: the demo's announcement of the mode reached scrolls over the waiting board
+            self.reset_timer.reset();
+            self.state = frame;
+        } else if self.chord.active() {
+            self.update_chord();
+        } else if self.menu.is_open() {
+            self.update_menu(btns);
+        } else {
+            match self.step_mode {
+                StepMode::Canvas => self.update_canvas(btns),
+                StepMode::Teams => self.update_teams(btns),
+                StepMode::Supersample | StepMode::Generations(_) => self.update_reseeding(btns),
+                StepMode::Wireworld => self.update_wireworld(btns),
+                StepMode::Ants => self.update_ants(btns),
+                StepMode::Elementary => self.update_elementary(btns),
+                StepMode::Evolving => self.update_evolving(btns),
+                StepMode::Pour => self.update_pour(btns),
+                StepMode::Life(_) | StepMode::Gravity => self.update_life(btns),
+            }
+        }
+
+        // Spec 27: a loud sound spawns a burst of live cells into a Life or Pour board in play (not one
+        // frozen, scrolling text, or under the menu), which may also bring a dying board back
+        if self.burst > 0
+            && matches!(self.step_mode, StepMode::Life(_) | StepMode::Pour)
+            && self.banner.is_empty()
+            && demo_frame.is_none()
+            && !(self.menu.is_open() || self.paused || self.single_step)
+        {
+            spawn_cells(&mut self.random_gen, &mut self.state, self.burst);
+            self.forget_history();
+        }
+
+        // walls (the `walls` feature) keep their fixed state whatever randomized, complemented,
+        // mutated, or loaded the board. They only apply to the Life rules: gravity would
+        // otherwise pile grains onto them and the canvas has no walls of its own. The demo's
+        // announcement is left whole, and the walls return with its board
+        if matches!(self.step_mode, StepMode::Life(_)) && demo_frame.is_none() {
+            life::apply_walls(&mut self.state, &WALLS);
+        }
+
+        // tick complement_timer: at least 500 ms between complement action
+        self.complement_timer.tick(false);
+
+        // track btn history for edge detection, and the B btn's auto-repeat and rewind
+        self.b_held_frames = if btn_b && !self.chord.active() {
+            self.b_held_frames.saturating_add(1)
+        } else {
+            0
+        };
+        self.a_was_pressed = btn_a;
+        self.b_was_pressed = btn_b;
+        self.logo_was_touched = self.logo;
+        self.age_cells();
+        match self.stepped_from {
+            Some(before) => self
+                .stats
+                .record_step(&before, &self.state, self.generation),
+            None => self.stats.record_board(&self.state, self.generation),
+        }
+
+        &self.state
+    }
+
+    /// fn update_demo(&mut self, bool)
+    ///
+    /// Spec 28: moves the demo mode on to the next step mode every DEMO_MODE_MS, until any btn (pressed),
+    /// logo touch, or shake hands the game over to the player
+    fn update_demo(&mut self, pressed: bool) {
+        if !self.demo {
+            return;
+        }
+        if pressed || self.logo || self.shaken {
+            self.demo = false;
+        } else if self.demo_timer.tick(true) {
+            self.select_next_mode();
+            self.announce_demo_mode();
+        }
+    }
+
+    /// fn update_chord(&mut self)
+    ///
+    /// Handles the A+B chord: a tap advances the symmetry mode if A was held first, or the complement mode
+    /// if B was held first, and otherwise opens or closes the settings menu
+    fn update_chord(&mut self) {
+        self.reset_timer.reset();
+        if !self.chord.tapped() {
+            return;
+        }
+        if self.chord.a_first() {
+            self.symmetry_mode = self.symmetry_mode.next();
+        } else if self.chord.b_first() {
+            self.complement_mode = self.complement_mode.next();
+        } else if self.menu.toggle() {
+            // the board is kept out from under the menu's text while it is open
+            self.menu_board = self.state;
+            self.menu_mode = self.step_mode;
+            self.show_menu_item();
+        } else {
+            self.banner.clear();
+            self.state = self.menu_board;
+            // a mode picked in the menu is announced once the menu has closed
+            if self.step_mode != self.menu_mode {
+                self.announce_mode();
+            }
+        }
+    }
+
+    /// fn update_menu(&mut self, Btns)
+    ///
+    /// Settings menu (Spec 7): nothing steps while it is open. Each new A press shows the next setting and
+    /// each new B press changes the setting shown, whose text loops until the next press
+    fn update_menu(&mut self, btns: Btns) {
+        self.reset_timer.reset();
+        self.state = self.menu_board;
+        if btns.a_pressed {
+            self.menu.next_item();
+            self.show_menu_item();
+        }
+        if btns.b_pressed {
+            self.change_setting();
+            self.show_menu_item();
+        }
+        self.menu_board = self.state;
+        if self.banner.is_empty() {
+            self.show_menu_item();
+        }
+        self.state = self.banner.next_frame().unwrap_or(self.menu_board);
+    }
+
+    /// fn update_canvas(&mut self, Btns)
+    ///
+    /// Canvas mode (Spec 13): the btns pan the window across the virtual board instead, each new A press
+    /// one column right and each new B press one row down (wrapping around), otherwise the virtual board
+    /// steps
+    fn update_canvas(&mut self, btns: Btns) {
+        if btns.a_pressed {
+            self.view_origin.1 = (self.view_origin.1 + 1) % CANVAS_COL_COUNT;
+        }
+        if btns.b_pressed {
+            self.view_origin.0 = (self.view_origin.0 + 1) % CANVAS_ROW_COUNT;
+        }
+        // tilting pans it one cell per CANVAS_PAN_MS towards the lowered edge, the first straight away
+        if self.tilt != gravity::Direction::None && self.pan_timer.finished() {
+            self.pan_timer.reset();
+            self.pan_view(self.tilt);
+        }
+        self.pan_timer.tick(false);
+
+        if self.shaken {
+            self.reset_timer.reset();
+            self.randomize_canvas();
+        } else if self.canvas.done() {
+            // Spec 5 for the whole canvas rather than just the visible window
+            if self.reset_timer.tick(true) {
+                self.randomize_canvas();
+            }
+        } else {
+            self.reset_timer.reset();
+            self.step_paced();
+        }
+        self.state = self.canvas.view(self.view_origin);
+    }
+
+    /// fn update_teams(&mut self, Btns)
+    ///
+    /// Teams mode (Spec 14): while a btn is held its player re-seeds their side of the board every frame
+    /// (A the left with team 0, B the right with team 1), shaking re-seeds the whole board with both teams
+    /// mixed at random, otherwise the teams step
+    fn update_teams(&mut self, btns: Btns) {
+        if self.shaken {
+            self.reset_timer.reset();
+            teams::seed_mixed(&mut self.random_gen, &mut self.teams);
+            self.state = teams::alive(&self.teams);
+            self.generation = 0;
+        } else if btns.a || btns.b {
+            self.reset_timer.reset();
+            self.seed_teams(btns.a, btns.b);
+        } else if life::done(&self.state) {
+            // Spec 5: once both teams have died out, restart with both sides re-seeded
+            if self.reset_timer.tick(true) {
+                self.seed_teams(true, true);
+            }
+        } else {
+            self.reset_timer.reset();
+            self.step_paced();
+        }
+    }
+
+    /// fn update_reseeding(&mut self, Btns)
+    ///
+    /// Supersample and Generations modes (Specs 16 and 17): A (or shaking) re-randomizes the board every
+    /// frame like Spec 3, and it restarts once every one of its cells has died like Spec 5, otherwise it
+    /// steps
+    fn update_reseeding(&mut self, btns: Btns) {
+        if btns.a || self.shaken {
+            self.reset_timer.reset();
+            self.reseed();
+        } else if life::done(&self.state) {
+            if self.reset_timer.tick(true) {
+                self.reseed();
+            }
+        } else {
+            self.reset_timer.reset();
+            self.step_paced();
+        }
+    }
+
+    /// fn update_wireworld(&mut self, Btns)
+    ///
+    /// Wireworld mode (Spec 18): holding A for 1s switches between running and editing the circuit (an
+    /// empty circuit is left to be edited rather than restarted), and shaking restores the clock circuit.
+    /// While editing, tapping A moves the cursor and pressing B changes the cell under it. While running,
+    /// pressing B fires an electron from the cursor
+    fn update_wireworld(&mut self, btns: Btns) {
+        self.reset_timer.reset();
+        if self.shaken {
+            self.wireworld = wireworld::Wireworld::new();
+            self.generation = 0;
+        } else if btns.a_gesture == Some(input::Gesture::Long) {
+            self.wireworld.toggle_running();
+        } else if self.wireworld.running() {
+            if btns.b_pressed {
+                self.wireworld.fire();
+            }
+            self.step_paced();
+        } else {
+            if btns.a_gesture == Some(input::Gesture::Short) {
+                self.wireworld.move_cursor();
+            }
+            if btns.b_pressed {
+                self.wireworld.edit();
+            }
+        }
+        self.wireworld.tick();
+        self.state = self.wireworld.occupied();
+    }
+
+    /// fn update_ants(&mut self, Btns)
+    ///
+    /// Ants mode (Spec 19): each new A press adds an ant, each new B press removes the newest one, and
+    /// shaking resets the grid to a single ant. The ants never die out by themselves, so there is no
+    /// restart
+    fn update_ants(&mut self, btns: Btns) {
+        self.reset_timer.reset();
+        if self.shaken {
+            self.colony = ant::Colony::new();
+            self.generation = 0;
+        } else {
+            if btns.a_pressed {
+                self.colony.add_random(&mut self.random_gen);
+            }
+            if btns.b_pressed {
+                self.colony.remove_last();
+            }
+            self.step_paced();
+        }
+        self.state = *self.colony.grid();
+    }
+
+    /// fn update_elementary(&mut self, Btns)
+    ///
+    /// Elementary mode (Spec 20): each new A press selects the next rule and scrolls it ("RULE 110")
+    /// across the grid (the waterfall waits until it has scrolled off), each new B press (or shaking)
+    /// re-seeds the top row. A waterfall that has died out stays dark until re-seeded
+    fn update_elementary(&mut self, btns: Btns) {
+        self.reset_timer.reset();
+        if btns.a_pressed {
+            self.waterfall.next_rule();
+            self.banner.show(text::Scroller::text(format_args!(
+                "RULE {}",
+                self.waterfall.rule()
+            )));
+        }
+        if btns.b_pressed || self.shaken {
+            self.waterfall.seed(&mut self.random_gen);
+            self.state = *self.waterfall.rows();
+            self.generation = 0;
+        }
+        match self.banner.next_frame() {
+            Some(frame) => self.state = frame,
+            None => self.step_paced(),
+        }
+    }
+
+    /// fn update_evolving(&mut self, Btns)
+    ///
+    /// Evolving mode (Spec 21): A (or shaking) re-randomizes the board every frame like Spec 3, keeping
+    /// the rule, and it restarts once terminal under the current rule like Spec 5. Every
+    /// EVOLVE_GENERATIONS steps the rule mutates, and the board waits while the new rule scrolls across
+    /// the grid
+    fn update_evolving(&mut self, btns: Btns) {
+        if btns.a || self.shaken {
+            self.reset_timer.reset();
+            self.randomize_evolving();
+        } else if !self.banner.is_empty() {
+            self.reset_timer.reset();
+        } else if life::is_terminal(
+            &self.evolving_board,
+            &mut self.scratch,
+            self.evolving_rule,
+            self.edge_mode,
+            &life::NO_WALLS,
+        ) {
+            if self.reset_timer.tick(true) {
+                self.randomize_evolving();
+            }
+        } else {
+            self.reset_timer.reset();
+            self.step_paced();
+            self.evolve_steps += self.steps_due;
+            if self.evolve_steps >= EVOLVE_GENERATIONS {
+                self.evolve_steps = 0;
+                self.evolve_rule();
+            }
+        }
+        self.state = self.banner.next_frame().unwrap_or(self.evolving_board);
+    }
+
+    /// fn update_pour(&mut self, Btns)
+    ///
+    /// Pour mode (Spec 26): Conway's rules, and every frame the MB2 is tilted POUR_CELLS random cells are
+    /// lit along the lowest edge after the step, so a dying soup can be kept alive by hand. A (or shaking)
+    /// re-randomizes the board like Spec 3, and one that has died out restarts like Spec 5 (without the
+    /// scroll) once it has been left untilted for 500ms
+    fn update_pour(&mut self, btns: Btns) {
+        if btns.a || self.shaken {
+            self.reset_timer.reset();
+            self.randomize();
+        } else if self.tilt != gravity::Direction::None {
+            self.reset_timer.reset();
+            self.step_paced();
+            pour_edge(&mut self.random_gen, &mut self.state, self.tilt, POUR_CELLS);
+        } else if life::done(&self.state) {
+            if self.reset_timer.tick(true) {
+                self.randomize();
+            }
+        } else {
+            self.reset_timer.reset();
+            self.step_paced();
+        }
+    }
+
+    /// fn update_life(&mut self, Btns)
+    ///
+    /// The Life rules and the gravity mode: items 10 to 17 of update(), in that order of precedence
+    fn update_life(&mut self, btns: Btns) {
+        if !self.banner.is_empty() {
+            self.update_restart_scroll(btns);
+        } else if self.single_step {
+            self.update_single_step(btns);
+        } else if self.paused {
+            self.update_paused(btns);
+        } else if btns.a_gesture == Some(input::Gesture::Long) {
+            // a long A press freezes the (last randomized) board in single-step mode
+            self.reset_timer.reset();
+            self.single_step = true;
+        } else if btns.pause_toggled {
+            // Spec 25: a double A press pauses on the board randomized by its first press (a logo
+            // touch on the board as it is)
+            self.reset_timer.reset();
+            self.paused = true;
+        } else if btns.a || self.shaken {
+            //Spec 3: while btn A pressed (or shaking), randomize every frame
+            self.reset_timer.reset();
+            self.randomize();
+        } else if REWIND_ENABLED && btns.b && self.b_held_frames + 1 >= self.rewind_hold_frames {
+            self.rewind_step();
+        } else if btns.b {
+            self.complement_held();
+        } else if self.stalled() {
+            // Spec 5: if all cells "dead" (or about to be), count 500 ms. If no user input after 500 ms, scroll the
+            // number of generations the board survived and then randomize state (see update_restart_scroll).
+            // With the `cycle-restart` feature a board repeating itself is treated as "dead" too
+            if self.reset_timer.tick(true) {
+                self.banner
+                    .show(text::Scroller::number(self.stats.current().generation));
+            }
+        } else {
+            // Spec 6: If not A btn press, not B btn press, and not all cells "dead", take GOL step
+            // (or a step of whichever rule / gravity mode has been selected in the settings menu)
+            // With the `turbo` feature several generations are computed before the next is shown
+            self.reset_timer.reset();
+            self.step_burst(self.steps_due * STEPS_PER_FRAME);
+        }
+    }
+
+    /// fn update_restart_scroll(&mut self, Btns)
+    ///
+    /// Spec 5, continued: a restarted board's generation count is scrolling across the grid. Once it has
+    /// scrolled off (or straight away on any input) the new random board appears
+    fn update_restart_scroll(&mut self, btns: Btns) {
+        self.reset_timer.reset();
+        let frame = if btns.a || btns.b || self.shaken {
+            None
+        } else {
+            self.banner.next_frame()
+        };
+        match frame {
+            Some(frame) => self.state = frame,
+            None => {
+                self.banner.clear();
+                self.randomize();
+                self.restarted = true;
+            }
+        }
+    }
+
+    /// fn update_single_step(&mut self, Btns)
+    ///
+    /// Single-step mode: the board is frozen (so the dead-state restart is also suppressed), each new A
+    /// press advances one generation and releasing B returns to normal play
+    fn update_single_step(&mut self, btns: Btns) {
+        self.reset_timer.reset();
+        if btns.a_pressed {
+            self.step();
+        } else if !btns.b && self.b_was_pressed {
+            self.single_step = false;
+        }
+    }
+
+    /// fn update_paused(&mut self, Btns)
+    ///
+    /// Spec 25: the board is frozen (the dead-state restart too) until A is double pressed again or the
+    /// logo touched
+    fn update_paused(&mut self, btns: Btns) {
+        self.reset_timer.reset();
+        if btns.pause_toggled {
+            self.paused = false;
+        }
+    }
+
+    /// fn rewind_step(&mut self)
+    ///
+    /// With the `rewind` feature, once B has been held for REWIND_HOLD_MS each frame steps back one Life
+    /// generation (past any complement the press made), until the history runs out. Releasing B resumes
+    /// forward play from the board reached
+    fn rewind_step(&mut self) {
+        self.reset_timer.reset();
+        // the rewound board is kept on release rather than a previewed one restored
+        self.preview = None;
+        if let Some(bits) = self.rewind.pop() {
+            self.stepped_from = Some(self.state);
+            self.state = unpack_board(bits);
+            self.generation = self.generation.saturating_sub(1);
+            self.forget_history();
+        }
+    }
+
+    /// fn complement_held(&mut self)
+    ///
+    /// Spec 4: If B btn pressed, complement state, then ignore B btn for 500 ms. What holding B does is the
+    /// complement policy's: nothing more (Once), complement again every 500 ms, or with the `b-repeat`
+    /// feature at an accelerating rate (Repeat), or show the complement only until B is released (Preview)
+    fn complement_held(&mut self) {
+        self.reset_timer.reset();
+        let held_frames = self.b_held_frames + 1;
+        let complement = match self.complement_policy {
+            ComplementPolicy::Once | ComplementPolicy::Preview => {
+                held_frames == 1 && self.complement_timer.finished()
+            }
+            ComplementPolicy::Repeat if B_REPEAT_ENABLED => {
+                self.b_repeat.update(held_frames)
+                    && (held_frames > 1 || self.complement_timer.finished())
+            }
+            ComplementPolicy::Repeat => self.complement_timer.finished(),
+        };
+        if !complement {
+            return;
+        }
+        if self.complement_policy == ComplementPolicy::Preview {
+            self.preview = Some(self.state);
+        }
+        match self.complement_mode {
+            ComplementMode::Full => complement_state(&mut self.state),
+            ComplementMode::Neighborhood => {
+                complement_neighborhood(&mut self.state, self.edge_mode)
+            }
+            ComplementMode::Center => complement_masked(&mut self.state, &CENTER_MASK),
+            ComplementMode::Quadrant => complement_masked(&mut self.state, &QUADRANT_MASK),
+            ComplementMode::Border => complement_masked(&mut self.state, &BORDER_MASK),
+        }
+        // a complemented board is a new board, so it starts counting generations afresh
+        // (a previewed one is not kept)
+        if self.preview.is_none() {
+            self.generation = 0;
+            self.forget_history();
+        }
+        self.complement_timer.reset();
+    }
+
+    /// fn age_cells(&mut self)
+    ///
+    /// Ages the cells by one generation if the board has changed since they were last aged (it was
+    /// stepped, or replaced by a new board), so that they do not keep aging while the board sits
+    /// paused in single-step mode
+    fn age_cells(&mut self) {
+        if self.aged != (self.generation, self.state) {
+            self.ages = age::update(&self.ages, &self.state);
+            self.aged = (self.generation, self.state);
+        }
+    }
+
+    /// fn advance(&mut self)
+    ///
+    /// Takes the normal-play step of Spec 6: one step of the selected rule / gravity mode, then any
+    /// mutation or glider injection of a Life board, recording the result for cycle detection
+    fn advance(&mut self) {
+        // with the `rewind` feature, remember each Life board before it is stepped, to step back to
+        if REWIND_ENABLED && matches!(self.step_mode, StepMode::Life(_)) {
+            self.rewind.push(pack_board(&self.state));
+        }
+        self.step();
+
+        // with the `mutation` feature, a Life step may also flip a few random cells
+        if MUTATION_ENABLED && matches!(self.step_mode, StepMode::Life(_)) {
+            mutate(&mut self.random_gen, &mut self.state, self.mutation_rate);
+        }
+        // with the `inject` feature, a sparse Life board is reinvigorated with a glider
+        if INJECT_ENABLED && matches!(self.step_mode, StepMode::Life(_)) {
+            inject_if_sparse(
+                &mut self.random_gen,
+                &mut self.state,
+                INJECT_THRESHOLD,
+                self.edge_mode,
+            );
+        }
+        if matches!(self.step_mode, StepMode::Life(_)) {
+            // mutation and injection must not touch the walls before the next internal step
+            life::apply_walls(&mut self.state, &WALLS);
+
+            // remember each Life generation, to spot it coming round again
+            let hash = pack_board(&self.state);
+            self.cycle_period = cycle::detect_cycle(self.history.as_slice(), hash);
+            self.history.push(hash);
+        }
+    }
+
+    /// fn step_burst(&mut self, u32) -> u32
+    ///
+    /// Advances the board up to max_steps times before it is next shown, returning how many steps were
+    /// taken. The first step is always taken, and each further one only while the board is not stalled,
+    /// so that a board dying mid-burst is shown (and restarted by Spec 5) rather than stepped past
+    fn step_burst(&mut self, max_steps: u32) -> u32 {
+        let shown = self.state;
+        let mut steps = 0;
+        while steps < max_steps && (steps == 0 || !self.stalled()) {
+            self.advance();
+            steps += 1;
+        }
+
+        // crossfade from the board last shown rather than the last internal step, whose
+        // births and deaths alone would not match it
+        if steps > 1 {
+            self.stepped_from = Some(shown);
+            self.classified = None;
+        }
+        steps
+    }
+
+    /// fn stalled(&mut self) -> bool
+    ///
+    /// Returns true if the board should wait out the death timer instead of stepping (Spec 5): it is
+    /// terminal (see is_terminal) or, with the `cycle-restart` feature, caught in a cycle
+    fn stalled(&mut self) -> bool {
+        (CYCLE_RESTART_ENABLED && self.cycle_period.is_some())
+            || is_terminal(
+                self.step_mode,
+                &self.state,
+                &mut self.scratch,
+                self.edge_mode,
+                &WALLS,
+            )
+    }
+
+    /// fn pan_view(&mut self, gravity::Direction)
+    ///
+    /// Moves the Canvas mode's window one cell towards direction, wrapping around the virtual board
+    fn pan_view(&mut self, direction: gravity::Direction) {
+        let (row, col) = &mut self.view_origin;
+        match direction {
+            gravity::Direction::Up => *row = (*row + CANVAS_ROW_COUNT - 1) % CANVAS_ROW_COUNT,
+            gravity::Direction::Down => *row = (*row + 1) % CANVAS_ROW_COUNT,
+            gravity::Direction::Left => *col = (*col + CANVAS_COL_COUNT - 1) % CANVAS_COL_COUNT,
+            gravity::Direction::Right => *col = (*col + 1) % CANVAS_COL_COUNT,
+            gravity::Direction::None => {}
+        }
+    }
+
+    /// fn step_paced(&mut self)
+    ///
+    /// Takes the steps that have fallen due this frame (Spec 24), crossfading from the board shown before
+    /// the first of them
+    fn step_paced(&mut self) {
+        let shown = self.state;
+        for _ in 0..self.steps_due {
+            self.step();
+        }
+        if self.steps_due > 1 {
+            self.stepped_from = Some(shown);
+            self.classified = None;
+        }
+    }
+
+    /// fn step(&mut self)
+    ///
+    /// Takes one step of the selected rule / gravity mode
+    fn step(&mut self) {
+        self.stepped_from = Some(self.state);
+        match self.step_mode {
+            StepMode::Life(i) => {
+                // the bitboard is the quickest step on the torus (bench.rs), the lookup table elsewhere
+                let result = if self.edge_mode == life::EdgeMode::Toroidal {
+                    life::step_classified(&self.state, life::PRESETS[i], self.edge_mode, &WALLS)
+                } else {
+                    life::step_classified_lut(
+                        &self.state,
+                        &self.rule_tables[i],
+                        self.edge_mode,
+                        &WALLS,
+                    )
+                };
+                self.state = result.next;
+                self.classified = Some(result);
+            }
+            StepMode::Gravity => gravity::settle(
+                &mut self.state,
+                self.tilt,
+                self.generation.is_multiple_of(2),
+            ),
+            StepMode::Canvas => {
+                self.canvas.step(life::PRESETS[0]);
+                self.state = self.canvas.view(self.view_origin);
+            }
+            StepMode::Teams => {
+                self.teams = teams::step(&self.teams, life::PRESETS[0], self.edge_mode);
+                self.state = teams::alive(&self.teams);
+            }
+            StepMode::Supersample => {
+                self.fine.step(life::PRESETS[0]);
+                self.state = supersample::coarse(&self.fine);
+            }
+            StepMode::Ants => {
+                self.colony.step();
+                self.state = *self.colony.grid();
+            }
+            StepMode::Pour => {
+                let result = life::step_classified(
+                    &self.state,
+                    life::PRESETS[0],
+                    self.edge_mode,
+                    &life::NO_WALLS,
+                );
+                self.state = result.next;
+                self.classified = Some(result);
+            }
+            StepMode::Evolving => {
+                let result = life::step_classified(
+                    &self.evolving_board,
+                    self.evolving_rule,
+                    self.edge_mode,
+                    &life::NO_WALLS,
+                );
+                self.evolving_board = result.next;
+                self.state = result.next;
+                self.classified = Some(result);
+            }
+            StepMode::Elementary => {
+                self.waterfall.step();
+                self.state = *self.waterfall.rows();
+            }
+            StepMode::Wireworld => {
+                self.wireworld.step();
+                self.state = self.wireworld.occupied();
+            }
+            StepMode::Generations(i) => {
+                self.gen_cells =
+                    generations::step(&self.gen_cells, generations::PRESETS[i], self.edge_mode);
+                self.state = generations::occupied(&self.gen_cells);
+            }
+        }
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// fn select_next_mode(&mut self)
+    ///
+    /// Advances the step mode (the settings menu's rule setting) and sets up the board of the mode selected
+    fn select_next_mode(&mut self) {
+        self.step_mode = self.step_mode.next();
+        self.banner.clear();
+        // a board repeating under the old rule need not repeat under the new one, nor
+        // would the old rule's boards be the ones that led up to this one
+        self.forget_history();
+        self.rewind.clear();
+        match self.step_mode {
+            StepMode::Canvas => self.randomize_canvas(),
+            StepMode::Teams => self.seed_teams(true, true),
+            StepMode::Supersample => self.randomize_fine(),
+            StepMode::Generations(_) => self.randomize_generations(),
+            StepMode::Wireworld => {
+                // the circuit is kept from the last visit to the mode
+                self.state = self.wireworld.occupied();
+                self.generation = 0;
+            }
+            StepMode::Ants => {
+                self.colony = ant::Colony::new();
+                self.state = *self.colony.grid();
+                self.generation = 0;
+            }
+            StepMode::Elementary => {
+                self.waterfall = elementary::Waterfall::new();
+                self.state = *self.waterfall.rows();
+                self.generation = 0;
+            }
+            StepMode::Evolving => {
+                self.evolving_rule = life::Rule::conway();
+                self.evolve_steps = 0;
+                self.randomize_evolving();
+            }
+            StepMode::Life(_) | StepMode::Gravity | StepMode::Pour => {}
+        }
+    }
+
+    /// fn announce_mode(&mut self)
+    ///
+    /// Queues the messages scrolled on entering the selected mode: the Elementary and Evolving modes
+    /// scroll their name and their rule
+    fn announce_mode(&mut self) {
+        if self.step_mode == StepMode::Elementary {
+            self.banner
+                .push(text::Scroller::text(format_args!("ELEMENTARY")));
+            self.banner.push(text::Scroller::text(format_args!(
+                "RULE {}",
+                self.waterfall.rule()
+            )));
+        } else if self.step_mode == StepMode::Evolving {
+            self.banner
+                .push(text::Scroller::text(format_args!("EVOLVING")));
+            self.banner
+                .push(text::Scroller::text(format_args!("{}", self.evolving_rule)));
+        }
+    }
+
+    /// fn announce_demo_mode(&mut self)
+    ///
+    /// Starts scrolling the name of the selected mode for the demo mode (a Life rule's in B/S notation),
+    /// keeping the board out from under it until it has scrolled by (see next_demo_frame)
+    fn announce_demo_mode(&mut self) {
+        if self.demo_banner.is_none() {
+            self.demo_board = self.state;
+        }
+        self.demo_banner = Some(match self.step_mode {
+            StepMode::Life(i) => text::Scroller::text(format_args!("{}", life::PRESETS[i])),
+            mode => text::Scroller::text(format_args!("{}", mode.name())),
+        });
+    }
+
+    /// fn next_demo_frame(&mut self) -> Option<LEDState>
+    ///
+    /// Returns the next frame of the demo mode's announcement, or None once it has scrolled by or the
+    /// demo has ended, putting the board it was shown over back
+    fn next_demo_frame(&mut self) -> Option<LEDState> {
+        let frame = self
+            .demo_banner
+            .as_mut()
+            .filter(|_| self.demo)
+            .and_then(text::Scroller::next_frame);
+        if frame.is_none() && self.demo_banner.take().is_some() {
+            self.state = self.demo_board;
+        }
+        frame
+    }
+
+    /// fn show_menu_item(&mut self)
+    ///
+    /// Starts scrolling the settings menu's setting and its value, e.g. "SPEED 200" or "RULE B3/S23"
+    fn show_menu_item(&mut self) {
+        let item = self.menu.item();
+        let label = item.label();
+        let message = match item {
+            menu::Item::Speed => {
+                text::Scroller::text(format_args!("{label} {}", self.pace.step_ms()))
+            }
+            menu::Item::Rule => match self.step_mode {
+                StepMode::Life(i) => {
+                    text::Scroller::text(format_args!("{label} {}", life::PRESETS[i]))
+                }
+                mode => text::Scroller::text(format_args!("{label} {}", mode.name())),
+            },
+            menu::Item::Density => text::Scroller::text(format_args!("{label} {}", self.density)),
+            menu::Item::Brightness => {
+                text::Scroller::text(format_args!("{label} {}", self.brightness))
+            }
+            menu::Item::Boundary => {
+                text::Scroller::text(format_args!("{label} {}", menu::edge_name(self.edge_mode)))
+            }
+            menu::Item::Complement => {
+                text::Scroller::text(format_args!("{label} {}", self.complement_policy.name()))
+            }
+            menu::Item::Mutation => {
+                text::Scroller::text(format_args!("{label} {}", self.mutation_rate))
+            }
+            menu::Item::Heatmap => {
+                text::Scroller::text(format_args!("{label} {}", menu::on_off(self.heatmap)))
+            }
+        };
+        self.banner.show(message);
+    }
+
+    /// fn change_setting(&mut self)
+    ///
+    /// Changes the setting shown by the settings menu to its next value
+    fn change_setting(&mut self) {
+        match self.menu.item() {
+            menu::Item::Speed => self.pace.set_step_ms(menu::next_speed(self.pace.step_ms())),
+            menu::Item::Rule => self.select_next_mode(),
+            menu::Item::Density => self.density = menu::next_density(self.density),
+            menu::Item::Brightness => {
+                self.brightness = self.brightness % render::BRIGHTNESS_LEVELS + 1;
+            }
+            menu::Item::Boundary => {
+                self.edge_mode = menu::next_edge(self.edge_mode);
+                // the board's history was stepped with the old edges
+                self.forget_history();
+                self.rewind.clear();
+            }
+            menu::Item::Complement => self.complement_policy = self.complement_policy.next(),
+            menu::Item::Mutation => {
+                self.mutation_rate = menu::next_mutation_rate(self.mutation_rate)
+            }
+            menu::Item::Heatmap => self.heatmap = !self.heatmap,
+        }
+    }
+
+    /// fn randomize(&mut self)
+    ///
+    /// Randomizes the board with the selected symmetry and density and restarts the generation count
+    fn randomize(&mut self) {
+        randomize_symmetric(
+            &mut self.random_gen,
+            &mut self.state,
+            self.symmetry_mode,
+            self.density,
+        );
+        self.generation = 0;
+        self.forget_history();
+        self.rewind.clear();
+    }
+
+    /// fn seed_teams(&mut self, bool, bool)
+    ///
+    /// Randomly re-seeds the left (team 0) and / or right (team 1) side of the Teams mode board and
+    /// restarts the generation count
+    fn seed_teams(&mut self, left: bool, right: bool) {
+        if left {
+            teams::seed(&mut self.random_gen, &mut self.teams, teams::Side::Left);
+        }
+        if right {
+            teams::seed(&mut self.random_gen, &mut self.teams, teams::Side::Right);
+        }
+        self.state = teams::alive(&self.teams);
+        self.generation = 0;
+    }
+
+    /// fn forget_history(&mut self)
+    ///
+    /// Clears the cycle detection history after the board was replaced rather than stepped
+    fn forget_history(&mut self) {
+        self.history.clear();
+        self.cycle_period = None;
+    }
+
+    /// fn reseed(&mut self)
+    ///
+    /// Randomizes the board of the Supersample or Generations mode, whichever is selected
+    fn reseed(&mut self) {
+        match self.step_mode {
+            StepMode::Supersample => self.randomize_fine(),
+            StepMode::Generations(_) => self.randomize_generations(),
+            _ => self.randomize(),
+        }
+    }
+
+    /// fn randomize_evolving(&mut self)
+    ///
+    /// Randomizes the board of the Evolving mode and restarts the generation count, keeping its rule
+    fn randomize_evolving(&mut self) {
+        self.randomize();
+        self.evolving_board = self.state;
+    }
+
+    /// fn evolve_rule(&mut self)
+    ///
+    /// Toggles one random birth count (1-8, since a rule born from 0 neighbors lights the whole empty
+    /// board) or survival count (0-8) of the Evolving mode's rule, and starts scrolling the new rule
+    fn evolve_rule(&mut self) {
+        let count = self.random_gen.random_u32() % 17;
+        if count < 8 {
+            self.evolving_rule.birth ^= 1 << (count + 1);
+        } else {
+            self.evolving_rule.survive ^= 1 << (count - 8);
+        }
+        self.banner
+            .show(text::Scroller::text(format_args!("{}", self.evolving_rule)));
+    }
+
+    /// fn randomize_generations(&mut self)
+    ///
+    /// Randomizes the board of the Generations mode with every lit cell alive (none dying) and
+    /// restarts the generation count
+    fn randomize_generations(&mut self) {
+        self.randomize();
+        self.gen_cells = self.state;
+    }
+
+    /// fn randomize_fine(&mut self)
+    ///
+    /// Randomizes the fine board of the Supersample mode and restarts the generation count
+    fn randomize_fine(&mut self) {
+        self.fine.randomize(&mut self.random_gen);
+        self.state = supersample::coarse(&self.fine);
+        self.generation = 0;
+    }
+
+    /// fn randomize_canvas(&mut self)
+    ///
+    /// Randomizes the whole virtual board of the Canvas mode and restarts the generation count
+    fn randomize_canvas(&mut self) {
+        self.canvas.randomize(&mut self.random_gen);
+        self.state = self.canvas.view(self.view_origin);
+        self.generation = 0;
+    }
+}
+
+/// StepMode Enum
+///
+/// Selects the stepping function applied on a normal (no btn, not "dead") frame.
+/// The settings menu's rule setting cycles through each of the life::PRESETS rules, the gravity mode, the
+/// canvas mode, the teams mode, the supersample mode, each of the generations::PRESETS, the
+/// Wireworld mode, the Ants mode, the Elementary mode, the Evolving mode, and then the Pour mode
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StepMode {
+    /// Life-like rules (life.rs), holding an index into life::PRESETS
+    Life(usize),
+    /// Lit cells fall towards the tilted edge (gravity.rs)
+    Gravity,
+    /// Conway's rules on a CANVAS_ROW_COUNT x CANVAS_COL_COUNT virtual board, of which a 5x5 window
+    /// panned with the btns and by tilting is shown (canvas.rs)
+    Canvas,
+    /// Conway's rules on a board of two teams, each seeded by one player's btn (teams.rs)
+    Teams,
+    /// Conway's rules on a board of SUPERSAMPLE_SCALE x SUPERSAMPLE_SCALE cells per LED, each LED as
+    /// bright as its block is full (supersample.rs)
+    Supersample,
+    /// Generations rules (generations.rs), holding an index into generations::PRESETS
+    Generations(usize),
+    /// Wireworld circuits laid out with an on-device editor (wireworld.rs)
+    Wireworld,
+    /// Langton's Ants walking a toroidal grid, added and removed with the btns (ant.rs)
+    Ants,
+    /// Elementary (1D) automata of the elementary::RULES scrolling down the grid as a waterfall
+    /// (elementary.rs)
+    Elementary,
+    /// A Life-like rule that mutates every EVOLVE_GENERATIONS generations
+    Evolving,
+    /// Conway's rules with cells poured in at the lowest edge of the tilted MB2
+    Pour,
+}
+
+/// Impl StepMode
+impl StepMode {
+    /// fn name(self) -> &'static str
+    ///
+    /// Returns the name the settings menu scrolls for the mode (Life rules are shown in B/S notation instead)
+    fn name(self) -> &'static str {
+        match self {
+            StepMode::Life(_) => "LIFE",
+            StepMode::Gravity => "GRAVITY",
+            StepMode::Canvas => "CANVAS",
+            StepMode::Teams => "TEAMS",
+            StepMode::Supersample => "SUPERSAMPLE",
+            StepMode::Generations(0) => "STAR WARS",
+            StepMode::Generations(_) => "BRIANS BRAIN",
+            StepMode::Wireworld => "WIREWORLD",
+            StepMode::Ants => "ANTS",
+            StepMode::Elementary => "ELEMENTARY",
+            StepMode::Evolving => "EVOLVING",
+            StepMode::Pour => "POUR",
+        }
+    }
+
+    /// fn next(self) -> Self
+    ///
+    /// Returns the mode selected next by the settings menu's rule setting
+    pub fn next(self) -> Self {
+        match self {
+            StepMode::Life(i) if i + 1 < life::PRESETS.len() => StepMode::Life(i + 1),
+            StepMode::Life(_) => StepMode::Gravity,
+            StepMode::Gravity => StepMode::Canvas,
+            StepMode::Canvas => StepMode::Teams,
+            StepMode::Teams => StepMode::Supersample,
+            StepMode::Supersample => StepMode::Generations(0),
+            StepMode::Generations(i) if i + 1 < generations::PRESETS.len() => {
+                StepMode::Generations(i + 1)
+            }
+            StepMode::Generations(_) => StepMode::Wireworld,
+            StepMode::Wireworld => StepMode::Ants,
+            StepMode::Ants => StepMode::Elementary,
+            StepMode::Elementary => StepMode::Evolving,
+            StepMode::Evolving => StepMode::Pour,
+            StepMode::Pour => StepMode::Life(0),
+        }
+    }
+}
+
+/// ComplementMode Enum
+///
+/// Selects what the B btn does to the board. Holding B and then tapping A cycles through the modes
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ComplementMode {
+    /// Flip every cell (Spec 4)
+    Full,
+    /// Light the unlit cells touching a lit cell
+    Neighborhood,
+    /// Flip the cells of CENTER_MASK
+    Center,
+    /// Flip the cells of QUADRANT_MASK
+    Quadrant,
+    /// Flip the cells of BORDER_MASK
+    Border,
+}
+
+/// Impl ComplementMode
+impl ComplementMode {
+    /// fn next(self) -> Self
+    ///
+    /// Returns the mode selected by the next "B first" chord
+    pub fn next(self) -> Self {
+        match self {
+            ComplementMode::Full => ComplementMode::Neighborhood,
+            ComplementMode::Neighborhood => ComplementMode::Center,
+            ComplementMode::Center => ComplementMode::Quadrant,
+            ComplementMode::Quadrant => ComplementMode::Border,
+            ComplementMode::Border => ComplementMode::Full,
+        }
+    }
+}
+
+/// ComplementPolicy Enum
+///
+/// Selects what holding the B btn does after its press has complemented the board (Spec 4), set in the
+/// settings menu
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ComplementPolicy {
+    /// Complement once per press (the default)
+    Once,
+    /// Complement again every 500ms while B is held, or at an accelerating rate with the `b-repeat`
+    /// feature (its default)
+    Repeat,
+    /// Show the complemented board, frozen, while B is held and the board from before it once B is
+    /// released
+    Preview,
+}
+
+/// Impl ComplementPolicy
+impl ComplementPolicy {
+    /// fn next(self) -> Self
+    ///
+    /// Returns the policy the settings menu changes to
+    pub fn next(self) -> Self {
+        match self {
+            ComplementPolicy::Once => ComplementPolicy::Repeat,
+            ComplementPolicy::Repeat => ComplementPolicy::Preview,
+            ComplementPolicy::Preview => ComplementPolicy::Once,
+        }
+    }
+
+    /// fn name(self) -> &'static str
+    ///
+    /// Returns the name the settings menu scrolls for the policy
+    fn name(self) -> &'static str {
+        match self {
+            ComplementPolicy::Once => "ONCE",
+            ComplementPolicy::Repeat => "REPEAT",
+            ComplementPolicy::Preview => "PREVIEW",
+        }
+    }
+}
+
+/// fn is_terminal(StepMode, &LEDState, &mut LEDState, life::EdgeMode, &life::Walls) -> bool
+///
+/// Returns true if the board should be restarted by the death timer. Under Life-like rules this is
+/// a board whose open (unwalled) cells are all dead, now or in the next generation (e.g. the all-alive
+/// board under Conway's rules), see life::is_terminal. In gravity mode only an empty board is terminal since a settled
+/// pile of cells is the point of that mode
+pub fn is_terminal(
+    step_mode: StepMode,
+    state: &LEDState,
+    scratch: &mut LEDState,
+    edges: life::EdgeMode,
+    walls: &life::Walls,
+) -> bool {
+    match step_mode {
+        StepMode::Life(i) => life::is_terminal(state, scratch, life::PRESETS[i], edges, walls),
+        StepMode::Gravity => life::done(state),
+        // Game::update handles these modes in branches of their own that never get here: the canvas
+        // checks its whole virtual board, and the teams restart once both have died out
+        StepMode::Canvas
+        | StepMode::Teams
+        | StepMode::Supersample
+        | StepMode::Generations(_)
+        | StepMode::Wireworld
+        | StepMode::Ants
+        | StepMode::Elementary
+        | StepMode::Evolving
+        | StepMode::Pour => life::done(state),
+    }
+}
+
+/// Btns Struct
+///
+/// The btns as one frame of Game::update sees them: the (release filtered) A and B btn states, whether
+/// each was newly pressed this frame, the gesture the A btn completed (if any), and whether the board
+/// was asked to pause or resume (Spec 25)
+#[derive(Clone, Copy)]
+struct Btns {
+    a: bool,
+    b: bool,
+    a_pressed: bool,
+    b_pressed: bool,
+    a_gesture: Option<input::Gesture>,
+    pause_toggled: bool,
+}
+
+/// Chord Struct
+///
+/// Tracks the A+B btn chord across frames. A short press ("tap") of the chord is reported when the
+/// first of the two btns is released, while a longer hold is reported every frame it continues.
+/// Until both btns are released the chord remains "active" so that the btn released last does not
+/// also trigger its single-btn action. The chord also remembers whether it was formed by holding one
+/// btn first and then pressing the other
+pub struct Chord {
+    held_frames: u32,
+    hold_frames: u32,
+    active: bool,
+    tapped: bool,
+    a_only_last_frame: bool,
+    b_only_last_frame: bool,
+    a_first: bool,
+    b_first: bool,
+}
+
+/// Impl Chord
+impl Chord {
+    /// fn new(u32) -> Self
+    ///
+    /// Returns a new Chord where holds of at least hold_frames frames count as a long hold
+    fn new(hold_frames: u32) -> Self {
+        Chord {
+            held_frames: 0,
+            hold_frames,
+            active: false,
+            tapped: false,
+            a_only_last_frame: false,
+            b_only_last_frame: false,
+            a_first: false,
+            b_first: false,
+        }
+    }
+
+    /// fn update(&mut self, bool, bool)
+    ///
+    /// Must be called once per frame with the current A and B btn states
+    fn update(&mut self, a_pressed: bool, b_pressed: bool) {
+        self.tapped = false;
+
+        if a_pressed && b_pressed {
+            if self.held_frames == 0 {
+                self.a_first = self.a_only_last_frame;
+                self.b_first = self.b_only_last_frame;
+            }
+            self.held_frames = self.held_frames.saturating_add(1);
+            self.active = true;
+        } else if self.held_frames > 0 {
+            self.tapped = self.held_frames < self.hold_frames;
+            self.held_frames = 0;
+        }
+
+        if !a_pressed && !b_pressed {
+            self.active = false;
+        }
+        self.a_only_last_frame = a_pressed && !b_pressed;
+        self.b_only_last_frame = b_pressed && !a_pressed;
+    }
+
+    /// fn a_first(&self) -> bool
+    ///
+    /// Returns true if the current (or just released) chord was formed by pressing B while A was
+    /// already held
+    fn a_first(&self) -> bool {
+        self.a_first
+    }
+
+    /// fn b_first(&self) -> bool
+    ///
+    /// Returns true if the current (or just released) chord was formed by pressing A while B was
+    /// already held
+    fn b_first(&self) -> bool {
+        self.b_first
+    }
+
+    /// fn active(&self) -> bool
+    ///
+    /// Returns true from the first frame the chord is pressed until both btns have been released
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    /// fn tapped(&self) -> bool
+    ///
+    /// Returns true on the single frame a short chord press is released
+    fn tapped(&self) -> bool {
+        self.tapped
+    }
+
+    /// fn long_hold_started(&self) -> bool
+    ///
+    /// Returns true on the single frame the chord reaches the long hold duration
+    fn long_hold_started(&self) -> bool {
+        self.held_frames == self.hold_frames
+    }
+
+    /// fn long_held(&self) -> bool
+    ///
+    /// Returns true every frame the chord has been held for at least the long hold duration
+    fn long_held(&self) -> bool {
+        self.held_frames >= self.hold_frames
+    }
+}
+
+/// AutoRepeat Struct
+///
+/// Decides when an action repeats while a btn is held, faster the longer it is held. The action fires on
+/// the first frame of the hold, then again after start_frames frames, and each following gap is one
+/// frame shorter than the last until it reaches floor_frames, after which it fires every floor_frames
+/// frames. With a 500 ms start and a 100 ms floor at 100 ms per frame it fires on held frames
+/// 1, 6, 10, 13, 15, 16, 17, ...
+pub struct AutoRepeat {
+    start_frames: u32,
+    floor_frames: u32,
+    gap: u32,
+    next: u32,
+}
+
+/// Impl AutoRepeat
+impl AutoRepeat {
+    /// fn new(u32, u32) -> Self
+    ///
+    /// Returns a new AutoRepeat whose first repeat comes start_frames after the press and whose repeats
+    /// never come closer together than floor_frames (both at least 1)
+    fn new(start_frames: u32, floor_frames: u32) -> Self {
+        let floor_frames = floor_frames.max(1);
+        AutoRepeat {
+            start_frames: start_frames.max(floor_frames),
+            floor_frames,
+            gap: 0,
+            next: 0,
+        }
+    }
+
+    /// fn update(&mut self, u32) -> bool
+    ///
+    /// Takes the number of consecutive frames the btn has been held, counting the current one (so 1 on
+    /// the frame it is pressed and 0 while it is released), and returns true if the action should fire
+    /// this frame
+    fn update(&mut self, held_frames: u32) -> bool {
+        if held_frames == 0 {
+            return false;
+        }
+        if held_frames == 1 {
+            self.gap = self.start_frames;
+            self.next = 1 + self.gap;
+            return true;
+        }
+        if held_frames < self.next {
+            return false;
+        }
+        self.gap = self.gap.saturating_sub(1).max(self.floor_frames);
+        self.next = held_frames + self.gap;
+        true
+    }
+}
+
+/// ReleaseFilter Struct
+///
+/// Adds release hysteresis to a btn. A press is reported immediately and a release as soon as the btn
+/// reads released, but a new press is only accepted once the btn has read released for release_frames
+/// consecutive frames. Presses read during that window are treated as noise from the previous
+/// release (e.g. contact bounce or LED matrix crosstalk on the btn line) and ignored
+pub struct ReleaseFilter {
+    release_frames: u32,
+    released_frames: u32,
+    held: bool,
+}
+
+/// Impl ReleaseFilter
+impl ReleaseFilter {
+    /// fn new(u32) -> Self
+    ///
+    /// Returns a new ReleaseFilter requiring release_frames released frames between presses. The btn
+    /// starts out released long enough that the first press is accepted immediately
+    fn new(release_frames: u32) -> Self {
+        ReleaseFilter {
+            release_frames,
+            released_frames: release_frames,
+            held: false,
+        }
+    }
+
+    /// fn update(&mut self, bool) -> bool
+    ///
+    /// Must be called once per frame with the raw btn state, returns the filtered btn state
+    fn update(&mut self, pressed: bool) -> bool {
+        if pressed {
+            if self.released_frames >= self.release_frames {
+                self.held = true;
+            }
+            self.released_frames = 0;
+        } else {
+            self.held = false;
+            self.released_frames = self.released_frames.saturating_add(1);
+        }
+        self.held
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::XorShift32;
+
+    const EMPTY: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+
+    /// A game driven by a reproducible random source
+    fn game() -> Game<XorShift32> {
+        Game::new(XorShift32::new(0x2545_F491))
+    }
+
+    /// Run frames of update() with no input until the game restarts its board, returning how many
+    /// it took (or None if it has not restarted after `limit` frames)
+    fn frames_until_restart(game: &mut Game<XorShift32>, limit: u32) -> Option<u32> {
+        (1..=limit).find(|_| {
+            game.update(false, false);
+            game.restarted()
+        })
+    }
+
+    #[test]
+    fn holding_a_randomizes_every_frame() {
+        let mut game = game();
+        let mut boards = Vec::new();
+        for _ in 0..4 {
+            boards.push(*game.update(true, false));
+            assert_eq!(game.generation(), 0);
+            assert!(game.stepped_from().is_none());
+            assert!(!game.restarted());
+        }
+        boards.dedup();
+        assert_eq!(boards.len(), 4);
+    }
+
+    /// A held A btn released with contact noise: it reads pressed again for a frame twice before
+    /// it finally settles
+    const NOISY_RELEASE: [bool; 9] = [true, true, true, false, true, false, true, false, false];
+
+    #[test]
+    fn release_filter_ignores_presses_straight_after_a_release() {
+        let mut filter = ReleaseFilter::new(2);
+        let filtered: Vec<bool> = NOISY_RELEASE.iter().map(|a| filter.update(*a)).collect();
+        assert_eq!(
+            filtered,
+            [true, true, true, false, false, false, false, false, false]
+        );
+        // once it has read released for 2 frames a press is accepted again
+        assert!(filter.update(true));
+    }
+
+    /// The held frames (counting from 1) on which `repeat` fires over a hold of `frames` frames
+    fn firing_frames(repeat: &mut AutoRepeat, frames: u32) -> Vec<u32> {
+        (1..=frames).filter(|held| repeat.update(*held)).collect()
+    }
+
+    #[test]
+    fn auto_repeat_speeds_up_to_its_floor() {
+        // 500ms then every 100ms at 100ms per frame, as B is set up
+        let mut repeat = AutoRepeat::new(5, 1);
+        assert_eq!(
+            firing_frames(&mut repeat, 20),
+            [1, 6, 10, 13, 15, 16, 17, 18, 19, 20]
+        );
+        // a higher floor stops the gaps shrinking sooner
+        let mut repeat = AutoRepeat::new(5, 3);
+        assert_eq!(firing_frames(&mut repeat, 20), [1, 6, 10, 13, 16, 19]);
+    }
+
+    #[test]
+    fn auto_repeat_starts_over_after_a_release() {
+        let mut repeat = AutoRepeat::new(5, 1);
+        firing_frames(&mut repeat, 30);
+        assert!(!repeat.update(0));
+        // a single tap fires once, and a new hold gets the slow start again
+        assert_eq!(firing_frames(&mut repeat, 1), [1]);
+        assert!(!repeat.update(0));
+        assert_eq!(firing_frames(&mut repeat, 10), [1, 6, 10]);
+    }
+
+    #[test]
+    fn auto_repeat_never_fires_closer_than_once_a_frame() {
+        // a start below the floor is raised to it, and a 0 floor to 1
+        let mut repeat = AutoRepeat::new(1, 4);
+        assert_eq!(firing_frames(&mut repeat, 12), [1, 5, 9]);
+        let mut repeat = AutoRepeat::new(0, 0);
+        assert_eq!(firing_frames(&mut repeat, 4), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn a_noisy_release_does_not_randomize_again() {
+        let mut game = game();
+        for (frame, a) in NOISY_RELEASE.iter().enumerate() {
+            let before = *game.state();
+            game.update(*a, false);
+            if frame >= 3 {
+                // stepped (or stalled) from the board the real press left, never randomized afresh
+                assert!(
+                    game.stepped_from() == Some(&before) || game.state() == &before,
+                    "frame {frame}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn a_dead_board_restarts_on_a_random_one() {
+        let mut game = game();
+        game.load(EMPTY);
+        let frames = frames_until_restart(&mut game, 200).expect("the dead board never restarted");
+        // the 500ms countdown, then the generation count scrolls across the grid
+        assert!(frames > ms_to_frames(DEATH_RESET_RATE_MS, REFRESH_RATE_MS));
+        assert_eq!(game.generation(), 0);
+        assert!(!life::open_done(game.state(), &WALLS));
+    }
+
+    /// Run `frames` frames with no input, checking that every step advances the generation count
+    /// and every restart sets it back to 0
+    fn step_and_count(game: &mut Game<XorShift32>, frames: u32) {
+        for frame in 0..frames {
+            let before = game.generation();
+            game.update(false, false);
+            if game.restarted() {
+                assert_eq!(game.generation(), 0, "frame {frame}");
+            } else if game.stepped_from().is_some() {
+                assert!(game.generation() > before, "frame {frame}");
+            } else {
+                assert_eq!(game.generation(), before, "frame {frame}");
+            }
+        }
+    }
+
+    #[test]
+    fn the_generation_count_advances_with_each_step() {
+        let mut game = game();
+        let mut glider = EMPTY;
+        life::place_pattern(&mut glider, &life::GLIDER, (1, 1), life::OverflowMode::Wrap);
+        game.load(glider);
+        assert_eq!(game.generation(), 0);
+        game.update(false, false);
+        if WALLS == life::NO_WALLS && !MUTATION_ENABLED {
+            assert_eq!(game.generation(), STEPS_PER_FRAME);
+        }
+        step_and_count(&mut game, 12);
+    }
+
+    /// Whether nothing but the rule changes a Life board from one step to the next
+    fn plain_steps() -> bool {
+        WALLS == life::NO_WALLS && !MUTATION_ENABLED && !INJECT_ENABLED
+    }
+
+    /// Every frame of `text` scrolling across the grid
+    fn scroll_frames(text: &str) -> Vec<LEDState> {
+        let mut scroller = text::Scroller::text(format_args!("{text}"));
+        core::iter::from_fn(|| scroller.next_frame()).collect()
+    }
+
+    /// A horizontal blinker across the middle of the grid
+    fn blinker() -> LEDState {
+        let mut blinker = EMPTY;
+        blinker[2][1..4].fill(1);
+        blinker
+    }
+
+    /// A block in the middle of the grid, still under every Life preset but Seeds
+    fn block() -> LEDState {
+        let mut block = EMPTY;
+        block[1][1..3].fill(1);
+        block[2][1..3].fill(1);
+        block
+    }
+
+    #[test]
+    fn the_demo_announcement_scrolls_over_the_waiting_board() {
+        let mut game = game();
+        game.load(blinker());
+        game.start_demo();
+        for (frame, expected) in scroll_frames("B3/S23").iter().enumerate() {
+            game.update(false, false);
+            assert_eq!(game.state(), expected, "frame {frame}");
+            assert!(!game.restarted(), "frame {frame}");
+            assert_eq!(game.generation(), 0, "frame {frame}");
+        }
+        // then the board carries on from where it was, rather than restarting
+        game.update(false, false);
+        assert!(!game.restarted());
+        assert_eq!(game.stepped_from(), Some(&blinker()));
+    }
+
+    #[test]
+    fn input_during_the_demo_announcement_acts_on_the_board() {
+        let mut plain = game();
+        plain.load(blinker());
+        plain.update(false, true);
+
+        let mut game = game();
+        game.load(blinker());
+        game.start_demo();
+        for _ in 0..3 {
+            game.update(false, false);
+        }
+        // B ends the demo and complements the board, not the text over it
+        game.update(false, true);
+        assert_eq!(game.state(), plain.state());
+    }
+
+    #[test]
+    fn the_demo_moves_on_to_the_next_mode_and_back_to_its_board() {
+        if !plain_steps() {
+            return;
+        }
+        let mut game = game();
+        game.load(block());
+        game.start_demo();
+        let announcements = scroll_frames("B3/S23").len() + scroll_frames("B36/S23").len();
+        let frames = ms_to_frames(DEMO_MODE_MS, REFRESH_RATE_MS) as usize + announcements;
+        let mut covered = 0;
+        for frame in 0..frames {
+            // the block repeats itself every step, which the `cycle-restart` feature would restart
+            game.cycle_period = None;
+            game.update(false, false);
+            assert!(!game.restarted(), "frame {frame}");
+            covered += (game.state() != &block()) as usize;
+        }
+        // HighLife was announced over the block, which is still there once it has scrolled by
+        assert!(game.step_mode == StepMode::Life(1));
+        assert_eq!(game.state(), &block());
+        assert!(covered > announcements / 2);
+    }
+
+    #[test]
+    fn a_dead_board_restarts_after_exactly_the_countdown_and_the_scroll() {
+        let countdown = ms_to_frames(DEATH_RESET_RATE_MS, REFRESH_RATE_MS);
+        let mut scroller = text::Scroller::number(0);
+        let scroll = core::iter::from_fn(|| scroller.next_frame()).count() as u32;
+
+        let mut game = game();
+        game.load(EMPTY);
+        // the countdown's progress (the pulse shown over the dead board) climbs every frame
+        let mut progress = 0.0;
+        for frame in 1..=countdown {
+            game.update(false, false);
+            assert!(!game.restarted(), "frame {frame}");
+            if frame < countdown {
+                assert!(game.restart_progress() > progress, "frame {frame}");
+                progress = game.restart_progress();
+            }
+        }
+
+        // then the generation count (0) scrolls across, and the frame after it the board restarts
+        for frame in 1..=scroll {
+            game.update(false, false);
+            assert!(!game.restarted(), "scroll frame {frame}");
+        }
+        game.update(false, false);
+        assert!(game.restarted());
+        assert_eq!(game.generation(), 0);
+    }
+
+    #[test]
+    fn single_step_mode_takes_one_generation_per_a_press() {
+        let mut game = game();
+        // a long A press enters single-step mode, randomizing until the hold is long enough
+        let hold = ms_to_frames(SINGLE_STEP_HOLD_MS, REFRESH_RATE_MS);
+        for frame in 1..=hold {
+            game.update(true, false);
+            assert_eq!(game.single_step, frame == hold, "frame {frame}");
+        }
+        let held = *game.state();
+        game.update(true, false);
+        assert_eq!(game.state(), &held);
+
+        let mut glider = EMPTY;
+        life::place_pattern(&mut glider, &life::GLIDER, (0, 0), life::OverflowMode::Wrap);
+        game.load(glider);
+        // walls or other edges would step the glider differently from life::life
+        let plain = WALLS == life::NO_WALLS && EDGE_MODE == life::EdgeMode::Toroidal;
+        let mut expected = glider;
+        let mut scratch = EMPTY;
+        for generation in 1..=3 {
+            // released, nothing steps on its own and a shake does not randomize
+            game.set_motion(true, gravity::Direction::None);
+            game.update(false, false);
+            let waiting = *game.state();
+            for _ in 0..2 {
+                game.update(false, false);
+                assert_eq!(game.state(), &waiting, "generation {generation}");
+            }
+            assert_eq!(game.generation(), generation - 1);
+            game.set_motion(false, gravity::Direction::None);
+
+            // each new press (but not holding it) takes exactly one step
+            game.update(true, false);
+            let stepped = *game.state();
+            for _ in 0..2 {
+                game.update(true, false);
+                assert_eq!(game.state(), &stepped, "generation {generation}");
+            }
+            assert_eq!(game.generation(), generation);
+            life::life(&mut expected, &mut scratch, life::Rule::conway());
+            if plain {
+                assert_eq!(stepped, expected, "generation {generation}");
+            }
+        }
+
+        // a dead board is left alone rather than restarted
+        game.load(EMPTY);
+        assert_eq!(frames_until_restart(&mut game, 100), None);
+
+        // B exits once released, after which the dead board restarts as usual
+        game.update(false, true);
+        assert!(game.single_step);
+        game.update(false, false);
+        assert!(!game.single_step);
+        assert!(frames_until_restart(&mut game, 200).is_some());
+    }
+
+    #[test]
+    fn a_burst_of_n_steps_advances_the_board_n_generations() {
+        let mut game = game();
+        let mut glider = EMPTY;
+        life::place_pattern(&mut glider, &life::GLIDER, (0, 0), life::OverflowMode::Wrap);
+        game.load(glider);
+
+        assert_eq!(game.step_burst(4), 4);
+        assert_eq!(game.generation(), 4);
+        // the crossfade starts from the board before the burst, not the last internal step
+        assert_eq!(game.stepped_from(), Some(&glider));
+        if plain_steps() && EDGE_MODE == life::EdgeMode::Toroidal {
+            // a glider moves one cell diagonally every 4 generations
+            let mut moved = EMPTY;
+            life::place_pattern(&mut moved, &life::GLIDER, (1, 1), life::OverflowMode::Wrap);
+            assert_eq!(game.state(), &moved);
+
+            assert_eq!(game.step_burst(8), 8);
+            moved = EMPTY;
+            life::place_pattern(&mut moved, &life::GLIDER, (3, 3), life::OverflowMode::Wrap);
+            assert_eq!(game.state(), &moved);
+            assert_eq!(game.generation(), 12);
+        }
+    }
+
+    #[test]
+    fn a_burst_stops_at_a_board_about_to_die() {
+        let mut game = game();
+        // the ends of a diagonal die and leave its middle cell, which dies next
+        let mut diagonal = EMPTY;
+        for (i, line) in diagonal.iter_mut().enumerate().take(4).skip(1) {
+            line[i] = 1;
+        }
+        game.load(diagonal);
+        if plain_steps() {
+            assert_eq!(game.step_burst(4), 1);
+            let mut single = EMPTY;
+            single[2][2] = 1;
+            assert_eq!(game.state(), &single);
+            assert_eq!(game.generation(), 1);
+
+            // a single step is always taken, even from a stalled board
+            game.load(EMPTY);
+            assert_eq!(game.step_burst(4), 1);
+        }
+    }
+
+    #[test]
+    fn the_generation_count_resets_on_randomize_and_complement() {
+        let mut game = game();
+        step_and_count(&mut game, 6);
+
+        // A randomizes, and counting starts again from the new board
+        game.update(true, false);
+        assert_eq!(game.generation(), 0);
+        step_and_count(&mut game, 6);
+
+        // so does a complement of the board
+        game.update(false, true);
+        assert_eq!(game.generation(), 0);
+        step_and_count(&mut game, 6);
+
+        // and a board that dies restarts at 0
+        game.load(EMPTY);
+        assert_eq!(game.generation(), 0);
+        assert!(frames_until_restart(&mut game, 200).is_some());
+        assert_eq!(game.generation(), 0);
+        step_and_count(&mut game, 6);
+    }
+
+    #[test]
+    fn randomize_then_die_then_restart() {
+        let mut game = game();
+        game.update(true, false);
+        let randomized = *game.state();
+        assert_eq!(game.generation(), 0);
+
+        // released, the board is stepped (or, if it happens to be stuck, restarts) rather than
+        // randomized again
+        game.update(false, false);
+        assert!(game.stepped_from() == Some(&randomized) || game.state() == &randomized);
+
+        // a board that dies is restarted without any input
+        game.load(EMPTY);
+        assert!(frames_until_restart(&mut game, 200).is_some());
+        let restarted = *game.state();
+
+        // and pressing A during the countdown randomizes it straight away instead
+        game.load(EMPTY);
+        game.update(false, false);
+        game.update(true, false);
+        assert!(!game.restarted());
+        assert_ne!(game.state(), &restarted);
+        assert!(!life::open_done(game.state(), &WALLS));
+    }
+}
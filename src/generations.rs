@@ -0,0 +1,114 @@
+//! "Generations" rules: Life-like automata whose cells decay through dying states.
+//!
+//! A rule of the Generations family, written S/B/C such as Star Wars (345/2/4), has C states per
+//! cell: 0 (dead), 1 (alive), and the dying states 2..C. Live cells survive or are born by the
+//! usual B/S counts of live (state 1) neighbors, but a live cell that does not survive starts to
+//! die instead of disappearing: it moves through the dying states one per generation, neither
+//! counting as a live neighbor nor able to be reborn, until it is dead again. Dying cells are
+//! shown dimmer the closer they are to death.
+
+use crate::fade::{Levels, MAX_BRIGHTNESS};
+use crate::life::{self, EdgeMode, Rule};
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// A rule of the Generations family
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GenRule {
+    /// Birth and survival counts of live neighbors
+    pub rule: Rule,
+    /// Number of cell states, dead and alive included (2 is an ordinary Life-like rule)
+    pub states: u8,
+}
+
+impl GenRule {
+    /// Star Wars, 345/2/4.
+    pub const fn star_wars() -> Self {
+        GenRule {
+            rule: Rule {
+                birth: 1 << 2,
+                survive: 1 << 3 | 1 << 4 | 1 << 5,
+            },
+            states: 4,
+        }
+    }
+
+    /// Brian's Brain, /2/3: a ready (dead) cell fires (is alive) with exactly 2 firing
+    /// neighbors, and a firing cell always becomes refractory (dying) for one generation.
+    pub const fn brians_brain() -> Self {
+        GenRule {
+            rule: Rule {
+                birth: 1 << 2,
+                survive: 0,
+            },
+            states: 3,
+        }
+    }
+}
+
+/// The Generations rules that can be cycled through on the device.
+pub const PRESETS: [GenRule; 2] = [GenRule::star_wars(), GenRule::brians_brain()];
+
+/// A board of cell states in 0..states: 0 dead, 1 alive, and the rest dying. An on/off board is
+/// a valid one, with every lit cell alive
+pub type Cells = [[u8; COL_COUNT]; ROW_COUNT];
+
+/// The generation following `cells` under `rule` and `edges`.
+pub fn step(cells: &Cells, rule: GenRule, edges: EdgeMode) -> Cells {
+    let mut next = [[0; COL_COUNT]; ROW_COUNT];
+
+    for (row, line) in next.iter_mut().enumerate() {
+        for (col, cell) in line.iter_mut().enumerate() {
+            let live_neighbors = || {
+                life::neighbors::<COL_COUNT, ROW_COUNT>(row, col, edges)
+                    .filter(|&(r, c)| cells[r][c] == 1)
+                    .count() as u8
+            };
+            *cell = match cells[row][col] {
+                0 => rule.rule.births(live_neighbors()) as u8,
+                1 if rule.rule.survives(live_neighbors()) => 1,
+                state => decay(state, rule.states),
+            };
+        }
+    }
+    next
+}
+
+/// The state after `state` for a cell that is not (or no longer) alive: one step closer to
+/// death, wrapping round to dead after the last dying state.
+fn decay(state: u8, states: u8) -> u8 {
+    if state + 1 >= states { 0 } else { state + 1 }
+}
+
+/// The cells of `cells` that are alive or dying, so the board only counts as empty once every
+/// cell has fully died.
+pub fn occupied(cells: &Cells) -> LEDState {
+    let mut state: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+    for (line, cell_line) in state.iter_mut().zip(cells.iter()) {
+        for (lit, cell) in line.iter_mut().zip(cell_line.iter()) {
+            *lit = (*cell != 0) as u8;
+        }
+    }
+    state
+}
+
+/// Brightness levels: live cells at MAX_BRIGHTNESS and dying cells fading evenly towards dark,
+/// so the last dying state before death is the dimmest.
+pub fn levels(cells: &Cells, states: u8) -> Levels {
+    let mut levels = [[0u8; COL_COUNT]; ROW_COUNT];
+    for (line, cell_line) in levels.iter_mut().zip(cells.iter()) {
+        for (level, cell) in line.iter_mut().zip(cell_line.iter()) {
+            *level = match *cell {
+                0 => 0,
+                // state k of `states` keeps (states - k) / (states - 1) of the full brightness
+                state => {
+                    let remaining = states.saturating_sub(state) as u16;
+                    let span = states.saturating_sub(1).max(1) as u16;
+                    (remaining * MAX_BRIGHTNESS as u16)
+                        .div_ceil(span)
+                        .min(MAX_BRIGHTNESS as u16) as u8
+                }
+            };
+        }
+    }
+    levels
+}
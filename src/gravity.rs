@@ -0,0 +1,226 @@
+//! A playful alternative to the GOL rules where lit LEDs behave like grains of sand.
+//!
+//! Each frame every lit cell falls one step towards whichever edge of the MB2 is tilted
+//! downwards, provided the cell it would fall into is empty. A grain resting on another one
+//! slides diagonally down off it instead if it can, so grains pour into slopes rather than
+//! stacking up in towers, and only grains resting against an edge (the "floor") or on a slope
+//! they cannot slide down stay where they are.
+
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// Tilt (in mg) an axis must exceed before it is considered to point "down"
+const TILT_THRESHOLD_MG: i16 = 300;
+
+/// The edge of the LED grid that is currently lowest, or None if the MB2 is lying flat
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    None,
+}
+
+impl Direction {
+    /// Derive the downhill direction from the x/y accelerometer readings (in mg).
+    ///
+    /// The axis with the larger reading wins; positive x tilts towards the right-hand column and
+    /// positive y tilts towards the bottom row (the raw LSM303AGR axes as mounted on the MB2).
+    pub fn from_accel(x: i16, y: i16) -> Self {
+        if x.unsigned_abs() < TILT_THRESHOLD_MG as u16
+            && y.unsigned_abs() < TILT_THRESHOLD_MG as u16
+        {
+            Direction::None
+        } else if x.unsigned_abs() >= y.unsigned_abs() {
+            if x > 0 {
+                Direction::Right
+            } else {
+                Direction::Left
+            }
+        } else if y > 0 {
+            Direction::Down
+        } else {
+            Direction::Up
+        }
+    }
+}
+
+/// The cell one step of (dr, dc) away from (row, col), or None if that is off the grid.
+fn offset(row: usize, col: usize, (dr, dc): (isize, isize)) -> Option<(usize, usize)> {
+    match (row.checked_add_signed(dr), col.checked_add_signed(dc)) {
+        (Some(next_row), Some(next_col)) if next_row < ROW_COUNT && next_col < COL_COUNT => {
+            Some((next_row, next_col))
+        }
+        _ => None,
+    }
+}
+
+/// Move every lit cell one step towards `tilt` if the destination cell is unlit, or else
+/// diagonally down to either side of it if one of those is unlit.
+///
+/// Cells are visited a line at a time from the floor up, so a connected column of grains falls
+/// together rather than only its bottom grain moving, and no grain moves twice in one frame. A
+/// grain able to slide either way tries the diagonal clockwise of the fall direction first if
+/// `clockwise_first`, so alternating it between frames spreads a pile evenly.
+pub fn settle(state: &mut LEDState, tilt: Direction, clockwise_first: bool) {
+    const LED_COUNT: usize = ROW_COUNT * COL_COUNT;
+
+    let (dr, dc): (isize, isize) = match tilt {
+        Direction::Up => (-1, 0),
+        Direction::Down => (1, 0),
+        Direction::Left => (0, -1),
+        Direction::Right => (0, 1),
+        Direction::None => return,
+    };
+
+    for step in 0..LED_COUNT {
+        // Down/Right floors are at the end of row-major (Up/Down) or column-major (Left/Right)
+        // order, so walk backwards for those
+        let i = if dr + dc > 0 {
+            LED_COUNT - 1 - step
+        } else {
+            step
+        };
+        let (row, col) = if dr != 0 {
+            (i / COL_COUNT, i % COL_COUNT)
+        } else {
+            (i % ROW_COUNT, i / ROW_COUNT)
+        };
+
+        if state[row][col] == 0 {
+            continue;
+        }
+
+        // the diagonals are the fall direction plus a quarter turn of it either way (on screen,
+        // with rows growing downwards, (dr, dc) turns clockwise into (dc, -dr))
+        let (clockwise, anticlockwise) = ((dr + dc, dc - dr), (dr - dc, dc + dr));
+        let (first, second) = if clockwise_first {
+            (clockwise, anticlockwise)
+        } else {
+            (anticlockwise, clockwise)
+        };
+        let target = [(dr, dc), first, second]
+            .into_iter()
+            .filter_map(|delta| offset(row, col, delta))
+            .find(|&(r, c)| state[r][c] == 0);
+
+        if let Some((next_row, next_col)) = target {
+            state[next_row][next_col] = state[row][col];
+            state[row][col] = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+    const TILTS: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// The cell one step downhill of (row, col), or None if it rests on the floor
+    fn below(row: usize, col: usize, tilt: Direction) -> Option<(usize, usize)> {
+        let delta = match tilt {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+            Direction::None => return None,
+        };
+        offset(row, col, delta)
+    }
+
+    fn live_count(state: &LEDState) -> usize {
+        state.iter().flatten().filter(|&&cell| cell != 0).count()
+    }
+
+    #[test]
+    fn accel_readings_pick_the_steeper_axis() {
+        assert_eq!(Direction::from_accel(0, 0), Direction::None);
+        assert_eq!(Direction::from_accel(299, -299), Direction::None);
+        assert_eq!(Direction::from_accel(500, 400), Direction::Right);
+        assert_eq!(Direction::from_accel(-500, 400), Direction::Left);
+        assert_eq!(Direction::from_accel(400, 500), Direction::Down);
+        assert_eq!(Direction::from_accel(400, -500), Direction::Up);
+    }
+
+    #[test]
+    fn a_lone_grain_falls_one_step_per_frame_to_the_floor() {
+        for tilt in TILTS {
+            let (mut row, mut col) = (ROW_COUNT / 2, COL_COUNT / 2);
+            let mut state = EMPTY;
+            state[row][col] = 1;
+
+            while let Some(next) = below(row, col, tilt) {
+                settle(&mut state, tilt, true);
+                (row, col) = next;
+                let mut expected = EMPTY;
+                expected[row][col] = 1;
+                assert_eq!(state, expected, "{tilt:?}");
+            }
+
+            // on the floor it stays put
+            settle(&mut state, tilt, true);
+            assert_eq!(state[row][col], 1, "{tilt:?}");
+            assert_eq!(live_count(&state), 1, "{tilt:?}");
+        }
+    }
+
+    #[test]
+    fn a_full_line_of_grains_stacks_against_the_floor() {
+        for tilt in TILTS {
+            // a line of grains all the way across the board, parallel to the floor
+            let mut state = EMPTY;
+            match tilt {
+                Direction::Up | Direction::Down => state[1] = [1; COL_COUNT],
+                _ => state.iter_mut().for_each(|line| line[1] = 1),
+            }
+            let line: Vec<(usize, usize)> = crate::live_cells(&state).collect();
+            for _ in 0..ROW_COUNT.max(COL_COUNT) {
+                settle(&mut state, tilt, false);
+            }
+
+            // every grain ends up against the floor in its own line, none are lost
+            let floor = |(row, col): (usize, usize)| match tilt {
+                Direction::Up => (0, col),
+                Direction::Down => (ROW_COUNT - 1, col),
+                Direction::Left => (row, 0),
+                _ => (row, COL_COUNT - 1),
+            };
+            let mut expected = EMPTY;
+            for cell in line {
+                let (row, col) = floor(cell);
+                expected[row][col] = 1;
+            }
+            assert_eq!(state, expected, "{tilt:?}");
+        }
+    }
+
+    #[test]
+    fn a_grain_on_a_grain_slides_down_off_it() {
+        let mut state = EMPTY;
+        state[ROW_COUNT - 1][2] = 1;
+        state[ROW_COUNT - 2][2] = 1;
+        settle(&mut state, Direction::Down, true);
+        assert_eq!(live_count(&state), 2);
+        assert_eq!(state[ROW_COUNT - 1][2], 1);
+        assert_eq!(state[ROW_COUNT - 2][2], 0);
+        assert_eq!(state[ROW_COUNT - 1][1] + state[ROW_COUNT - 1][3], 1);
+    }
+
+    #[test]
+    fn lying_flat_leaves_the_board_alone() {
+        let mut state = EMPTY;
+        state[0][0] = 1;
+        state[2][3] = 1;
+        state[4][1] = 1;
+        let before = state;
+        settle(&mut state, Direction::None, true);
+        assert_eq!(state, before);
+    }
+}
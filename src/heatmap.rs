@@ -0,0 +1,74 @@
+//! Heatmap: how often each cell has been alive, shown as a persistent glow.
+//!
+//! A count of the generations each cell has spent alive is kept across every board the game
+//! plays, randomizations included, so the cells that patterns keep coming back to glow
+//! brightest. Dead cells are lit by their count relative to the busiest cell's, up to
+//! HEAT_MAX_BRIGHTNESS, and live cells are drawn over the heatmap at full brightness.
+
+use crate::fade::{Levels, MAX_BRIGHTNESS};
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// Brightness of the hottest dead cell, dim enough for the live cells to stand out over it
+const HEAT_MAX_BRIGHTNESS: u8 = 4;
+
+/// Per-cell generations alive, parallel to the board.
+pub struct Heatmap {
+    counts: [[u16; COL_COUNT]; ROW_COUNT],
+}
+
+impl Heatmap {
+    /// Create a heatmap in which no cell has been alive yet.
+    pub const fn new() -> Self {
+        Heatmap {
+            counts: [[0; COL_COUNT]; ROW_COUNT],
+        }
+    }
+
+    /// Count one more generation for every live cell of `state`. Once a count would overflow
+    /// every count is halved, which keeps their proportions.
+    pub fn update(&mut self, state: &LEDState) {
+        if self.counts.iter().flatten().any(|count| *count == u16::MAX) {
+            for count in self.counts.iter_mut().flatten() {
+                *count /= 2;
+            }
+        }
+        for (count, cell) in self.counts.iter_mut().flatten().zip(state.iter().flatten()) {
+            *count += (*cell != 0) as u16;
+        }
+    }
+
+    /// Brightness levels: live cells of `state` at MAX_BRIGHTNESS and dead cells by their share
+    /// of the highest count, from 1 for any cell that has ever been alive up to
+    /// HEAT_MAX_BRIGHTNESS.
+    pub fn levels(&self, state: &LEDState) -> Levels {
+        let hottest = self
+            .counts
+            .iter()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(1) as u32;
+        let mut levels = [[0u8; COL_COUNT]; ROW_COUNT];
+
+        for (row, line) in levels.iter_mut().enumerate() {
+            for (col, level) in line.iter_mut().enumerate() {
+                let count = self.counts[row][col] as u32;
+                *level = if state[row][col] != 0 {
+                    MAX_BRIGHTNESS
+                } else if count == 0 {
+                    0
+                } else {
+                    (1 + count * (HEAT_MAX_BRIGHTNESS as u32 - 1) / hottest) as u8
+                };
+            }
+        }
+        levels
+    }
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
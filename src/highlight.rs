@@ -0,0 +1,34 @@
+//! Highlighting of births and deaths for teaching the Life rules.
+//!
+//! On the frame after a Life step, newly born cells flash at full brightness while cells that
+//! survived are shown dimmer and cells that just died glow faintly, so it is easy to see which
+//! cells each rule creates and removes. Enabled with the `highlight` cargo feature.
+
+use crate::fade::MAX_BRIGHTNESS;
+use crate::life::StepResult;
+use crate::{COL_COUNT, ROW_COUNT};
+
+/// Brightness of a cell that was alive before and after the step
+const SURVIVED_BRIGHTNESS: u8 = 4;
+/// Brightness of a cell that was alive before the step but not after it
+const DIED_BRIGHTNESS: u8 = 1;
+
+/// Brightness levels (0..=MAX_BRIGHTNESS) for the board produced by `step`.
+pub fn levels(step: &StepResult) -> [[u8; COL_COUNT]; ROW_COUNT] {
+    let mut levels = [[0u8; COL_COUNT]; ROW_COUNT];
+
+    for (row, line) in levels.iter_mut().enumerate() {
+        for (col, level) in line.iter_mut().enumerate() {
+            *level = if step.born[row][col] != 0 {
+                MAX_BRIGHTNESS
+            } else if step.next[row][col] != 0 {
+                SURVIVED_BRIGHTNESS
+            } else if step.died[row][col] != 0 {
+                DIED_BRIGHTNESS
+            } else {
+                0
+            };
+        }
+    }
+    levels
+}
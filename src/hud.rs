@@ -0,0 +1,82 @@
+//! A lightweight heads-up display drawn over the board.
+//!
+//! The live population (0..=25) is shown as a bar rising from the bottom of the rightmost
+//! LED column, one pixel per started group of 5 live cells.
+
+use crate::{COL_COUNT, LEDState, ROW_COUNT, live_cells};
+
+/// Count the lit cells on the board.
+pub fn population(state: &LEDState) -> u8 {
+    live_cells(state).count() as u8
+}
+
+/// Map a population of 0..=ROW_COUNT * COL_COUNT onto a bar height of 0..=ROW_COUNT pixels
+/// (0..=25 onto 0..=5 on the MB2).
+///
+/// Any non-empty board lights at least one pixel so that a nearly dead board is still
+/// distinguishable from an empty one.
+pub fn bar_height(population: u8) -> usize {
+    (population as usize * ROW_COUNT)
+        .div_ceil(ROW_COUNT * COL_COUNT)
+        .min(ROW_COUNT)
+}
+
+/// Return a copy of the board with its rightmost column replaced by the population bar.
+pub fn with_population_bar(state: &LEDState) -> LEDState {
+    let height = bar_height(population(state));
+    let mut frame = *state;
+
+    for (row, line) in frame.iter_mut().enumerate() {
+        line[COL_COUNT - 1] = (row >= ROW_COUNT - height) as u8;
+    }
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+
+    #[test]
+    fn population_counts_the_lit_cells() {
+        assert_eq!(population(&EMPTY), 0);
+        assert_eq!(population(&[[1; COL_COUNT]; ROW_COUNT]), 25);
+
+        let mut mixed = EMPTY;
+        mixed[0][0] = 1;
+        mixed[2][3] = 1;
+        // any lit level counts once
+        mixed[4][4] = 9;
+        assert_eq!(population(&mixed), 3);
+    }
+
+    #[test]
+    fn the_bar_grows_a_pixel_per_started_group_of_5() {
+        assert_eq!(bar_height(0), 0);
+        assert_eq!(bar_height(1), 1);
+        for height in 1..=ROW_COUNT {
+            let last = (height * 5) as u8;
+            assert_eq!(bar_height(last), height);
+            if height < ROW_COUNT {
+                assert_eq!(bar_height(last + 1), height + 1);
+            }
+        }
+        assert_eq!(bar_height(25), ROW_COUNT);
+    }
+
+    #[test]
+    fn the_bar_replaces_the_rightmost_column_from_the_bottom() {
+        let mut state = EMPTY;
+        // the first 6 cells in row-major order
+        for i in 0..6 {
+            state[i / COL_COUNT][i % COL_COUNT] = 1;
+        }
+        let frame = with_population_bar(&state);
+        let bar: Vec<u8> = frame.iter().map(|line| line[COL_COUNT - 1]).collect();
+        assert_eq!(bar, [0, 0, 0, 1, 1]);
+        // the cell under the bar is hidden, the rest of the board is kept
+        assert_eq!(frame[0][..COL_COUNT - 1], state[0][..COL_COUNT - 1]);
+        assert_eq!(frame[1][..COL_COUNT - 1], state[1][..COL_COUNT - 1]);
+    }
+}
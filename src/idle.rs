@@ -0,0 +1,46 @@
+//! Idle dimming: the display dims itself while nobody is using the MB2.
+//!
+//! Frames are counted since the last input (a btn press or a shake). Once the count passes the
+//! idle timeout the display brightness steps down one level at a time to a low floor, and any
+//! input restores full brightness at once. The brightness is applied by render::Dimmed.
+
+use crate::fade::MAX_BRIGHTNESS;
+
+/// Tracks the frames since the last input and the display brightness they call for
+pub struct IdleDimmer {
+    idle_frames: u32,
+    timeout_frames: u32,
+    step_frames: u32,
+    floor: u8,
+}
+
+impl IdleDimmer {
+    /// Start dimming after `timeout_frames` frames without input, one brightness level every
+    /// `step_frames` frames, down to `floor` (kept between 1 and MAX_BRIGHTNESS so the game
+    /// stays visible).
+    pub fn new(timeout_frames: u32, step_frames: u32, floor: u8) -> Self {
+        IdleDimmer {
+            idle_frames: 0,
+            timeout_frames,
+            step_frames: step_frames.max(1),
+            floor: floor.clamp(1, MAX_BRIGHTNESS),
+        }
+    }
+
+    /// Count one frame, with or without input.
+    pub fn update(&mut self, input: bool) {
+        self.idle_frames = if input {
+            0
+        } else {
+            self.idle_frames.saturating_add(1)
+        };
+    }
+
+    /// The brightness (0..=MAX_BRIGHTNESS) the display should be scaled to.
+    pub fn brightness(&self) -> u8 {
+        let dimmed_steps = self.idle_frames.saturating_sub(self.timeout_frames) / self.step_frames;
+        MAX_BRIGHTNESS
+            .saturating_sub(dimmed_steps.min(MAX_BRIGHTNESS as u32) as u8)
+            .max(self.floor)
+    }
+}
@@ -0,0 +1,226 @@
+//! Debouncing of btn presses between the game's frames.
+//!
+//! The game reads the btns once per 100ms frame, so a quick tap falling between two reads would
+//! be missed even though the display no longer blocks. `Debounced` wraps a btn pin and is sampled
+//! at each of the frame's 10ms sub-frames while the main loop waits (and while an animation plays).
+//! Its state only changes once DEBOUNCE_SAMPLES samples in a row disagree with it, so contact
+//! bounce on a press or release never shows up as extra presses. Each change is queued as an
+//! event for the next frame (events.rs), so a tap shorter than a frame is not lost.
+//!
+//! On top of the debounced state, which the game reads once per frame, `Gestures` classifies a
+//! btn's presses into short, long, and double presses, so that one btn can carry several actions.
+
+use embedded_hal::digital::{ErrorType, InputPin};
+
+/// Consecutive samples (10ms apart) that must agree before the debounced state changes
+pub const DEBOUNCE_SAMPLES: u8 = 2;
+
+/// A debounced btn pin
+pub struct Debounced<P> {
+    pin: P,
+    /// The debounced state: `true` while pressed
+    pressed: bool,
+    /// Samples in a row that have disagreed with `pressed`
+    disagreeing: u8,
+}
+
+impl<P: InputPin> Debounced<P> {
+    /// Wrap `pin`, starting in the state it reads now.
+    pub fn new(mut pin: P) -> Self {
+        let pressed = pin.is_low().unwrap_or(false);
+        Debounced {
+            pin,
+            pressed,
+            disagreeing: 0,
+        }
+    }
+
+    /// Read the btn once, moving the debounced state after DEBOUNCE_SAMPLES disagreeing reads.
+    /// Returns the new state (`true` for pressed) if it changed.
+    pub fn sample(&mut self) -> Option<bool> {
+        let low = self.pin.is_low().unwrap_or(self.pressed);
+        if low == self.pressed {
+            self.disagreeing = 0;
+            return None;
+        }
+        self.disagreeing += 1;
+        if self.disagreeing < DEBOUNCE_SAMPLES {
+            return None;
+        }
+        self.pressed = low;
+        self.disagreeing = 0;
+        Some(low)
+    }
+}
+
+impl<P: ErrorType> ErrorType for Debounced<P> {
+    type Error = P::Error;
+}
+
+/// Reads low (pressed) while the debounced btn is held
+impl<P: InputPin> InputPin for Debounced<P> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.is_low()?)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.pressed)
+    }
+}
+
+/// A classified btn press
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Gesture {
+    /// Pressed and released before a long press, and not pressed again within the double press window
+    Short,
+    /// Held for the long press duration (reported while still held)
+    Long,
+    /// Pressed again within the double press window of a short press (reported on the second press)
+    Double,
+}
+
+/// Classifies one btn's state, read once per frame, into gestures
+pub struct Gestures {
+    long_frames: u32,
+    double_frames: u32,
+    /// Frames the btn has been held, 0 while released
+    held_frames: u32,
+    /// Frames since a short press was released, while it could still become a double press
+    since_short: Option<u32>,
+    /// The press being held is the second of a double press, so it is not also a short or long one
+    second: bool,
+}
+
+impl Gestures {
+    /// Report a long press once the btn has been held `long_frames` frames, and a double press if it
+    /// is pressed again within `double_frames` frames of releasing a short one. With a
+    /// `double_frames` of 0 there are no double presses, and short presses are reported on release
+    /// rather than once the window has passed.
+    pub const fn new(long_frames: u32, double_frames: u32) -> Self {
+        Gestures {
+            long_frames,
+            double_frames,
+            held_frames: 0,
+            since_short: None,
+            second: false,
+        }
+    }
+
+    /// Forget the press in progress and any short press that could still become a double press.
+    pub fn cancel(&mut self) {
+        self.held_frames = 0;
+        self.since_short = None;
+        self.second = false;
+    }
+
+    /// Feed one frame's btn state and return the gesture it completes, if any.
+    pub fn update(&mut self, pressed: bool) -> Option<Gesture> {
+        if pressed {
+            if self.held_frames == 0 && self.since_short.take().is_some() {
+                self.second = true;
+                self.held_frames = 1;
+                return Some(Gesture::Double);
+            }
+            self.held_frames = self.held_frames.saturating_add(1);
+            return (!self.second && self.held_frames == self.long_frames).then_some(Gesture::Long);
+        }
+
+        let held_frames = core::mem::take(&mut self.held_frames);
+        if held_frames > 0 {
+            let short = !self.second && held_frames < self.long_frames;
+            self.second = false;
+            if short && self.double_frames == 0 {
+                return Some(Gesture::Short);
+            }
+            self.since_short = short.then_some(0);
+            return None;
+        }
+        let since_short = self.since_short? + 1;
+        if since_short >= self.double_frames {
+            self.since_short = None;
+            Some(Gesture::Short)
+        } else {
+            self.since_short = Some(since_short);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ButtonPress, FakeButton};
+
+    #[test]
+    fn a_press_release_press_comes_through_debounced() {
+        // Debounced::new takes the first read; then each state is held for a few samples, with a
+        // 1-sample bounce on the release
+        let mut button = Debounced::new(FakeButton::new(&[
+            false, true, true, true, false, true, false, false, true, true, true,
+        ]));
+        let changes: Vec<(usize, bool)> = (0..11)
+            .filter_map(|sample| button.sample().map(|pressed| (sample, pressed)))
+            .collect();
+        assert_eq!(changes, [(1, true), (6, false), (8, true)]);
+        assert!(button.pressed());
+    }
+
+    #[test]
+    fn a_single_sample_glitch_never_changes_the_state() {
+        let mut button = Debounced::new(FakeButton::new(&[false, true, false, true, false, false]));
+        assert!((0..6).all(|_| button.sample().is_none()));
+        assert!(!button.pressed());
+    }
+
+    /// Feed `frames` frames of `pressed` and collect the gestures reported
+    fn feed(gestures: &mut Gestures, pressed: bool, frames: u32) -> Vec<Gesture> {
+        (0..frames)
+            .filter_map(|_| gestures.update(pressed))
+            .collect()
+    }
+
+    #[test]
+    fn a_tap_is_short_once_the_double_window_passes() {
+        let mut gestures = Gestures::new(10, 4);
+        assert_eq!(feed(&mut gestures, true, 2), []);
+        assert_eq!(feed(&mut gestures, false, 4), []);
+        assert_eq!(feed(&mut gestures, false, 1), [Gesture::Short]);
+        assert_eq!(feed(&mut gestures, false, 10), []);
+    }
+
+    #[test]
+    fn a_second_tap_within_the_window_is_double() {
+        let mut gestures = Gestures::new(10, 4);
+        feed(&mut gestures, true, 2);
+        feed(&mut gestures, false, 3);
+        assert_eq!(feed(&mut gestures, true, 1), [Gesture::Double]);
+        // the second press is neither short nor long
+        assert_eq!(feed(&mut gestures, true, 20), []);
+        assert_eq!(feed(&mut gestures, false, 10), []);
+    }
+
+    #[test]
+    fn a_hold_is_long_while_still_held() {
+        let mut gestures = Gestures::new(10, 4);
+        assert_eq!(feed(&mut gestures, true, 9), []);
+        assert_eq!(feed(&mut gestures, true, 1), [Gesture::Long]);
+        assert_eq!(feed(&mut gestures, true, 5), []);
+        assert_eq!(feed(&mut gestures, false, 10), []);
+    }
+
+    #[test]
+    fn without_a_double_window_a_tap_is_short_on_release() {
+        let mut gestures = Gestures::new(10, 0);
+        feed(&mut gestures, true, 3);
+        assert_eq!(gestures.update(false), Some(Gesture::Short));
+    }
+
+    #[test]
+    fn cancel_forgets_a_pending_short_press() {
+        let mut gestures = Gestures::new(10, 4);
+        feed(&mut gestures, true, 2);
+        feed(&mut gestures, false, 1);
+        gestures.cancel();
+        assert_eq!(feed(&mut gestures, false, 10), []);
+    }
+}
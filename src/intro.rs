@@ -0,0 +1,130 @@
+//! A short splash animation shown at power-up so it is obvious the MB2 has booted.
+//!
+//! A square outline grows from the center LED out to the edge of the grid and shrinks back
+//! again, one ring per frame, over about one second. Pressing either button skips the rest.
+//! It is played by the animation player (animation.rs).
+
+use crate::animation::Animation;
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// How long each frame of the intro is shown
+const INTRO_FRAME_MS: u32 = 200;
+/// Radius of the outermost ring, which touches the nearest edge of the grid
+const MAX_RADIUS: usize = (if ROW_COUNT < COL_COUNT {
+    ROW_COUNT
+} else {
+    COL_COUNT
+} - 1)
+    / 2;
+/// Number of frames: out from the center to MAX_RADIUS and back in again
+const INTRO_FRAMES: usize = 2 * MAX_RADIUS + 1;
+
+/// Frame `index` of the intro: the outline of the square whose rows and columns are all at
+/// most the frame's radius from the center LED.
+pub fn frame(index: usize) -> LEDState {
+    let index = index % INTRO_FRAMES;
+    let radius = if index <= MAX_RADIUS {
+        index
+    } else {
+        INTRO_FRAMES - 1 - index
+    };
+
+    let mut state: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+    for (row, line) in state.iter_mut().enumerate() {
+        for (col, cell) in line.iter_mut().enumerate() {
+            let distance = row.abs_diff(ROW_COUNT / 2).max(col.abs_diff(COL_COUNT / 2));
+            *cell = (distance == radius) as u8;
+        }
+    }
+    state
+}
+
+/// The intro animation.
+pub const INTRO: Animation = Animation {
+    frames: INTRO_FRAMES,
+    frame_ms: INTRO_FRAME_MS,
+    frame,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fade::{self, Levels};
+    use crate::render::Renderer;
+    use embedded_hal::delay::DelayNs;
+
+    /// A backend that keeps every image drawn on it
+    #[derive(Default)]
+    struct Recorder(Vec<Levels>);
+
+    impl Renderer for Recorder {
+        fn render_levels(&mut self, levels: &Levels) {
+            self.0.push(*levels);
+        }
+    }
+
+    /// A delay that only adds up how long it was asked to wait
+    #[derive(Default)]
+    struct Clock {
+        elapsed_ns: u64,
+    }
+
+    impl DelayNs for Clock {
+        fn delay_ns(&mut self, ns: u32) {
+            self.elapsed_ns += ns as u64;
+        }
+    }
+
+    /// The cells lit in `state`, as (row, col)
+    fn lit(state: &LEDState) -> Vec<(usize, usize)> {
+        (0..ROW_COUNT)
+            .flat_map(|row| (0..COL_COUNT).map(move |col| (row, col)))
+            .filter(|(row, col)| state[*row][*col] != 0)
+            .collect()
+    }
+
+    #[test]
+    fn the_square_grows_to_the_edge_and_shrinks_back() {
+        assert_eq!(INTRO_FRAMES, 5);
+        assert_eq!(lit(&frame(0)), [(2, 2)]);
+        assert_eq!(lit(&frame(1)).len(), 8);
+        assert_eq!(frame(1)[2][2], 0);
+        assert_eq!(frame(1)[1][1], 1);
+        // the outermost ring is the border of the grid
+        assert_eq!(lit(&frame(2)).len(), 16);
+        assert!(lit(&frame(2)).iter().all(|(row, col)| {
+            *row == 0 || *row == ROW_COUNT - 1 || *col == 0 || *col == COL_COUNT - 1
+        }));
+        assert_eq!(frame(3), frame(1));
+        assert_eq!(frame(4), frame(0));
+        // indices past the end start over
+        assert_eq!(frame(INTRO_FRAMES), frame(0));
+    }
+
+    #[test]
+    fn the_intro_plays_every_frame_in_about_a_second() {
+        let mut renderer = Recorder::default();
+        let mut clock = Clock::default();
+        INTRO.play(&mut renderer, &mut clock, || false);
+
+        let expected: Vec<Levels> = (0..INTRO_FRAMES)
+            .map(|index| fade::levels(&frame(index)))
+            .collect();
+        assert_eq!(renderer.0, expected);
+        assert_eq!(clock.elapsed_ns, 1_000_000_000);
+    }
+
+    #[test]
+    fn a_press_skips_the_rest_of_the_intro() {
+        let mut renderer = Recorder::default();
+        let mut clock = Clock::default();
+        let mut polls = 0;
+        // pressed during the second frame
+        INTRO.play(&mut renderer, &mut clock, || {
+            polls += 1;
+            polls > 30
+        });
+        assert_eq!(renderer.0.len(), 2);
+        assert!(clock.elapsed_ns < 2 * INTRO_FRAME_MS as u64 * 1_000_000);
+    }
+}
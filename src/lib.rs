@@ -0,0 +1,1266 @@
+//! Lib.rs
+//! Copyright © 2026 Sean Springer
+//! [This program is licensed under the "MIT License"]
+//! Please see the file LICENSE in the source distribution of this software for license terms.
+//!
+//! The hardware-independent half of Conway's Game of Life (GOL) on the Microbit V2 (MB2)
+//!
+//! The Rust code present here can be summarized as follows (top to bottom order):
+//! 1. Declares the modules holding the game logic (game.rs and everything it steps and draws)
+//! 2. Defines a set of constants dictating the LED board size, refresh rates, and game settings
+//! 3. Defines and implements a convience trait for the 2 button Microbit InputPin structs
+//! 4. Defines helper functions which randomize or complement the current board state
+//! 5. Defines and implements a helper struct for simplifying the the refresh rate criteria
+//!
+//! None of it touches the MB2's peripherals except through traits (RandomSource, InputPin, Renderer,
+//! FlashPage) or behind `cfg(target_os = "none")`, so it also builds for the host and its unit tests run there:
+//! `cargo test-host` (an alias of `cargo test --lib --target x86_64-unknown-linux-gnu`, see .cargo/config.toml).
+//! The firmware itself (main.rs and the drivers it declares) only builds for the MB2
+
+#![cfg_attr(not(test), no_std)]
+
+pub mod age;
+pub mod animation;
+pub mod ant;
+#[cfg(not(target_os = "none"))]
+pub mod bench;
+pub mod bitboard;
+pub mod boot;
+pub mod canvas;
+pub mod collapse;
+pub mod cycle;
+pub mod elementary;
+pub mod events;
+pub mod fade;
+pub mod fault;
+pub mod fps;
+pub mod game;
+pub mod generations;
+pub mod gravity;
+pub mod heatmap;
+pub mod highlight;
+pub mod hud;
+pub mod idle;
+pub mod input;
+pub mod intro;
+pub mod life;
+pub mod menu;
+pub mod orientation;
+pub mod pace;
+pub mod persist;
+pub mod random;
+pub mod record;
+pub mod remote;
+pub mod render;
+pub mod rewind;
+pub mod selftest;
+pub mod shake;
+#[cfg(feature = "sparse")]
+pub mod sparse;
+pub mod stats;
+pub mod supersample;
+pub mod symmetry;
+pub mod teams;
+pub mod text;
+pub mod trail;
+pub mod wireworld;
+pub mod ws2812;
+
+use embedded_hal::digital::InputPin;
+
+use random::RandomSource;
+
+/// The MB2 has 5 LED rows and 5 LED columns
+pub const ROW_COUNT: usize = 5;
+pub const COL_COUNT: usize = 5;
+/// Type definition defining the LEDState to be a ROW_COUNT x COL_COUNT (5x5) array of u8 variables
+pub type LEDState = [[u8; COL_COUNT]; ROW_COUNT];
+// boards are packed into (and randomized from) the bits of a single u32
+const _: () = assert!(ROW_COUNT * COL_COUNT <= 32);
+/// Spec 1: 10 frames per second refresh rate (100ms)
+pub const REFRESH_RATE_MS: u32 = 100;
+/// Per Spec 5: a "dead" state waits 500ms
+pub const DEATH_RESET_RATE_MS: u32 = 500;
+/// Per Spec 2: percentage of LEDs lit by a randomization. 50% uses the hardware random bits directly;
+/// lower values (e.g. 30) tend to give longer-lived starts on the 5x5 grid
+pub const RANDOM_DENSITY_PERCENT: u8 = 50;
+/// Per Spec 4: a complement action can only occur 1 time per 500ms
+pub const COMPLEMENT_RESET_RATE_MS: u32 = 500;
+/// With the `b-repeat` cargo feature, holding B repeats the complement, starting COMPLEMENT_RESET_RATE_MS
+/// apart and speeding up to at most once per B_REPEAT_FLOOR_MS (see game::AutoRepeat)
+pub const B_REPEAT_ENABLED: bool = cfg!(feature = "b-repeat");
+pub const B_REPEAT_FLOOR_MS: u32 = 100;
+/// With the `rewind` cargo feature, holding B at least REWIND_HOLD_MS steps back through the last REWIND_DEPTH
+/// Life generations, one per frame (see rewind.rs). This replaces the `b-repeat` repeating complement
+pub const REWIND_ENABLED: bool = cfg!(feature = "rewind");
+pub const REWIND_HOLD_MS: u32 = 500;
+pub const REWIND_DEPTH: usize = 64;
+/// Holding the A btn at least this long is a long press, which freezes the board in single-step mode
+pub const SINGLE_STEP_HOLD_MS: u32 = 1_000;
+/// The edges of the grid wrap around (a torus), or with the `mobius` cargo feature the left and right edges
+/// wrap with a twist while the top and bottom are bounded, or with the `reflect` cargo feature every edge
+/// reflects neighbor lookups back onto the grid, or with the `bounded` cargo feature every edge is bounded
+/// (see life::EdgeMode)
+pub const EDGE_MODE: life::EdgeMode = if cfg!(feature = "mobius") {
+    life::EdgeMode::Mobius
+} else if cfg!(feature = "reflect") {
+    life::EdgeMode::Reflect
+} else if cfg!(feature = "bounded") {
+    life::EdgeMode::Bounded
+} else {
+    life::EdgeMode::Toroidal
+};
+/// With the `walls` cargo feature, the border LEDs are frozen as WALL_KIND walls enclosing the 3x3 interior
+/// (see life::Walls) while the Life rules are selected
+pub const WALLS: life::Walls = if cfg!(feature = "walls") {
+    life::border_walls(WALL_KIND)
+} else {
+    life::NO_WALLS
+};
+/// Whether the `walls` feature's border is permanently lit (counted as live neighbors), or with the
+/// `dead-walls` cargo feature permanently dark
+pub const WALL_KIND: life::Wall = if cfg!(feature = "dead-walls") {
+    life::Wall::Dead
+} else {
+    life::Wall::Live
+};
+/// Size of the virtual board stepped by the Canvas step mode (canvas.rs), of which a 5x5 window is shown:
+/// 32x32, or 64x64 with the `sparse` cargo feature
+pub const CANVAS_ROW_COUNT: usize = if cfg!(feature = "sparse") { 64 } else { 32 };
+pub const CANVAS_COL_COUNT: usize = if cfg!(feature = "sparse") { 64 } else { 32 };
+/// Cells of the Supersample step mode's fine board per LED along each axis, so 2 steps a 10x10 board
+/// (supersample.rs)
+pub const SUPERSAMPLE_SCALE: usize = 2;
+/// Most live cells the `sparse` feature's canvas board can hold (sparse.rs)
+pub const SPARSE_CAPACITY: usize = 512;
+/// The Evolving step mode's rule mutates (one birth or survival count toggled) every this many generations
+pub const EVOLVE_GENERATIONS: u32 = 50;
+/// Cells lit along the lowest edge every frame the MB2 is tilted in the Pour step mode
+pub const POUR_CELLS: u8 = 2;
+/// Spec 28: in the demo mode selected at power-up, the step mode changes this often
+pub const DEMO_MODE_MS: u32 = 15_000;
+/// Most messages (mode names, rules, numbers) the Game can have waiting to scroll across the grid (text.rs)
+pub const BANNER_QUEUE_LEN: usize = 2;
+/// Spec 23: the display brightness setting at power-up, 1 to render::BRIGHTNESS_LEVELS; the host can change it over RTT
+/// (remote.rs)
+pub const DEFAULT_BRIGHTNESS: u8 = render::BRIGHTNESS_LEVELS;
+/// While the MB2 is tilted in the Canvas step mode, the window pans one cell this often towards the lowered edge
+pub const CANVAS_PAN_MS: u32 = 200;
+/// With the `heatmap` cargo feature, the MB2 powers up showing how many generations each cell has been alive
+/// (heatmap.rs), with the live cells drawn over it, instead of the generation crossfade. The heatmap is
+/// toggled while running in the settings menu (menu.rs)
+pub const HEATMAP_ENABLED: bool = cfg!(feature = "heatmap");
+/// With the `mutation` cargo feature, each LED may also flip after a Life step so the board never fully settles
+pub const MUTATION_ENABLED: bool = cfg!(feature = "mutation");
+/// Chance (in permille) of each LED flipping after a Life step with the `mutation` feature, until another
+/// rate is set in the settings menu (menu.rs) or by the host over RTT (remote.rs)
+pub const MUTATION_RATE_PERMILLE: u16 = 10;
+/// With the `inject` cargo feature, a glider is stamped onto a sparse (but not empty) board after a Life step
+pub const INJECT_ENABLED: bool = cfg!(feature = "inject");
+/// Population below which the `inject` feature considers a board sparse
+pub const INJECT_THRESHOLD: u8 = 3;
+/// With the `cycle-restart` cargo feature (on by default), a Life board repeating a recent generation restarts like a dead board
+pub const CYCLE_RESTART_ENABLED: bool = cfg!(feature = "cycle-restart");
+/// Number of recent generations remembered for cycle detection, the longest period detected (cycle.rs)
+pub const CYCLE_HISTORY: usize = 8;
+/// Spec 24: time between generations, independent of the REFRESH_RATE_MS frame rate; the host can change it over RTT
+/// (remote.rs)
+pub const STEP_INTERVAL_MS: u32 = REFRESH_RATE_MS;
+/// Most steps taken in one frame when fast-forwarding with a step interval shorter than a frame
+pub const MAX_STEPS_PER_FRAME: u32 = 10;
+/// Generations computed per displayed frame in normal play (only the last is shown): 1, or 4 with the
+/// `turbo` cargo feature for watching fast-evolving patterns without speeding up the display
+pub const STEPS_PER_FRAME: u32 = if cfg!(feature = "turbo") { 4 } else { 1 };
+/// Holding the A+B chord at least this long shows the population HUD instead of opening the settings menu
+pub const CHORD_HOLD_MS: u32 = 500;
+/// After the A btn is released it must read released for this long before a new press is accepted,
+/// so a noisy release does not trigger a trailing re-randomization
+pub const A_RELEASE_MS: u32 = 200;
+/// A second press of the A btn within this long of releasing a short press makes it a double press
+/// (Spec 25). It has to outlast A_RELEASE_MS for a second press to be seen at all
+pub const DOUBLE_PRESS_MS: u32 = 400;
+
+/// ButtonPress Trait
+///
+/// Defines a convience trait that can extend the methods available to the HAL GPIO pins.
+/// Requires that the implementors of the ButtonPress trait also implement the Hal::digital::InputPin
+/// trait for meaningful implementation
+pub trait ButtonPress: InputPin {
+    /// fn pressed(&mut self) -> bool : Abstract!
+    ///
+    /// Must be defined by the implementor. Should return true if the button is pressed and false otherwise.
+    /// Alternatively, this function could be interpreted as returning true if the user is influence the
+    /// InputPin to be in a state other than it's Reset state
+    fn pressed(&mut self) -> bool;
+}
+
+/// Implementation of ButtonPress trait for every InputPin wired like the A (bus 0, pin 14) and B
+/// (bus 0, pin 23) btns
+///
+/// Implemented for any InputPin rather than the two concrete pins so that the btn-consuming logic
+/// (the startup checks, input::Debounced, and the animation skips) can use any pin, including a
+/// scripted stand-in for the real btns
+impl<T: InputPin> ButtonPress for T {
+    /// Returns true if the voltage on the pin is equal to ground.
+    /// The A and B btns are pressed when the voltage is equal to ground because these btns are Momentary
+    /// switches (Normally Open) and so the pressed state completes the circuit (see the nRF52820 schematic)
+    ///
+    /// This is a single raw read: once the game is running the btns are wrapped in input::Debounced, whose
+    /// debounced state is what is read here
+    fn pressed(&mut self) -> bool {
+        self.is_low().unwrap()
+    }
+}
+
+/// FakeButton Struct
+///
+/// A scripted stand-in for a btn pin in the host tests: each read of the pin returns the next entry
+/// of the script (true for pressed, i.e. reading low), and the last entry once the script has run out.
+/// It is an InputPin, and so a ButtonPress through the implementation above
+#[cfg(test)]
+pub(crate) struct FakeButton {
+    script: &'static [bool],
+    next: usize,
+}
+
+#[cfg(test)]
+impl FakeButton {
+    /// Script the btn's reads, one entry per read (an empty script reads released)
+    pub(crate) fn new(script: &'static [bool]) -> Self {
+        FakeButton { script, next: 0 }
+    }
+}
+
+#[cfg(test)]
+impl embedded_hal::digital::ErrorType for FakeButton {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(test)]
+impl InputPin for FakeButton {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.is_low()?)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        let pressed = self.script.get(self.next).or(self.script.last()).copied();
+        self.next += 1;
+        Ok(pressed.unwrap_or(false))
+    }
+}
+
+/// fn unpack_board(u32) -> LEDState
+///
+/// Takes a u32 and returns the LEDState it encodes.
+///
+/// Bit i of the u32 (counting from the least significant bit, bit 0) is assigned to LED i, where
+/// LEDs are numbered in row-major order from top-left to bottom-right, i.e. bit i sets
+/// state[i / COL_COUNT][i % COL_COUNT]. Only the right-most (least significant) 25 bits are used;
+/// bits 25-31 are ignored. For example 0x0000_001F lights the top row and 0x0100_0000 lights only
+/// the bottom-right LED.
+///
+/// This is the same mapping used by the hex boards accepted over RTT (remote.rs), and the 25
+/// character rtt-log output lists LEDs in the same order, so logged and loaded boards round-trip
+pub fn unpack_board(bits: u32) -> LEDState {
+    const LED_COUNT: usize = ROW_COUNT * COL_COUNT;
+    let mut state: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+
+    for i in 0..LED_COUNT {
+        let row: usize = i / COL_COUNT;
+        let col: usize = i % COL_COUNT;
+
+        //extract bit at ith position (counting from the LSB) as 1 or 0 then cast as u8
+        let bit: u8 = ((bits >> i) & 1) as u8;
+        state[row][col] = bit;
+    }
+    state
+}
+
+/// fn pack_board(&LEDState) -> u32
+///
+/// Takes a reference to an LEDState and returns it packed into the 25 least significant bits of a
+/// u32, the inverse of unpack_board(). Any non-zero LED value packs as a 1 bit
+pub fn pack_board(state: &LEDState) -> u32 {
+    live_cells(state).fold(0, |bits, (row, col)| bits | 1 << (row * COL_COUNT + col))
+}
+
+/// fn live_cells(&LEDState) -> impl Iterator<Item = (usize, usize)>
+///
+/// Takes a reference to an LEDState and returns an iterator over the (row, col) coordinates of each
+/// lit LED in row-major order (top-left to bottom-right), without allocating
+pub fn live_cells(state: &LEDState) -> impl Iterator<Item = (usize, usize)> + '_ {
+    state.iter().enumerate().flat_map(|(row, line)| {
+        line.iter()
+            .enumerate()
+            .filter(|(_, item)| **item != 0)
+            .map(move |(col, _)| (row, col))
+    })
+}
+
+/// fn randomize_state(&mut impl RandomSource, &mut LEDState)
+///
+/// Takes a mutable reference to a RandomSource (normally the Hal hardware random number
+/// generator) and a mutable references to the 5x5 array LEDState which is altered in-place.
+///
+/// Randomizes the board at the configured RANDOM_DENSITY_PERCENT, see randomize_state_density()
+pub fn randomize_state<R: RandomSource>(random_gen: &mut R, state: &mut LEDState) {
+    randomize_state_density(random_gen, state, RANDOM_DENSITY_PERCENT);
+}
+
+/// fn randomize_symmetric(&mut impl RandomSource, &mut LEDState, SymmetryMode, u8)
+///
+/// Randomizes the board at the given density (normally RANDOM_DENSITY_PERCENT, see
+/// randomize_state_density()) and then mirrors/rotates one region of it across the rest of the
+/// board according to mode (see symmetry.rs)
+pub fn randomize_symmetric<R: RandomSource>(
+    random_gen: &mut R,
+    state: &mut LEDState,
+    mode: symmetry::SymmetryMode,
+    density: u8,
+) {
+    randomize_state_density(random_gen, state, density);
+    symmetry::symmetrize(state, mode);
+}
+
+/// fn randomize_state_density(&mut impl RandomSource, &mut LEDState, u8)
+///
+/// Takes a mutable reference to a RandomSource (normally the Hal hardware random number generator),
+/// a mutable references to the 5x5 array LEDState which is altered in-place, and
+/// the percentage (0..=100, larger values are treated as 100) of LEDs that should be lit.
+///
+/// Only a single u32 is drawn from the (slow) random source. At 50% its
+/// 25 least significant bits are assigned to the LEDState in order (bit 0 to the top-left LED
+/// through bit 24 to the bottom-right LED), see unpack_board(). For any other density the
+/// draw seeds a xorshift32 generator whose output bytes are compared against a threshold, one
+/// byte per LED, so each LED is lit with the requested probability
+pub fn randomize_state_density<R: RandomSource>(
+    random_gen: &mut R,
+    state: &mut LEDState,
+    density: u8,
+) {
+    let seed: u32 = random_gen.random_u32();
+
+    if density == 50 {
+        *state = unpack_board(seed);
+        return;
+    }
+
+    // a byte below threshold (out of 256) lights the LED
+    let threshold: u16 = (density.min(100) as u16 * 256 + 50) / 100;
+    let mut prng = random::XorShift32::new(seed);
+    let mut bytes = [0u8; 4];
+
+    for (i, item) in state.iter_mut().flatten().enumerate() {
+        if i % 4 == 0 {
+            bytes = prng.next_u32().to_le_bytes();
+        }
+        *item = ((bytes[i % 4] as u16) < threshold) as u8;
+    }
+}
+
+/// fn complement_state(&mut LEDState)
+///
+/// Takes a mutable reference to the current LEDState and alters it in-place
+///
+/// Given the current LEDState, iterate through each LED Diode and flip its state
+/// (on->off and off->on). Each LED is mutably iterated through and its state is
+/// complemented using XOR boolean logic
+pub fn complement_state(state: &mut LEDState) {
+    for row in state.iter_mut() {
+        for item in row.iter_mut() {
+            *item ^= 1;
+        }
+    }
+}
+
+/// fn mutate(&mut impl RandomSource, &mut LEDState, u16)
+///
+/// Takes a random source, a mutable reference to the current LEDState which is altered in-place,
+/// and a per-LED mutation rate in permille (parts per 1000)
+///
+/// Each LED is flipped (on->off and off->on) with probability rate_permille / 1000, so a rate of
+/// 10 flips one LED every 4 frames on average. Only a single u32 is drawn from the (slow) random
+/// source; it seeds a xorshift32 generator which supplies one value per LED
+pub fn mutate<R: RandomSource>(random_gen: &mut R, state: &mut LEDState, rate_permille: u16) {
+    let mut prng = random::XorShift32::new(random_gen.random_u32());
+
+    for item in state.iter_mut().flatten() {
+        if prng.next_u32() % 1_000 < rate_permille as u32 {
+            *item ^= 1;
+        }
+    }
+}
+
+/// fn pour_edge(&mut impl RandomSource, &mut LEDState, gravity::Direction, u8)
+///
+/// Takes a random source, a mutable reference to the current LEDState which is altered in-place,
+/// the edge of the grid that is lowest, and a number of cells
+///
+/// Lights count random LEDs (some may be lit already, or picked twice) along the edge, or none if the
+/// MB2 is lying flat. Only a single u32 is drawn from the (slow) random source; it seeds a xorshift32
+/// generator which supplies one value per cell
+pub fn pour_edge<R: RandomSource>(
+    random_gen: &mut R,
+    state: &mut LEDState,
+    edge: gravity::Direction,
+    count: u8,
+) {
+    let mut prng = random::XorShift32::new(random_gen.random_u32());
+
+    for _ in 0..count {
+        let i = prng.next_u32() as usize;
+        let (row, col) = match edge {
+            gravity::Direction::Up => (0, i % COL_COUNT),
+            gravity::Direction::Down => (ROW_COUNT - 1, i % COL_COUNT),
+            gravity::Direction::Left => (i % ROW_COUNT, 0),
+            gravity::Direction::Right => (i % ROW_COUNT, COL_COUNT - 1),
+            gravity::Direction::None => return,
+        };
+        state[row][col] = 1;
+    }
+}
+
+/// fn spawn_cells(&mut impl RandomSource, &mut LEDState, u8)
+///
+/// Takes a random source, a mutable reference to the current LEDState which is altered in-place,
+/// and a number of cells
+///
+/// Lights count random LEDs anywhere on the board (some may be lit already, or picked twice). Only a
+/// single u32 is drawn from the (slow) random source; it seeds a xorshift32 generator which supplies
+/// one value per cell
+pub fn spawn_cells<R: RandomSource>(random_gen: &mut R, state: &mut LEDState, count: u8) {
+    let mut prng = random::XorShift32::new(random_gen.random_u32());
+
+    for _ in 0..count {
+        let led = prng.next_u32() as usize % (ROW_COUNT * COL_COUNT);
+        state[led / COL_COUNT][led % COL_COUNT] = 1;
+    }
+}
+
+/// fn inject_if_sparse(&mut impl RandomSource, &mut LEDState, u8, life::EdgeMode) -> bool
+///
+/// Takes a random source, a mutable reference to the current LEDState which is altered in-place,
+/// a population threshold, and the edge mode the board is stepped with
+///
+/// If the board is not empty but has fewer than threshold lit LEDs, a glider (life::GLIDER) is
+/// stamped into the first empty 3x3 area found starting from a random LED, and true is returned.
+/// On the torus the areas wrap around the edges; on any other edges a glider split across them
+/// would not be a glider, so the areas running past the bottom or right edge are skipped and the
+/// glider is placed with life::OverflowMode::Clip. A board at or above the threshold, an empty
+/// board (left to the dead-board restart), or one without an empty 3x3 area is left untouched and
+/// false is returned
+pub fn inject_if_sparse<R: RandomSource>(
+    random_gen: &mut R,
+    state: &mut LEDState,
+    threshold: u8,
+    edges: life::EdgeMode,
+) -> bool {
+    const LED_COUNT: usize = ROW_COUNT * COL_COUNT;
+
+    let population = live_cells(state).count();
+    if population == 0 || population >= threshold as usize {
+        return false;
+    }
+
+    let overflow = if edges == life::EdgeMode::Toroidal {
+        life::OverflowMode::Wrap
+    } else {
+        life::OverflowMode::Clip
+    };
+    let start = random_gen.random_u32() as usize % LED_COUNT;
+    for i in 0..LED_COUNT {
+        let led = (start + i) % LED_COUNT;
+        let origin = (led / COL_COUNT, led % COL_COUNT);
+        if overflow == life::OverflowMode::Clip
+            && (origin.0 + 3 > ROW_COUNT || origin.1 + 3 > COL_COUNT)
+        {
+            continue;
+        }
+        let empty = (0..3).all(|dr| {
+            (0..3).all(|dc| {
+                let (row, col) = life::wrap::<COL_COUNT, ROW_COUNT>(origin.0 + dr, origin.1 + dc);
+                state[row][col] == 0
+            })
+        });
+        if empty {
+            life::place_pattern(state, &life::GLIDER, origin, overflow);
+            return true;
+        }
+    }
+    false
+}
+
+/// fn complement_masked(&mut LEDState, &LEDState)
+///
+/// Takes a mutable reference to the current LEDState which is altered in-place, and a mask
+///
+/// A more surgical complement_state: only the LEDs whose mask cell is non-zero are flipped (using
+/// the same XOR logic), every other LED is left untouched. See CENTER_MASK, QUADRANT_MASK, and
+/// BORDER_MASK
+pub fn complement_masked(state: &mut LEDState, mask: &LEDState) {
+    for (item, masked) in state.iter_mut().flatten().zip(mask.iter().flatten()) {
+        if *masked != 0 {
+            *item ^= 1;
+        }
+    }
+}
+
+/// fn mask_rect(usize, usize, usize, usize, bool) -> LEDState
+///
+/// Builds a complement mask of the rectangle of rows top..bottom and cols left..right (bottom and
+/// right exclusive), or of everything outside it if inside is false
+pub const fn mask_rect(
+    top: usize,
+    left: usize,
+    bottom: usize,
+    right: usize,
+    inside: bool,
+) -> LEDState {
+    let mut mask: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+    let mut row = 0;
+    while row < ROW_COUNT {
+        let mut col = 0;
+        while col < COL_COUNT {
+            let in_rect = row >= top && row < bottom && col >= left && col < right;
+            mask[row][col] = (in_rect == inside) as u8;
+            col += 1;
+        }
+        row += 1;
+    }
+    mask
+}
+
+/// Complement mask of every LED not on the outer edge (the center 3x3 on the MB2)
+pub const CENTER_MASK: LEDState = mask_rect(1, 1, ROW_COUNT - 1, COL_COUNT - 1, true);
+/// Complement mask of the top-left quadrant, including the middle row and column (3x3 on the MB2)
+pub const QUADRANT_MASK: LEDState =
+    mask_rect(0, 0, ROW_COUNT.div_ceil(2), COL_COUNT.div_ceil(2), true);
+/// Complement mask of the outer edge of the grid
+pub const BORDER_MASK: LEDState = mask_rect(1, 1, ROW_COUNT - 1, COL_COUNT - 1, false);
+
+/// fn complement_neighborhood(&mut LEDState, life::EdgeMode)
+///
+/// Takes a mutable reference to the current LEDState and alters it in-place, and the edge mode the
+/// board is being stepped with
+///
+/// A gentler alternative to complement_state: only the unlit LEDs which touch at least one lit LED
+/// (counting neighbors the same way as the GOL step, see life::neighbor_count) are flipped on.
+/// Lit LEDs and unlit LEDs in empty regions of the board are left untouched. Neighbors are counted
+/// against a snapshot of the board taken before any flips, so a flip made by this call never causes
+/// further flips within the same call
+pub fn complement_neighborhood(state: &mut LEDState, edges: life::EdgeMode) {
+    let snapshot = *state;
+
+    for (row, line) in state.iter_mut().enumerate() {
+        for (col, item) in line.iter_mut().enumerate() {
+            if *item == 0 && life::neighbor_count(&snapshot, row, col, edges) > 0 {
+                *item ^= 1;
+            }
+        }
+    }
+}
+
+/// fn ms_to_frames(u32, u32) -> u32
+///
+/// Converts a duration in milliseconds into a number of refresh frames of refresh_ms each.
+/// The result is rounded to the nearest whole frame (so 550ms at a 120ms refresh rate becomes
+/// 5 frames rather than silently truncating to 4) and is never less than 1 frame, so that a timer
+/// built from it can never be finished before it has been ticked at least once
+pub const fn ms_to_frames(ms: u32, refresh_ms: u32) -> u32 {
+    let remainder = ms % refresh_ms;
+    let mut frames = ms / refresh_ms;
+
+    // round half up without risking overflow on 2 * remainder
+    if remainder >= refresh_ms - remainder {
+        frames += 1;
+    }
+
+    if frames == 0 { 1 } else { frames }
+}
+
+/// fn time_left_us(u32, u32, u32) -> u32
+///
+/// Returns how many microseconds are left at now until offset_us after start, all read from a
+/// free-running microsecond clock, or 0 once that deadline has passed (a frame whose logic
+/// overran its budget never yields a huge or underflowed wait). The elapsed time is taken with
+/// wrapping arithmetic, so it stays correct across the clock wrapping through 0
+pub const fn time_left_us(start: u32, now: u32, offset_us: u32) -> u32 {
+    offset_us.saturating_sub(now.wrapping_sub(start))
+}
+
+/// ResetTimer Struct
+///
+/// The ResetTimer struct tracks a current loop count (multiple of the REFRESH_RATE_MS) and a
+/// total loop count (also a multiple of REFRESH_RATE_MS) to determine when a period of time has elapsed.
+/// Frame totals should be built with ms_to_frames() so that the rounding rules are applied uniformly.
+pub struct ResetTimer {
+    current: u32,
+    total: u32,
+}
+
+/// Impl ResetTimer
+///
+/// Provides method to initalize the reset timer, reset its counting, update the clock,
+/// and check if the timer has expired
+impl ResetTimer {
+    /// fn new(u32) -> Self
+    ///
+    /// Returns a new ResetTimer instance that expires after total_frames frames.
+    /// The timer is initialized to its finished() == true state so that the first action it guards
+    /// is not delayed. Use reset() to restart the timer
+    pub fn new(total_frames: u32) -> Self {
+        ResetTimer {
+            current: total_frames,
+            total: total_frames,
+        }
+    }
+
+    /// fn reset(&mut self)
+    ///
+    /// reset the timer to it's starting state (furthest from expired)
+    pub fn reset(&mut self) {
+        self.current = 0;
+    }
+
+    /// fn tick(&mut self, bool) -> bool
+    ///
+    /// This method will update the timer's count by one frame, returning true if this update
+    /// has caused the timer to expire and false otherwise. If reset_if_finished is true,
+    /// then the internal timer state will reset if this function returns true
+    pub fn tick(&mut self, reset_if_finished: bool) -> bool {
+        self.current = self.current.saturating_add(1).min(self.total);
+
+        let is_finished = self.finished();
+        if is_finished && reset_if_finished {
+            self.reset();
+        }
+
+        is_finished
+    }
+
+    /// fn finsihed(&self) -> bool
+    ///
+    /// This method will return true if the timer has expired and false otherwise
+    pub fn finished(&self) -> bool {
+        self.current >= self.total
+    }
+
+    /// fn remaining(&self) -> u32
+    ///
+    /// Returns the number of frames left before the timer expires (0 once finished)
+    pub fn remaining(&self) -> u32 {
+        self.total.saturating_sub(self.current)
+    }
+
+    /// fn progress(&self) -> f32
+    ///
+    /// Returns how far along the timer is as a fraction in 0.0..=1.0, where 0.0 is freshly reset
+    /// and 1.0 is expired. Used to brighten the empty board's pulse as its restart approaches
+    pub fn progress(&self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+
+        1.0 - self.remaining() as f32 / self.total as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A RandomSource that always draws the same u32
+    struct Fixed(u32);
+
+    impl RandomSource for Fixed {
+        fn random_u32(&mut self) -> u32 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn a_fake_button_plays_its_script_through_button_press() {
+        let mut button = FakeButton::new(&[true, false, true]);
+        let presses: Vec<bool> = (0..5).map(|_| button.pressed()).collect();
+        // the last entry holds once the script runs out
+        assert_eq!(presses, [true, false, true, true, true]);
+        assert!(!FakeButton::new(&[]).pressed());
+    }
+
+    #[test]
+    fn live_cells_yields_the_lit_leds_in_row_major_order() {
+        let state = [
+            [1, 0, 0, 0, 1],
+            [0, 0, 0, 0, 0],
+            [0, 0, 9, 0, 0],
+            [0, 1, 0, 0, 0],
+            [0, 0, 0, 1, 0],
+        ];
+        let cells: Vec<(usize, usize)> = live_cells(&state).collect();
+        assert_eq!(cells, [(0, 0), (0, 4), (2, 2), (3, 1), (4, 3)]);
+    }
+
+    #[test]
+    fn live_cells_yields_nothing_for_an_empty_board() {
+        assert_eq!(live_cells(&[[0; COL_COUNT]; ROW_COUNT]).count(), 0);
+        assert_eq!(
+            live_cells(&[[1; COL_COUNT]; ROW_COUNT]).count(),
+            ROW_COUNT * COL_COUNT
+        );
+    }
+
+    #[test]
+    fn complement_masked_flips_only_the_masked_leds() {
+        for mask in [CENTER_MASK, QUADRANT_MASK, BORDER_MASK] {
+            let before = unpack_board(0x0155_A5C3);
+            let mut state = before;
+            complement_masked(&mut state, &mask);
+            for row in 0..ROW_COUNT {
+                for col in 0..COL_COUNT {
+                    let flipped = state[row][col] != before[row][col];
+                    assert_eq!(flipped, mask[row][col] != 0, "({row}, {col})");
+                }
+            }
+            // flipping the same LEDs again restores the board
+            complement_masked(&mut state, &mask);
+            assert_eq!(state, before);
+        }
+    }
+
+    /// Every edge mode the GOL step (and so complement_neighborhood) supports
+    const EDGE_MODES: [life::EdgeMode; 4] = [
+        life::EdgeMode::Bounded,
+        life::EdgeMode::Toroidal,
+        life::EdgeMode::Mobius,
+        life::EdgeMode::Reflect,
+    ];
+
+    /// A board lit at `cells`
+    fn board_of(cells: &[(usize, usize)]) -> LEDState {
+        let mut state = [[0; COL_COUNT]; ROW_COUNT];
+        for &(row, col) in cells {
+            state[row][col] = 1;
+        }
+        state
+    }
+
+    #[test]
+    fn complement_neighborhood_lights_the_8_neighbors_of_a_lone_cell() {
+        for edges in EDGE_MODES {
+            let mut state = board_of(&[(2, 2)]);
+            complement_neighborhood(&mut state, edges);
+            let mut block = [[0; COL_COUNT]; ROW_COUNT];
+            for line in &mut block[1..4] {
+                line[1..4].fill(1);
+            }
+            assert_eq!(state, block, "{edges:?}");
+        }
+    }
+
+    #[test]
+    fn complement_neighborhood_counts_against_the_board_before_any_flips() {
+        // a horizontal blinker in the middle row only touches the rows either side of it, so
+        // the outer rows stay dark even though the newly lit rows next to them touch them
+        let mut state = board_of(&[(2, 1), (2, 2), (2, 3)]);
+        complement_neighborhood(&mut state, life::EdgeMode::Bounded);
+        let mut expected = [[1; COL_COUNT]; ROW_COUNT];
+        expected[0] = [0; COL_COUNT];
+        expected[ROW_COUNT - 1] = [0; COL_COUNT];
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn complement_neighborhood_reaches_across_the_edges_it_wraps() {
+        // a lone cell in the top-left corner, plus the cells each mode sees next to it
+        let bounded = [(0, 0), (0, 1), (1, 0), (1, 1)];
+        let cases: [(life::EdgeMode, &[(usize, usize)]); 4] = [
+            (life::EdgeMode::Bounded, &bounded),
+            (life::EdgeMode::Reflect, &bounded),
+            (
+                life::EdgeMode::Toroidal,
+                &[(0, 4), (1, 4), (4, 0), (4, 1), (4, 4)],
+            ),
+            (life::EdgeMode::Mobius, &[(3, 4), (4, 4)]),
+        ];
+        for (edges, extra) in cases {
+            let mut state = board_of(&[(0, 0)]);
+            complement_neighborhood(&mut state, edges);
+            let mut expected = board_of(&bounded);
+            for &(row, col) in extra {
+                expected[row][col] = 1;
+            }
+            assert_eq!(state, expected, "{edges:?}");
+        }
+    }
+
+    #[test]
+    fn the_masks_cover_their_regions() {
+        assert_eq!(live_cells(&CENTER_MASK).count(), 9);
+        assert_eq!(CENTER_MASK[0], [0; COL_COUNT]);
+        assert_eq!(CENTER_MASK[2], [0, 1, 1, 1, 0]);
+        assert_eq!(
+            live_cells(&QUADRANT_MASK).collect::<Vec<_>>(),
+            [
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 1),
+                (1, 2),
+                (2, 0),
+                (2, 1),
+                (2, 2)
+            ]
+        );
+        // the border is exactly what the centre is not
+        assert_eq!(live_cells(&BORDER_MASK).count(), 16);
+        for (border, center) in BORDER_MASK
+            .iter()
+            .flatten()
+            .zip(CENTER_MASK.iter().flatten())
+        {
+            assert_ne!(border, center);
+        }
+        // the full complement flips every LED
+        let mut state = [[0; COL_COUNT]; ROW_COUNT];
+        complement_masked(&mut state, &[[1; COL_COUNT]; ROW_COUNT]);
+        assert_eq!(state, [[1; COL_COUNT]; ROW_COUNT]);
+    }
+
+    /// Whether `state` holds the glider stamped with its (0, 0) offset at some LED, wrapping
+    fn holds_a_glider(state: &LEDState, overflow: life::OverflowMode) -> bool {
+        (0..ROW_COUNT * COL_COUNT).any(|led| {
+            let mut glider = [[0; COL_COUNT]; ROW_COUNT];
+            life::place_pattern(
+                &mut glider,
+                &life::GLIDER,
+                (led / COL_COUNT, led % COL_COUNT),
+                overflow,
+            );
+            live_cells(&glider).count() == life::GLIDER.len()
+                && live_cells(&glider).all(|(row, col)| state[row][col] != 0)
+        })
+    }
+
+    #[test]
+    fn inject_if_sparse_stamps_a_glider_below_the_threshold() {
+        for seed in [0, 7, 12, 24, 0x2545_F491] {
+            let mut state = [[0; COL_COUNT]; ROW_COUNT];
+            state[2][2] = 1;
+            state[2][3] = 1;
+            assert!(inject_if_sparse(
+                &mut Fixed(seed),
+                &mut state,
+                3,
+                life::EdgeMode::Toroidal
+            ));
+            // the glider lands clear of the LEDs already lit, which are kept
+            assert_eq!(live_cells(&state).count(), 2 + life::GLIDER.len());
+            assert!(state[2][2] != 0 && state[2][3] != 0);
+            assert!(holds_a_glider(&state, life::OverflowMode::Wrap));
+        }
+    }
+
+    #[test]
+    fn inject_if_sparse_keeps_gliders_whole_on_bounded_edges() {
+        // from every starting LED, including the ones whose 3x3 area would run past an edge
+        for seed in 0..(ROW_COUNT * COL_COUNT) as u32 {
+            let mut state = [[0; COL_COUNT]; ROW_COUNT];
+            state[0][0] = 1;
+            assert!(inject_if_sparse(
+                &mut Fixed(seed),
+                &mut state,
+                3,
+                life::EdgeMode::Bounded
+            ));
+            assert_eq!(live_cells(&state).count(), 1 + life::GLIDER.len());
+            assert!(holds_a_glider(&state, life::OverflowMode::Clip));
+        }
+    }
+
+    #[test]
+    fn inject_if_sparse_is_a_no_op_at_or_above_the_threshold_and_on_an_empty_board() {
+        let sparse = unpack_board(0b111);
+        for threshold in [0, 1, 3] {
+            let mut state = sparse;
+            assert!(!inject_if_sparse(
+                &mut Fixed(0),
+                &mut state,
+                threshold,
+                life::EdgeMode::Toroidal
+            ));
+            assert_eq!(state, sparse);
+        }
+        let mut empty = [[0; COL_COUNT]; ROW_COUNT];
+        assert!(!inject_if_sparse(
+            &mut Fixed(0),
+            &mut empty,
+            3,
+            life::EdgeMode::Toroidal
+        ));
+        assert_eq!(empty, [[0; COL_COUNT]; ROW_COUNT]);
+    }
+
+    #[test]
+    fn inject_if_sparse_needs_an_empty_3x3_area() {
+        // a lit LED in every other row and column leaves no 3x3 area empty
+        let mut state = [[0; COL_COUNT]; ROW_COUNT];
+        for (row, col) in [
+            (0, 0),
+            (0, 2),
+            (2, 0),
+            (2, 2),
+            (0, 4),
+            (4, 0),
+            (2, 4),
+            (4, 2),
+            (4, 4),
+        ] {
+            state[row][col] = 1;
+        }
+        let before = state;
+        assert!(!inject_if_sparse(
+            &mut Fixed(0),
+            &mut state,
+            10,
+            life::EdgeMode::Toroidal
+        ));
+        assert_eq!(state, before);
+    }
+
+    /// How many LEDs differ between two boards
+    fn flips(before: &LEDState, after: &LEDState) -> usize {
+        before
+            .iter()
+            .flatten()
+            .zip(after.iter().flatten())
+            .filter(|(a, b)| a != b)
+            .count()
+    }
+
+    #[test]
+    fn mutate_flips_nothing_at_rate_0_and_everything_at_1000() {
+        let before = unpack_board(0x0155_A5C3);
+        let mut state = before;
+        mutate(&mut Fixed(0x2545_F491), &mut state, 0);
+        assert_eq!(state, before);
+        mutate(&mut Fixed(0x2545_F491), &mut state, 1_000);
+        assert_eq!(flips(&before, &state), ROW_COUNT * COL_COUNT);
+    }
+
+    /// Cells flipped at 100 permille by the xorshift32 generator seeded with 0x2545_F491
+    const SEEDED_FLIPS: usize = 2;
+
+    #[test]
+    fn mutate_flips_the_same_cells_for_the_same_seed() {
+        let before = unpack_board(0x0155_A5C3);
+        let mut state = before;
+        mutate(&mut Fixed(0x2545_F491), &mut state, 100);
+        assert_eq!(flips(&before, &state), SEEDED_FLIPS);
+        // flipping the same cells again restores the board
+        mutate(&mut Fixed(0x2545_F491), &mut state, 100);
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn mutate_flips_rate_permille_of_the_cells_on_average() {
+        let mut random_gen = random::XorShift32::new(0x2545_F491);
+        let frames = 10_000;
+        for rate_permille in [MUTATION_RATE_PERMILLE, 100, 250] {
+            let mut total = 0;
+            for _ in 0..frames {
+                let before = unpack_board(random_gen.next_u32());
+                let mut state = before;
+                mutate(&mut random_gen, &mut state, rate_permille);
+                total += flips(&before, &state);
+            }
+            // 25 cells a frame at rate_permille / 1000 each, within 10%
+            let expected = frames * ROW_COUNT * COL_COUNT * rate_permille as usize / 1_000;
+            assert!(
+                total.abs_diff(expected) <= expected / 10,
+                "{rate_permille}: {total} vs {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn ms_to_frames_rounds_to_the_nearest_frame() {
+        // 500ms is 4.17 frames of 120ms and 550ms is 4.58, which truncation would also make 4
+        assert_eq!(ms_to_frames(500, 120), 4);
+        assert_eq!(ms_to_frames(550, 120), 5);
+        // halfway rounds up
+        assert_eq!(ms_to_frames(540, 120), 5);
+        assert_eq!(ms_to_frames(539, 120), 4);
+    }
+
+    #[test]
+    fn ms_to_frames_is_exact_for_multiples() {
+        assert_eq!(ms_to_frames(500, 100), 5);
+        assert_eq!(ms_to_frames(1_000, 100), 10);
+        assert_eq!(ms_to_frames(120, 120), 1);
+        assert_eq!(ms_to_frames(u32::MAX, 1), u32::MAX);
+    }
+
+    #[test]
+    fn ms_to_frames_is_at_least_one_frame() {
+        assert_eq!(ms_to_frames(0, 100), 1);
+        assert_eq!(ms_to_frames(10, 100), 1);
+        assert_eq!(ms_to_frames(49, 100), 1);
+        assert_eq!(ms_to_frames(60, 100), 1);
+    }
+
+    #[test]
+    fn time_left_us_counts_down_to_the_deadline() {
+        assert_eq!(time_left_us(1_000, 1_000, 100_000), 100_000);
+        assert_eq!(time_left_us(1_000, 31_000, 100_000), 70_000);
+        assert_eq!(time_left_us(1_000, 100_999, 100_000), 1);
+    }
+
+    #[test]
+    fn time_left_us_is_zero_once_the_logic_overran() {
+        assert_eq!(time_left_us(1_000, 101_000, 100_000), 0);
+        assert_eq!(time_left_us(1_000, 501_000, 100_000), 0);
+    }
+
+    #[test]
+    fn time_left_us_survives_the_clock_wrapping() {
+        let start = u32::MAX - 10_000;
+        assert_eq!(
+            time_left_us(start, start.wrapping_add(30_000), 100_000),
+            70_000
+        );
+        assert_eq!(time_left_us(start, start.wrapping_add(120_000), 100_000), 0);
+    }
+
+    #[test]
+    fn reset_timer_counts_down_from_reset_to_finished() {
+        let mut timer = ResetTimer::new(4);
+        // a new timer starts finished so the first action it guards is not delayed
+        assert!(timer.finished());
+        assert_eq!(timer.remaining(), 0);
+        assert_eq!(timer.progress(), 1.0);
+
+        timer.reset();
+        assert!(!timer.finished());
+        assert_eq!(timer.remaining(), 4);
+        assert_eq!(timer.progress(), 0.0);
+
+        for (remaining, progress) in [(3, 0.25), (2, 0.5), (1, 0.75)] {
+            assert!(!timer.tick(false));
+            assert_eq!(timer.remaining(), remaining);
+            assert_eq!(timer.progress(), progress);
+        }
+        assert!(timer.tick(false));
+        assert_eq!(timer.remaining(), 0);
+        assert_eq!(timer.progress(), 1.0);
+    }
+
+    #[test]
+    fn reset_timer_clamps_at_its_total() {
+        let mut timer = ResetTimer::new(2);
+        for _ in 0..10 {
+            assert!(timer.tick(false));
+        }
+        assert_eq!(timer.remaining(), 0);
+        assert_eq!(timer.progress(), 1.0);
+
+        timer.current = u32::MAX;
+        timer.total = u32::MAX;
+        assert!(timer.tick(false));
+        assert_eq!(timer.remaining(), 0);
+    }
+
+    #[test]
+    fn reset_timer_restarts_when_asked_to_on_expiry() {
+        let mut timer = ResetTimer::new(2);
+        timer.reset();
+        assert!(!timer.tick(true));
+        assert!(timer.tick(true));
+        assert_eq!(timer.remaining(), 2);
+        assert_eq!(timer.progress(), 0.0);
+        assert_eq!(ResetTimer::new(0).progress(), 1.0);
+    }
+
+    #[test]
+    fn pack_board_round_trips_random_boards() {
+        let mut prng = random::XorShift32::new(0x9E37_79B9);
+        for _ in 0..1_000 {
+            let bits = prng.next_u32() & 0x01FF_FFFF;
+            let state = unpack_board(bits);
+            assert_eq!(pack_board(&state), bits);
+            assert_eq!(unpack_board(pack_board(&state)), state);
+        }
+        assert_eq!(pack_board(&[[0; COL_COUNT]; ROW_COUNT]), 0);
+        assert_eq!(pack_board(&[[1; COL_COUNT]; ROW_COUNT]), 0x01FF_FFFF);
+    }
+
+    #[test]
+    fn pack_board_packs_any_lit_level_as_one() {
+        let mut state = [[0; COL_COUNT]; ROW_COUNT];
+        state[0][1] = 9;
+        state[4][4] = 2;
+        assert_eq!(pack_board(&state), 1 << 1 | 1 << 24);
+    }
+
+    #[test]
+    fn unpack_board_maps_bit_i_to_row_major_led_i() {
+        for i in 0..ROW_COUNT * COL_COUNT {
+            let state = unpack_board(1 << i);
+            for (row, line) in state.iter().enumerate() {
+                for (col, item) in line.iter().enumerate() {
+                    let expected = (row * COL_COUNT + col == i) as u8;
+                    assert_eq!(*item, expected, "bit {i} at ({row}, {col})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unpack_board_lights_the_documented_examples() {
+        assert_eq!(
+            unpack_board(0x0000_001F),
+            [
+                [1, 1, 1, 1, 1],
+                [0, 0, 0, 0, 0],
+                [0, 0, 0, 0, 0],
+                [0, 0, 0, 0, 0],
+                [0, 0, 0, 0, 0],
+            ]
+        );
+        assert_eq!(
+            unpack_board(0x0100_0000),
+            [
+                [0, 0, 0, 0, 0],
+                [0, 0, 0, 0, 0],
+                [0, 0, 0, 0, 0],
+                [0, 0, 0, 0, 0],
+                [0, 0, 0, 0, 1],
+            ]
+        );
+    }
+
+    #[test]
+    fn unpack_board_ignores_bits_above_the_25th() {
+        assert_eq!(unpack_board(0xFE00_0000), [[0; COL_COUNT]; ROW_COUNT]);
+        assert_eq!(unpack_board(0xFE00_0001), unpack_board(0x0000_0001));
+    }
+
+    #[test]
+    fn randomize_at_half_density_uses_the_unpack_mapping() {
+        let seed = 0x0155_A5C3;
+        let mut state = [[0; COL_COUNT]; ROW_COUNT];
+        randomize_state_density(&mut Fixed(seed), &mut state, 50);
+        assert_eq!(state, unpack_board(seed));
+        assert_eq!(
+            state,
+            [
+                [1, 1, 0, 0, 0],
+                [0, 1, 1, 1, 0],
+                [1, 0, 0, 1, 0],
+                [1, 1, 0, 1, 0],
+                [1, 0, 1, 0, 1],
+            ]
+        );
+    }
+
+    #[test]
+    fn randomize_at_0_and_100_percent_clears_and_fills_the_board() {
+        for seed in 1..100 {
+            let mut rng = random::XorShift32::new(seed);
+            let mut state = [[1; COL_COUNT]; ROW_COUNT];
+            randomize_state_density(&mut rng, &mut state, 0);
+            assert_eq!(state, [[0; COL_COUNT]; ROW_COUNT]);
+            randomize_state_density(&mut rng, &mut state, 100);
+            assert_eq!(state, [[1; COL_COUNT]; ROW_COUNT]);
+            // larger values are treated as 100
+            randomize_state_density(&mut rng, &mut state, 255);
+            assert_eq!(state, [[1; COL_COUNT]; ROW_COUNT]);
+        }
+    }
+
+    #[test]
+    fn randomize_lights_the_requested_share_on_average() {
+        const SEEDS: u32 = 1_000;
+        let mut rng = random::XorShift32::new(0x5EED);
+        let mut state = [[0; COL_COUNT]; ROW_COUNT];
+        let mut lit = 0;
+        for _ in 0..SEEDS {
+            randomize_state_density(&mut rng, &mut state, 30);
+            lit += live_cells(&state).count();
+        }
+        let percent = lit as f32 * 100.0 / (SEEDS as usize * ROW_COUNT * COL_COUNT) as f32;
+        assert!((28.0..32.0).contains(&percent), "{percent}% lit");
+    }
+
+    /// A board randomized with `mode` from each of a range of seeds
+    fn symmetric_boards(mode: symmetry::SymmetryMode) -> impl Iterator<Item = LEDState> {
+        let mut rng = random::XorShift32::new(0xC0FFEE);
+        (0..200).map(move |_| {
+            let mut state = [[0; COL_COUNT]; ROW_COUNT];
+            randomize_symmetric(&mut rng, &mut state, mode, RANDOM_DENSITY_PERCENT);
+            state
+        })
+    }
+
+    /// `state` mirrored left to right
+    fn mirror_lr(state: &LEDState) -> LEDState {
+        state.map(|mut line| {
+            line.reverse();
+            line
+        })
+    }
+
+    /// `state` mirrored top to bottom
+    fn mirror_tb(state: &LEDState) -> LEDState {
+        let mut mirrored = *state;
+        mirrored.reverse();
+        mirrored
+    }
+
+    /// `state` turned a quarter turn clockwise
+    fn rotate(state: &LEDState) -> LEDState {
+        let mut rotated = [[0; COL_COUNT]; ROW_COUNT];
+        for (row, line) in state.iter().enumerate() {
+            for (col, cell) in line.iter().enumerate() {
+                rotated[col][ROW_COUNT - 1 - row] = *cell;
+            }
+        }
+        rotated
+    }
+
+    #[test]
+    fn horizontal_and_vertical_boards_are_their_own_mirror_images() {
+        for state in symmetric_boards(symmetry::SymmetryMode::Horizontal) {
+            assert_eq!(mirror_lr(&state), state);
+        }
+        for state in symmetric_boards(symmetry::SymmetryMode::Vertical) {
+            assert_eq!(mirror_tb(&state), state);
+        }
+    }
+
+    #[test]
+    fn quad_boards_match_all_four_mirror_images() {
+        let mut centers = [0; 2];
+        for state in symmetric_boards(symmetry::SymmetryMode::Quad) {
+            assert_eq!(mirror_lr(&state), state);
+            assert_eq!(mirror_tb(&state), state);
+            assert_eq!(mirror_lr(&mirror_tb(&state)), state);
+            centers[state[ROW_COUNT / 2][COL_COUNT / 2] as usize] += 1;
+        }
+        // the center is its own image, so it is kept as drawn either way
+        assert!(centers.iter().all(|&count| count > 0), "{centers:?}");
+    }
+
+    #[test]
+    fn rotational_boards_survive_a_half_turn() {
+        let mut centers = [0; 2];
+        for state in symmetric_boards(symmetry::SymmetryMode::Rotational) {
+            let half_turn = rotate(&rotate(&state));
+            assert_eq!(half_turn, state);
+            // on the square MB2 grid it holds for a quarter turn too
+            assert_eq!(rotate(&state), state);
+            centers[state[ROW_COUNT / 2][COL_COUNT / 2] as usize] += 1;
+        }
+        assert!(centers.iter().all(|&count| count > 0), "{centers:?}");
+    }
+}
@@ -1,38 +1,1071 @@
-//! Conway's Game of Life implemented on a 5×5 "frame
-//! buffer" of `u8` pixels that can be either 0 or 1.
+//! Conway's Game of Life implemented on a `Board<W, H>`
+//! "frame buffer" of `u8` pixels that can be either 0 or 1.
+//! The stepping functions are generic over the grid size,
+//! which is inferred from the board passed in: main.rs
+//! steps the 5×5 `LEDState` of the MB2's LEDs, and
+//! canvas.rs a larger board of which only a window is
+//! shown. By default the grid wraps around at its edges (it
+//! is a torus), see `EdgeMode` for the alternatives, and
+//! the two dimensions need not be equal.
+//!
+//! Walls and the classified step only exist at the LED
+//! matrix's size.
+
+use crate::{COL_COUNT, LEDState, ROW_COUNT, bitboard, pack_board, unpack_board};
+
+/// A grid of H rows of W cells, each 0 or 1.
+pub type Board<const W: usize, const H: usize> = [[u8; W]; H];
 
 /// Return `true` iff the frame buffer contains no 1
 /// pixels.
-pub fn done(fb: &[[u8; 5]; 5]) -> bool {
-    fb == &[[0u8; 5]; 5]
-}
-
-/// Make a step according to the Game of Life rules.
-pub fn life(fb: &mut [[u8; 5]; 5]) {
-    let prev = *fb;
-    for row in 0..5 {
-        for col in 0..5 {
-            let prev_row = (row + 4) % 5;
-            let next_row = (row + 1) % 5;
-            let prev_col = (col + 4) % 5;
-            let next_col = (col + 1) % 5;
-            let coords = [
-                (prev_row, prev_col),
-                (prev_row, col),
-                (prev_row, next_col),
-                (row, prev_col),
-                (row, next_col),
-                (next_row, prev_col),
-                (next_row, col),
-                (next_row, next_col),
-            ];
-            let neighbors = coords.into_iter().map(|(r, c)| prev[r][c]).sum();
-            #[allow(clippy::manual_range_contains)]
-            match (prev[row][col], neighbors) {
-                (1, n) if n < 2 || n > 3 => fb[row][col] = 0,
-                (0, 3) => fb[row][col] = 1,
-                (_, _) => (),
+pub fn done<const W: usize, const H: usize>(fb: &Board<W, H>) -> bool {
+    fb.iter().flatten().all(|cell| *cell == 0)
+}
+
+/// A totalistic Life-like rule in B/S notation.
+///
+/// Bit `n` of `birth` set means a dead cell with `n` live
+/// neighbors is born; bit `n` of `survive` set means a live
+/// cell with `n` live neighbors stays alive.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Rule {
+    pub birth: u16,
+    pub survive: u16,
+}
+
+impl Rule {
+    /// Conway's Game of Life, B3/S23.
+    pub const fn conway() -> Self {
+        Rule {
+            birth: 1 << 3,
+            survive: 1 << 2 | 1 << 3,
+        }
+    }
+
+    /// HighLife, B36/S23.
+    pub const fn highlife() -> Self {
+        Rule {
+            birth: 1 << 3 | 1 << 6,
+            survive: 1 << 2 | 1 << 3,
+        }
+    }
+
+    /// Seeds, B2/S (nothing survives).
+    pub const fn seeds() -> Self {
+        Rule {
+            birth: 1 << 2,
+            survive: 0,
+        }
+    }
+
+    /// Day & Night, B3678/S34678.
+    pub const fn day_and_night() -> Self {
+        Rule {
+            birth: 1 << 3 | 1 << 6 | 1 << 7 | 1 << 8,
+            survive: 1 << 3 | 1 << 4 | 1 << 6 | 1 << 7 | 1 << 8,
+        }
+    }
+
+    /// Parse a rule in B/S notation such as `"B36/S23"` or
+    /// `"b2/s"`. Returns `None` for anything malformed,
+    /// including neighbor counts above 8.
+    pub fn from_bs(text: &str) -> Option<Rule> {
+        let (birth, survive) = text.trim().split_once('/')?;
+        let birth = birth.strip_prefix(['B', 'b'])?;
+        let survive = survive.strip_prefix(['S', 's'])?;
+        Some(Rule {
+            birth: parse_counts(birth)?,
+            survive: parse_counts(survive)?,
+        })
+    }
+
+    /// Return `true` iff a dead cell with `neighbors` live
+    /// neighbors is born.
+    pub fn births(&self, neighbors: u8) -> bool {
+        self.birth & (1 << neighbors) != 0
+    }
+
+    /// Return `true` iff a live cell with `neighbors` live
+    /// neighbors survives.
+    pub fn survives(&self, neighbors: u8) -> bool {
+        self.survive & (1 << neighbors) != 0
+    }
+}
+
+/// Writes the rule in B/S notation, e.g. `B36/S23`.
+impl core::fmt::Display for Rule {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "B")?;
+        for count in (0..=8).filter(|&count| self.births(count)) {
+            write!(f, "{count}")?;
+        }
+        write!(f, "/S")?;
+        for count in (0..=8).filter(|&count| self.survives(count)) {
+            write!(f, "{count}")?;
+        }
+        Ok(())
+    }
+}
+
+/// How neighbors are found across the edges of the grid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EdgeMode {
+    /// Both pairs of edges wrap around: the grid is a
+    /// torus.
+    Toroidal,
+    /// Every edge is bounded: there is nothing beyond the
+    /// grid, so edge cells have only 5 neighbors and corner
+    /// cells 3, as on a board cut out of an infinite plane
+    /// of dead cells.
+    Bounded,
+    /// The left and right edges wrap around with a twist:
+    /// leaving the right edge at row `r` re-enters the left
+    /// edge at row `ROW_COUNT - 1 - r` (and vice versa),
+    /// like a Möbius strip. The top and bottom edges are
+    /// bounded: there is nothing beyond them, so cells in
+    /// the top and bottom rows have only 5 neighbors.
+    Mobius,
+    /// Every edge reflects: a neighbor just past an edge is
+    /// looked up on the edge row or column instead (index
+    /// -1 reads index 0 and index N reads index N - 1), so
+    /// edge cells see a mirror image of the board rather
+    /// than the opposite side. A lookup that reflects back
+    /// onto the cell itself is skipped, so an edge cell
+    /// counts 7 lookups of 5 cells (its 2 neighbors along
+    /// the edge twice) and a corner cell 5 lookups of 3
+    /// cells (its 2 neighbors along the edges twice).
+    Reflect,
+}
+
+/// A cell of a `Walls` mask.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Wall {
+    /// An ordinary cell, stepped by the rules.
+    Open,
+    /// Always dead.
+    Dead,
+    /// Always alive, and so counted as a live neighbor by
+    /// the cells around it.
+    Live,
+}
+
+/// Cells whose state is fixed, parallel to the board.
+pub type Walls = [[Wall; COL_COUNT]; ROW_COUNT];
+
+/// A mask with no walls: every cell is stepped.
+pub const NO_WALLS: Walls = [[Wall::Open; COL_COUNT]; ROW_COUNT];
+
+/// A ring of `wall` around the edge of the grid, enclosing
+/// the open cells inside it.
+pub const fn border_walls(wall: Wall) -> Walls {
+    let mut walls = NO_WALLS;
+    let mut row = 0;
+    while row < ROW_COUNT {
+        let mut col = 0;
+        while col < COL_COUNT {
+            if row == 0 || col == 0 || row == ROW_COUNT - 1 || col == COL_COUNT - 1 {
+                walls[row][col] = wall;
+            }
+            col += 1;
+        }
+        row += 1;
+    }
+    walls
+}
+
+/// Force every walled cell of `fb` to its fixed value.
+pub fn apply_walls(fb: &mut LEDState, walls: &Walls) {
+    for (cell, wall) in fb.iter_mut().flatten().zip(walls.iter().flatten()) {
+        match wall {
+            Wall::Open => {}
+            Wall::Dead => *cell = 0,
+            Wall::Live => *cell = 1,
+        }
+    }
+}
+
+/// Return `true` iff no cell of `fb` outside the walls is
+/// alive.
+pub fn open_done(fb: &LEDState, walls: &Walls) -> bool {
+    fb.iter()
+        .flatten()
+        .zip(walls.iter().flatten())
+        .all(|(cell, wall)| *cell == 0 || *wall != Wall::Open)
+}
+
+/// The named rules that can be cycled through on the device.
+pub const PRESETS: [Rule; 4] = [
+    Rule::conway(),
+    Rule::highlife(),
+    Rule::seeds(),
+    Rule::day_and_night(),
+];
+
+/// Parse a run of neighbor-count digits (0-8) into a bitmask.
+fn parse_counts(digits: &str) -> Option<u16> {
+    let mut mask = 0;
+    for c in digits.chars() {
+        let n = c.to_digit(10).filter(|n| *n <= 8)?;
+        mask |= 1 << n;
+    }
+    Some(mask)
+}
+
+/// Make a step on the torus according to `rule`, e.g.
+/// `Rule::conway()` for the Game of Life itself or one of
+/// the other `PRESETS`.
+///
+/// `scratch` is caller-owned working space (its contents are
+/// overwritten), so no second board is built on the stack
+/// for each step.
+pub fn life<const W: usize, const H: usize>(
+    fb: &mut Board<W, H>,
+    scratch: &mut Board<W, H>,
+    rule: Rule,
+) {
+    step_with_rule(fb, scratch, rule, EdgeMode::Toroidal);
+}
+
+/// Make a step according to an arbitrary B/S `rule` with
+/// the given `edges`, using `scratch` to hold the previous
+/// generation.
+pub fn step_with_rule<const W: usize, const H: usize>(
+    fb: &mut Board<W, H>,
+    scratch: &mut Board<W, H>,
+    rule: Rule,
+    edges: EdgeMode,
+) {
+    scratch.copy_from_slice(fb);
+    next_generation(scratch, fb, rule, edges);
+}
+
+/// The next state of a cell for each of the 512 possible
+/// 3x3 neighborhoods under `rule`.
+///
+/// Bit `3 * (dr + 1) + (dc + 1)` of the index is the cell at
+/// offset (`dr`, `dc`), so bit 4 is the cell itself and the
+/// other 8 bits its neighbors. Swapping tables swaps rules.
+pub fn build_rule_table(rule: Rule) -> [u8; 512] {
+    let mut table = [0; 512];
+    for (neighborhood, next) in table.iter_mut().enumerate() {
+        let alive = neighborhood & 1 << 4 != 0;
+        let neighbors = (neighborhood & !(1 << 4)).count_ones() as u8;
+        *next = if alive {
+            rule.survives(neighbors)
+        } else {
+            rule.births(neighbors)
+        } as u8;
+    }
+    table
+}
+
+/// Make a step using a table from `build_rule_table`: each
+/// cell's 9-bit neighborhood is gathered and looked up
+/// rather than counted and tested against the rule. Gives
+/// the same result as `step_with_rule` with the table's
+/// rule.
+pub fn step_lut<const W: usize, const H: usize>(
+    fb: &mut Board<W, H>,
+    scratch: &mut Board<W, H>,
+    table: &[u8; 512],
+    edges: EdgeMode,
+) {
+    scratch.copy_from_slice(fb);
+    for (row, line) in fb.iter_mut().enumerate() {
+        for (col, cell) in line.iter_mut().enumerate() {
+            let mut neighborhood = (scratch[row][col] as usize) << 4;
+            for (bit, (dr, dc)) in NEIGHBORHOOD.into_iter().enumerate() {
+                if (dr, dc) != (0, 0)
+                    && let Some((r, c)) = neighbor::<W, H>(row, col, dr, dc, edges)
+                {
+                    neighborhood |= (scratch[r][c] as usize) << bit;
+                }
+            }
+            *cell = table[neighborhood];
+        }
+    }
+}
+
+/// The offsets of the 3x3 neighborhood in the bit order of
+/// `build_rule_table`.
+const NEIGHBORHOOD: [(isize, isize); 9] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 0),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A step of the board with every cell classified by what
+/// happened to it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StepResult {
+    /// The next generation.
+    pub next: LEDState,
+    /// 1 for each cell that was dead and is now alive.
+    pub born: LEDState,
+    /// 1 for each cell that was alive and is now dead.
+    pub died: LEDState,
+}
+
+/// Make a step of `fb` according to `rule`, classifying each
+/// cell as born, died, or unchanged. A cell that is alive in
+/// both `fb` and `next` survived.
+///
+/// Cells under `walls` keep their fixed value (`fb` is
+/// expected to already hold it, see `apply_walls`), while
+/// live walls still count as neighbors of the cells next to
+/// them.
+///
+/// On the torus the step is taken on a packed bitboard
+/// (bitboard.rs).
+pub fn step_classified(fb: &LEDState, rule: Rule, edges: EdgeMode, walls: &Walls) -> StepResult {
+    let mut next = [[0; COL_COUNT]; ROW_COUNT];
+    if edges == EdgeMode::Toroidal {
+        next = unpack_board(bitboard::step(pack_board(fb), rule));
+    } else {
+        next_generation(fb, &mut next, rule, edges);
+    }
+    classify(fb, next, walls)
+}
+
+/// As `step_classified`, but stepping with a table from
+/// `build_rule_table` (see `step_lut`) on every kind of
+/// edge.
+pub fn step_classified_lut(
+    fb: &LEDState,
+    table: &[u8; 512],
+    edges: EdgeMode,
+    walls: &Walls,
+) -> StepResult {
+    let mut next = *fb;
+    let mut scratch = [[0; COL_COUNT]; ROW_COUNT];
+    step_lut(&mut next, &mut scratch, table, edges);
+    classify(fb, next, walls)
+}
+
+/// Put `walls` back onto `next`, the generation after `fb`,
+/// and mark which cells were born and which died.
+fn classify(fb: &LEDState, next: LEDState, walls: &Walls) -> StepResult {
+    let mut result = StepResult {
+        next,
+        born: [[0; COL_COUNT]; ROW_COUNT],
+        died: [[0; COL_COUNT]; ROW_COUNT],
+    };
+    apply_walls(&mut result.next, walls);
+
+    let cells = fb.iter().flatten().zip(result.next.iter().flatten());
+    let masks = result
+        .born
+        .iter_mut()
+        .flatten()
+        .zip(result.died.iter_mut().flatten());
+    for ((was, is), (born, died)) in cells.zip(masks) {
+        *born = (*was == 0 && *is != 0) as u8;
+        *died = (*was != 0 && *is == 0) as u8;
+    }
+    result
+}
+
+/// Return `true` iff the board is finished under `rule`:
+/// it is already empty, or its next generation is (such as
+/// the all-alive board under Conway's rules). Boards whose
+/// next generation equals themselves (still lifes) are not
+/// terminal. Walled cells are ignored, so a board is empty
+/// once every open cell is dead. `scratch` is overwritten
+/// with the next generation.
+pub fn is_terminal(
+    fb: &LEDState,
+    scratch: &mut LEDState,
+    rule: Rule,
+    edges: EdgeMode,
+    walls: &Walls,
+) -> bool {
+    if open_done(fb, walls) {
+        return true;
+    }
+    next_generation(fb, scratch, rule, edges);
+    open_done(scratch, walls)
+}
+
+/// Write the generation following `prev` under `rule` and
+/// `edges` into `fb`.
+fn next_generation<const W: usize, const H: usize>(
+    prev: &Board<W, H>,
+    fb: &mut Board<W, H>,
+    rule: Rule,
+    edges: EdgeMode,
+) {
+    for row in 0..H {
+        for col in 0..W {
+            let neighbors = neighbor_count(prev, row, col, edges);
+            fb[row][col] = match prev[row][col] {
+                1 => rule.survives(neighbors) as u8,
+                _ => rule.births(neighbors) as u8,
+            };
+        }
+    }
+}
+
+/// Count the live cells among the 8 neighbors of
+/// (`row`, `col`), crossing the edges of the grid as given
+/// by `edges`.
+pub fn neighbor_count<const W: usize, const H: usize>(
+    fb: &Board<W, H>,
+    row: usize,
+    col: usize,
+    edges: EdgeMode,
+) -> u8 {
+    neighbors::<W, H>(row, col, edges)
+        .map(|(r, c)| fb[r][c])
+        .sum()
+}
+
+/// The (row, col) coordinates of the neighbors of (`row`,
+/// `col`) on a W x H grid: 8 of them, or fewer next to a
+/// bounded edge of `edges`.
+pub fn neighbors<const W: usize, const H: usize>(
+    row: usize,
+    col: usize,
+    edges: EdgeMode,
+) -> impl Iterator<Item = (usize, usize)> {
+    const OFFSETS: [(isize, isize); 8] = [
+        (-1, -1),
+        (-1, 0),
+        (-1, 1),
+        (0, -1),
+        (0, 1),
+        (1, -1),
+        (1, 0),
+        (1, 1),
+    ];
+    OFFSETS
+        .into_iter()
+        .filter_map(move |(dr, dc)| neighbor::<W, H>(row, col, dr, dc, edges))
+}
+
+/// The cell reached by moving (`dr`, `dc`) from (`row`,
+/// `col`) on a W x H grid, or `None` if that crosses a
+/// bounded edge.
+///
+/// Under `EdgeMode::Mobius` the vertical move is checked
+/// against the bounded top and bottom edges first, and the
+/// row is flipped only if the horizontal move then crosses
+/// a side edge. So the corner (0, W - 1) has the neighbors
+/// (0, W - 2), (1, W - 2), and (1, W - 1) on its own side,
+/// and (H - 1, 0) and (H - 2, 0) across the twisted edge.
+///
+/// Under `EdgeMode::Reflect` each coordinate is reflected
+/// separately, and `None` is returned if the result is the
+/// starting cell, so no cell is ever its own neighbor.
+fn neighbor<const W: usize, const H: usize>(
+    row: usize,
+    col: usize,
+    dr: isize,
+    dc: isize,
+    edges: EdgeMode,
+) -> Option<(usize, usize)> {
+    let start = (row, col);
+    // offsets are at most 1, so adding a whole grid keeps
+    // the sums from going negative
+    let row = row + H;
+    let col = col + W;
+    let (row, col) = (row.wrapping_add_signed(dr), col.wrapping_add_signed(dc));
+
+    match edges {
+        EdgeMode::Toroidal => Some(wrap::<W, H>(row, col)),
+        EdgeMode::Bounded => {
+            let row = row.checked_sub(H).filter(|r| *r < H)?;
+            let col = col.checked_sub(W).filter(|c| *c < W)?;
+            Some((row, col))
+        }
+        EdgeMode::Mobius => {
+            let row = row.checked_sub(H).filter(|r| *r < H)?;
+            if (W..2 * W).contains(&col) {
+                Some((row, col - W))
+            } else {
+                Some((H - 1 - row, col % W))
+            }
+        }
+        EdgeMode::Reflect => {
+            let reflect = |i: usize, n: usize| i.clamp(n, 2 * n - 1) - n;
+            Some((reflect(row, H), reflect(col, W))).filter(|cell| *cell != start)
+        }
+    }
+}
+
+/// Map (`row`, `col`) onto a W x H grid, wrapping
+/// coordinates past the bottom or right edge around to the
+/// top or left as on a torus.
+pub fn wrap<const W: usize, const H: usize>(row: usize, col: usize) -> (usize, usize) {
+    (row % H, col % W)
+}
+
+/// A glider as (row, col) offsets for `place_pattern`,
+/// travelling down and to the right.
+pub const GLIDER: [(usize, usize); 5] = [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+
+/// What `place_pattern` does with cells that fall outside
+/// the grid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OverflowMode {
+    /// Drop them.
+    Clip,
+    /// Wrap them around the opposite edges (see `wrap`).
+    Wrap,
+}
+
+/// Light the cells of `pattern`, given as (row, col)
+/// offsets, with its (0, 0) cell at `origin`. Cells beyond
+/// the bottom or right edge are handled per `overflow`;
+/// the rest of `state` is left as it is.
+pub fn place_pattern(
+    state: &mut LEDState,
+    pattern: &[(usize, usize)],
+    origin: (usize, usize),
+    overflow: OverflowMode,
+) {
+    for &(row, col) in pattern {
+        let (row, col) = (origin.0 + row, origin.1 + col);
+        let (row, col) = match overflow {
+            OverflowMode::Clip if row >= ROW_COUNT || col >= COL_COUNT => continue,
+            OverflowMode::Clip => (row, col),
+            OverflowMode::Wrap => wrap::<COL_COUNT, ROW_COUNT>(row, col),
+        };
+        state[row][col] = 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HORIZONTAL_BLINKER: LEDState = [
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 1, 1, 1, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+    ];
+    const VERTICAL_BLINKER: LEDState = [
+        [0, 0, 0, 0, 0],
+        [0, 0, 1, 0, 0],
+        [0, 0, 1, 0, 0],
+        [0, 0, 1, 0, 0],
+        [0, 0, 0, 0, 0],
+    ];
+
+    #[test]
+    fn blinker_oscillates_with_period_2() {
+        // the same scratch board is reused across steps
+        let mut fb = HORIZONTAL_BLINKER;
+        let mut scratch = [[0; COL_COUNT]; ROW_COUNT];
+        life(&mut fb, &mut scratch, Rule::conway());
+        assert_eq!(fb, VERTICAL_BLINKER);
+        life(&mut fb, &mut scratch, Rule::conway());
+        assert_eq!(fb, HORIZONTAL_BLINKER);
+    }
+
+    #[test]
+    fn the_lookup_table_steps_like_counting_neighbors() {
+        let mut random = crate::random::XorShift32::new(0x2545_F491);
+        for rule in PRESETS {
+            let table = build_rule_table(rule);
+            for edges in EDGE_MODES {
+                for _ in 0..20 {
+                    let mut reference = unpack_board(random.next_u32());
+                    let mut lut = reference;
+                    let mut scratch = [[0; COL_COUNT]; ROW_COUNT];
+                    for generation in 0..50 {
+                        step_with_rule(&mut reference, &mut scratch, rule, edges);
+                        step_lut(&mut lut, &mut scratch, &table, edges);
+                        assert_eq!(lut, reference, "{rule} {edges:?} generation {generation}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn classified_lookup_table_steps_match_classified_steps() {
+        let mut random = crate::random::XorShift32::new(0x2545_F491);
+        for walls in [NO_WALLS, border_walls(Wall::Live), border_walls(Wall::Dead)] {
+            for edges in EDGE_MODES {
+                let mut fb = unpack_board(random.next_u32());
+                apply_walls(&mut fb, &walls);
+                let table = build_rule_table(Rule::conway());
+                assert_eq!(
+                    step_classified_lut(&fb, &table, edges, &walls),
+                    step_classified(&fb, Rule::conway(), edges, &walls),
+                    "{edges:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn blinker_births_and_deaths_alternate_each_half_period() {
+        // the centre cell survives both half periods; the ends of one phase die as the ends of
+        // the other are born
+        let born_vertical = [
+            [0, 0, 0, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+        ];
+        let born_horizontal = [
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 1, 0, 1, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+        ];
+        for edges in [
+            EdgeMode::Toroidal,
+            EdgeMode::Bounded,
+            EdgeMode::Mobius,
+            EdgeMode::Reflect,
+        ] {
+            let vertical = step_classified(&HORIZONTAL_BLINKER, Rule::conway(), edges, &NO_WALLS);
+            assert_eq!(vertical.next, VERTICAL_BLINKER, "{edges:?}");
+            assert_eq!(vertical.born, born_vertical, "{edges:?}");
+            assert_eq!(vertical.died, born_horizontal, "{edges:?}");
+
+            let horizontal = step_classified(&vertical.next, Rule::conway(), edges, &NO_WALLS);
+            assert_eq!(horizontal.next, HORIZONTAL_BLINKER, "{edges:?}");
+            assert_eq!(horizontal.born, born_horizontal, "{edges:?}");
+            assert_eq!(horizontal.died, born_vertical, "{edges:?}");
+        }
+    }
+
+    /// Cells lit in the 3x3 interior inside `border_walls`
+    fn interior(fb: &LEDState) -> Vec<(usize, usize)> {
+        crate::live_cells(fb)
+            .filter(|(row, col)| {
+                (1..ROW_COUNT - 1).contains(row) && (1..COL_COUNT - 1).contains(col)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn live_walls_count_as_live_neighbors() {
+        let walls = border_walls(Wall::Live);
+        let mut fb = [[0; COL_COUNT]; ROW_COUNT];
+        apply_walls(&mut fb, &walls);
+        // an interior corner touches 5 wall cells, the middle of an interior edge 3, the centre none
+        assert_eq!(neighbor_count(&fb, 1, 1, EdgeMode::Toroidal), 5);
+        assert_eq!(neighbor_count(&fb, 1, 2, EdgeMode::Toroidal), 3);
+        assert_eq!(neighbor_count(&fb, 2, 2, EdgeMode::Toroidal), 0);
+
+        // so the walls alone bring the 4 interior edge cells to life
+        let step = step_classified(&fb, Rule::conway(), EdgeMode::Toroidal, &walls);
+        assert_eq!(interior(&step.next), [(1, 2), (2, 1), (2, 3), (3, 2)]);
+        assert_eq!(step.born, {
+            let mut born = [[0; COL_COUNT]; ROW_COUNT];
+            for (row, col) in [(1, 2), (2, 1), (2, 3), (3, 2)] {
+                born[row][col] = 1;
+            }
+            born
+        });
+    }
+
+    #[test]
+    fn a_ring_of_live_walls_crowds_out_a_single_interior_cell() {
+        let walls = border_walls(Wall::Live);
+        let mut fb = [[0; COL_COUNT]; ROW_COUNT];
+        apply_walls(&mut fb, &walls);
+        fb[2][2] = 1;
+        // the centre has no live neighbor and dies, and its 4 edge neighbors now touch 4 live cells
+        // (3 walls and the centre), too many to be born
+        let step = step_classified(&fb, Rule::conway(), EdgeMode::Toroidal, &walls);
+        assert_eq!(interior(&step.next), []);
+        assert_eq!(step.died[2][2], 1);
+        // the walls never change
+        for (cell, wall) in step.next.iter().flatten().zip(walls.iter().flatten()) {
+            if *wall == Wall::Live {
+                assert_eq!(*cell, 1);
+            }
+        }
+        assert!(open_done(&step.next, &walls));
+    }
+
+    #[test]
+    fn dead_walls_stay_dark_and_count_for_nothing() {
+        let walls = border_walls(Wall::Dead);
+        // a lit border is forced dark, as the game does before stepping
+        let mut fb = [[1; COL_COUNT]; ROW_COUNT];
+        apply_walls(&mut fb, &walls);
+        assert_eq!(interior(&fb).len(), 9);
+        assert_eq!(crate::live_cells(&fb).count(), 9);
+
+        // the full 3x3 interior inside dark walls: only its corners survive, as on a bounded grid
+        let step = step_classified(&fb, Rule::conway(), EdgeMode::Toroidal, &walls);
+        assert_eq!(interior(&step.next), [(1, 1), (1, 3), (3, 1), (3, 3)]);
+        assert_eq!(crate::live_cells(&step.next).count(), 4);
+    }
+
+    #[test]
+    fn seeds_never_lets_a_cell_survive() {
+        // every cell of a 2x2 block has 3 live neighbors, so it would survive Conway's rules, and
+        // the cells around it have at most 2
+        let block = [
+            [0, 0, 0, 0, 0],
+            [0, 1, 1, 0, 0],
+            [0, 1, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+        ];
+        let mut fb = block;
+        let mut scratch = [[0; COL_COUNT]; ROW_COUNT];
+        step_with_rule(&mut fb, &mut scratch, Rule::seeds(), EdgeMode::Toroidal);
+        for (was, is) in block.iter().flatten().zip(fb.iter().flatten()) {
+            assert!(!(*was != 0 && *is != 0));
+        }
+        // the 8 cells edge-adjacent to the block (2 live neighbors each) are born
+        assert_eq!(
+            fb,
+            [
+                [0, 1, 1, 0, 0],
+                [1, 0, 0, 1, 0],
+                [1, 0, 0, 1, 0],
+                [0, 1, 1, 0, 0],
+                [0, 0, 0, 0, 0],
+            ]
+        );
+    }
+
+    #[test]
+    fn from_bs_parses_the_presets() {
+        assert_eq!(Rule::from_bs("B3/S23"), Some(Rule::conway()));
+        assert_eq!(Rule::from_bs(" b36/s23 "), Some(Rule::highlife()));
+        assert_eq!(Rule::from_bs("B2/S"), Some(Rule::seeds()));
+        assert_eq!(Rule::from_bs("B3678/S34678"), Some(Rule::day_and_night()));
+        for rule in PRESETS {
+            assert_eq!(Rule::from_bs(&rule.to_string()), Some(rule));
+        }
+    }
+
+    #[test]
+    fn from_bs_rejects_malformed_rules() {
+        assert_eq!(Rule::from_bs("B3S23"), None);
+        assert_eq!(Rule::from_bs("3/23"), None);
+        assert_eq!(Rule::from_bs("B39/S23"), None);
+        assert_eq!(Rule::from_bs("Bx/S23"), None);
+        assert_eq!(Rule::from_bs(""), None);
+    }
+
+    #[test]
+    fn block_is_a_still_life() {
+        let block = [
+            [0, 0, 0, 0, 0],
+            [0, 1, 1, 0, 0],
+            [0, 1, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+        ];
+        let mut fb = block;
+        let mut scratch = [[0; COL_COUNT]; ROW_COUNT];
+        step_with_rule(&mut fb, &mut scratch, Rule::conway(), EdgeMode::Toroidal);
+        assert_eq!(fb, block);
+    }
+
+    /// A glider anchored at the bottom right corner, so two of its rows and columns fall past the
+    /// bottom and right edges
+    const CORNER: (usize, usize) = (ROW_COUNT - 1, COL_COUNT - 1);
+
+    #[test]
+    fn clipping_drops_the_cells_past_both_edges() {
+        let mut fb = [[0; COL_COUNT]; ROW_COUNT];
+        place_pattern(&mut fb, &GLIDER, CORNER, OverflowMode::Clip);
+        // only the glider's (0, 0) offset is on the grid, and it is not one of its cells
+        assert_eq!(fb, [[0; COL_COUNT]; ROW_COUNT]);
+
+        place_pattern(
+            &mut fb,
+            &GLIDER,
+            (ROW_COUNT - 3, COL_COUNT - 2),
+            OverflowMode::Clip,
+        );
+        let mut expected = [[0; COL_COUNT]; ROW_COUNT];
+        expected[2][4] = 1;
+        expected[4][3] = 1;
+        expected[4][4] = 1;
+        assert_eq!(fb, expected);
+    }
+
+    #[test]
+    fn wrapping_carries_the_cells_past_both_edges_around() {
+        let mut fb = [[0; COL_COUNT]; ROW_COUNT];
+        place_pattern(&mut fb, &GLIDER, CORNER, OverflowMode::Wrap);
+        let expected = [
+            [0, 1, 0, 0, 0],
+            [1, 1, 0, 0, 1],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [1, 0, 0, 0, 0],
+        ];
+        assert_eq!(fb, expected);
+    }
+
+    #[test]
+    fn placing_a_pattern_leaves_the_other_cells_alone() {
+        let mut fb = [[1; COL_COUNT]; ROW_COUNT];
+        place_pattern(&mut fb, &GLIDER, CORNER, OverflowMode::Wrap);
+        assert_eq!(fb, [[1; COL_COUNT]; ROW_COUNT]);
+    }
+
+    #[test]
+    fn glider_crosses_the_torus_back_to_its_start() {
+        let mut start = [[0; COL_COUNT]; ROW_COUNT];
+        place_pattern(&mut start, &GLIDER, (0, 0), OverflowMode::Wrap);
+
+        // a glider moves one cell diagonally every 4 generations
+        let mut fb = start;
+        let mut scratch = [[0; COL_COUNT]; ROW_COUNT];
+        for generation in 1..4 * ROW_COUNT {
+            step_with_rule(&mut fb, &mut scratch, Rule::conway(), EdgeMode::Toroidal);
+            assert_ne!(fb, start, "generation {generation}");
+            assert_eq!(fb.iter().flatten().filter(|cell| **cell != 0).count(), 5);
+        }
+        step_with_rule(&mut fb, &mut scratch, Rule::conway(), EdgeMode::Toroidal);
+        assert_eq!(fb, start);
+    }
+
+    /// The 4 edge modes, in the column order of the tables below
+    const EDGE_MODES: [EdgeMode; 4] = [
+        EdgeMode::Bounded,
+        EdgeMode::Toroidal,
+        EdgeMode::Mobius,
+        EdgeMode::Reflect,
+    ];
+
+    #[test]
+    fn edge_cells_count_their_neighbors_per_edge_mode() {
+        let mut fb = [[0; COL_COUNT]; ROW_COUNT];
+        for (row, col) in [(0, 1), (1, 1), (4, 0), (0, 4), (1, 4)] {
+            fb[row][col] = 1;
+        }
+        // (row, col) and the live neighbors under Bounded, Toroidal, Mobius, and Reflect
+        let expected = [
+            ((0, 0), [2, 5, 2, 3]),
+            ((1, 0), [2, 4, 2, 2]),
+            ((0, 2), [2, 2, 2, 3]),
+            ((4, 4), [0, 2, 0, 0]),
+            ((2, 2), [1, 1, 1, 1]),
+        ];
+        for ((row, col), counts) in expected {
+            for (edges, count) in EDGE_MODES.into_iter().zip(counts) {
+                assert_eq!(
+                    neighbor_count(&fb, row, col, edges),
+                    count,
+                    "({row}, {col}) {edges:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn edge_modes_look_up_as_many_neighbors_as_documented() {
+        // (row, col) and the lookups under Bounded, Toroidal, Mobius, and Reflect
+        let expected = [
+            ((0, 0), [3, 8, 5, 5]),
+            ((4, 4), [3, 8, 5, 5]),
+            ((2, 0), [5, 8, 8, 7]),
+            ((0, 2), [5, 8, 5, 7]),
+            ((2, 2), [8, 8, 8, 8]),
+        ];
+        for ((row, col), counts) in expected {
+            for (edges, count) in EDGE_MODES.into_iter().zip(counts) {
+                let cells: Vec<_> = neighbors::<COL_COUNT, ROW_COUNT>(row, col, edges).collect();
+                assert_eq!(cells.len(), count, "({row}, {col}) {edges:?}");
+                // no cell is its own neighbor, not even by reflecting at a corner
+                assert!(!cells.contains(&(row, col)), "({row}, {col}) {edges:?}");
             }
         }
     }
+
+    /// A 5 wide, 3 high board, so swapping rows and columns anywhere shows up
+    type Wide = Board<5, 3>;
+
+    #[test]
+    fn non_square_boards_count_neighbors_by_row_then_column() {
+        let full: Wide = [[1; 5]; 3];
+        assert_eq!(
+            (0..3)
+                .map(|row| (0..5)
+                    .map(|col| neighbor_count(&full, row, col, EdgeMode::Bounded))
+                    .collect())
+                .collect::<Vec<Vec<u8>>>(),
+            [[3, 5, 5, 5, 3], [5, 8, 8, 8, 5], [3, 5, 5, 5, 3]]
+        );
+        for (row, col) in (0..3).flat_map(|row| (0..5).map(move |col| (row, col))) {
+            assert_eq!(neighbor_count(&full, row, col, EdgeMode::Toroidal), 8);
+        }
+
+        // the left column wraps round to the right edge, and every row sees all 3 of its cells
+        let left: Wide = [[1, 0, 0, 0, 0]; 3];
+        for row in 0..3 {
+            assert_eq!(
+                (0..5)
+                    .map(|col| neighbor_count(&left, row, col, EdgeMode::Toroidal))
+                    .collect::<Vec<u8>>(),
+                [2, 3, 0, 0, 3]
+            );
+        }
+
+        // the top-right corner is diagonally next to the bottom-left one on the torus only
+        let mut corner: Wide = [[0; 5]; 3];
+        corner[0][4] = 1;
+        assert_eq!(neighbor_count(&corner, 2, 0, EdgeMode::Toroidal), 1);
+        assert_eq!(neighbor_count(&corner, 2, 0, EdgeMode::Bounded), 0);
+        assert_eq!(neighbor_count(&corner, 1, 3, EdgeMode::Bounded), 1);
+        assert_eq!(neighbor_count(&corner, 1, 2, EdgeMode::Toroidal), 0);
+    }
+
+    #[test]
+    fn non_square_boards_step_by_row_then_column() {
+        let blinker: Wide = [[0, 0, 0, 0, 0], [0, 1, 1, 1, 0], [0, 0, 0, 0, 0]];
+        let mut scratch: Wide = [[0; 5]; 3];
+
+        let mut fb = blinker;
+        step_with_rule(&mut fb, &mut scratch, Rule::conway(), EdgeMode::Bounded);
+        assert_eq!(fb, [[0, 0, 1, 0, 0]; 3]);
+        step_with_rule(&mut fb, &mut scratch, Rule::conway(), EdgeMode::Bounded);
+        assert_eq!(fb, blinker);
+
+        // only 3 rows high, the torus wraps the upright blinker onto itself: its cells keep 2
+        // neighbors and the columns either side get 3, so it grows instead of turning back
+        let mut fb = blinker;
+        life(&mut fb, &mut scratch, Rule::conway());
+        assert_eq!(fb, [[0, 0, 1, 0, 0]; 3]);
+        life(&mut fb, &mut scratch, Rule::conway());
+        assert_eq!(fb, [[0, 1, 1, 1, 0]; 3]);
+    }
+
+    #[test]
+    fn mobius_corners_see_across_the_twisted_edge() {
+        // the cells that count as a neighbor of (row, col) when they are the only one lit
+        let neighbors = |row, col| {
+            (0..ROW_COUNT)
+                .flat_map(|r| (0..COL_COUNT).map(move |c| (r, c)))
+                .filter(|&(r, c)| {
+                    let mut fb = [[0; COL_COUNT]; ROW_COUNT];
+                    fb[r][c] = 1;
+                    neighbor_count(&fb, row, col, EdgeMode::Mobius) == 1
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            neighbors(0, COL_COUNT - 1),
+            [(0, 3), (1, 3), (1, 4), (3, 0), (4, 0)]
+        );
+        assert_eq!(
+            neighbors(ROW_COUNT - 1, 0),
+            [(0, 4), (1, 4), (3, 0), (3, 1), (4, 1)]
+        );
+    }
+
+    #[test]
+    fn a_glider_crosses_the_mobius_edge_upside_down() {
+        let mut torus = [[0; COL_COUNT]; ROW_COUNT];
+        place_pattern(&mut torus, &GLIDER, (0, 1), OverflowMode::Wrap);
+        let mut mobius = torus;
+        let mut scratch = [[0; COL_COUNT]; ROW_COUNT];
+
+        // the glider moves the same way on both until it reaches the right edge
+        for generation in 1..=6 {
+            step_with_rule(&mut torus, &mut scratch, Rule::conway(), EdgeMode::Toroidal);
+            step_with_rule(&mut mobius, &mut scratch, Rule::conway(), EdgeMode::Mobius);
+            assert_eq!(torus, mobius, "generation {generation}");
+        }
+
+        // the first cell born across it lands in row 3 on the torus but row 1 (5 - 1 - 3) on the
+        // strip; the rest of the board is the same
+        step_with_rule(&mut torus, &mut scratch, Rule::conway(), EdgeMode::Toroidal);
+        step_with_rule(&mut mobius, &mut scratch, Rule::conway(), EdgeMode::Mobius);
+        assert_eq!((torus[3][0], torus[1][0]), (1, 0));
+        assert_eq!((mobius[3][0], mobius[1][0]), (0, 1));
+        for row in 0..ROW_COUNT {
+            assert_eq!(torus[row][1..], mobius[row][1..]);
+        }
+    }
+
+    #[test]
+    fn torus_neighbors_wrap_around_every_edge() {
+        let mut fb = [[0; COL_COUNT]; ROW_COUNT];
+        fb[ROW_COUNT - 1][COL_COUNT - 1] = 1;
+        fb[ROW_COUNT - 1][0] = 1;
+        fb[0][COL_COUNT - 1] = 1;
+        assert_eq!(neighbor_count(&fb, 0, 0, EdgeMode::Toroidal), 3);
+    }
+
+    #[test]
+    fn lone_cells_and_the_empty_board_are_terminal() {
+        let mut scratch = [[0; COL_COUNT]; ROW_COUNT];
+        let empty = [[0; COL_COUNT]; ROW_COUNT];
+        assert!(is_terminal(
+            &empty,
+            &mut scratch,
+            Rule::conway(),
+            EdgeMode::Toroidal,
+            &NO_WALLS
+        ));
+
+        let mut lone = empty;
+        lone[2][2] = 1;
+        assert!(is_terminal(
+            &lone,
+            &mut scratch,
+            Rule::conway(),
+            EdgeMode::Toroidal,
+            &NO_WALLS
+        ));
+        assert!(!is_terminal(
+            &HORIZONTAL_BLINKER,
+            &mut scratch,
+            Rule::conway(),
+            EdgeMode::Toroidal,
+            &NO_WALLS
+        ));
+    }
+
+    #[test]
+    fn rules_print_in_bs_notation() {
+        assert_eq!(Rule::conway().to_string(), "B3/S23");
+        assert_eq!(Rule::highlife().to_string(), "B36/S23");
+        assert_eq!(Rule::seeds().to_string(), "B2/S");
+    }
+
+    #[test]
+    fn the_full_board_is_terminal_but_a_still_life_is_not() {
+        let mut scratch = [[0; COL_COUNT]; ROW_COUNT];
+        // every cell of the full torus has 8 live neighbors, so all of them die at once
+        assert!(is_terminal(
+            &[[1; COL_COUNT]; ROW_COUNT],
+            &mut scratch,
+            Rule::conway(),
+            EdgeMode::Toroidal,
+            &NO_WALLS
+        ));
+
+        let block = [
+            [0, 0, 0, 0, 0],
+            [0, 1, 1, 0, 0],
+            [0, 1, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+        ];
+        assert!(!is_terminal(
+            &block,
+            &mut scratch,
+            Rule::conway(),
+            EdgeMode::Toroidal,
+            &NO_WALLS
+        ));
+    }
 }
@@ -0,0 +1,148 @@
+//! Ambient light sensing through the LED matrix, for adapting the display's brightness.
+//!
+//! As in the micro:bit runtime, the LEDs themselves are the sensor. Every row (anode) is driven
+//! low and three of the columns (cathodes), which double as analog inputs, high, reverse biasing
+//! the LEDs so that their junctions charge up. The columns are then left floating while the
+//! light falling on the LEDs discharges them, faster the brighter it is, and after DISCHARGE_US
+//! their voltages are read with the SAADC. A reading well below the supply means a bright room.
+//!
+//! A measurement borrows the pins from the nonblocking display for a few milliseconds, with its
+//! TIMER2 interrupt masked, so the main loop takes one at the end of a frame only every
+//! LIGHT_SAMPLE_MS. The SAADC is shared with the microphone and the speed knob (sound.rs, pot.rs), so each measurement sets
+//! up the channels it needs and leaves the SAADC disabled. The readings are smoothed by an exponential moving average, so that a
+//! hand passing over the MB2 does not make the display pump.
+
+use core::sync::atomic::{Ordering, compiler_fence};
+
+use embedded_hal::delay::DelayNs;
+use microbit::pac::{self, NVIC, SAADC};
+
+use crate::fade::MAX_BRIGHTNESS;
+
+/// How long the columns are left floating before being read. Longer is more sensitive in dim
+/// light (and takes longer away from the display)
+const DISCHARGE_US: u32 = 3_000;
+/// How long the columns are driven high to charge the LEDs before they are left floating
+const PRECHARGE_US: u32 = 50;
+/// P0 row pins (anodes) of the LED matrix
+const ROW_PINS: [usize; 5] = [21, 22, 15, 24, 19];
+/// P0 column pins of the LED matrix read as analog inputs: COL1 (AIN4), COL3 (AIN7), COL5 (AIN6)
+const SENSE_PINS: [usize; 3] = [28, 31, 30];
+/// The other columns, held high so that their LEDs stay reverse biased: COL2 on P0, COL4 on P1
+const OTHER_COL_P0: usize = 11;
+const OTHER_COL_P1: usize = 5;
+/// Readings (10-bit, 0 to 3.6V) at or above which the room counts as dark, and at or below which
+/// it counts as bright. Rough values: the discharge varies from MB2 to MB2
+const DARK_READING: u32 = 800;
+const BRIGHT_READING: u32 = 300;
+/// Weight of a new reading in the moving average, as 1 / 2^SMOOTHING_SHIFT
+const SMOOTHING_SHIFT: u32 = 3;
+
+/// Measures the ambient light through the LEDs
+pub struct LightSensor {
+    /// Moving average of the readings, scaled by 2^SMOOTHING_SHIFT, or None before the first
+    average: Option<u32>,
+    min_brightness: u8,
+}
+
+impl LightSensor {
+    /// Start without a measurement, for a display brightness of at least `min_brightness` in the
+    /// dark.
+    pub fn new(min_brightness: u8) -> Self {
+        LightSensor {
+            average: None,
+            min_brightness: min_brightness.min(MAX_BRIGHTNESS),
+        }
+    }
+
+    /// Take one measurement, blanking the display for about DISCHARGE_US.
+    pub fn measure(&mut self, saadc: &SAADC, delay: &mut impl DelayNs) {
+        NVIC::mask(pac::Interrupt::TIMER2);
+        // SAFETY: the display interrupt is masked, so nothing else drives the matrix pins until
+        // they are handed back as outputs below, and its next refresh sets them all again
+        let (p0, p1) = unsafe { (&*pac::P0::ptr(), &*pac::P1::ptr()) };
+
+        let rows = ROW_PINS.iter().fold(0, |bits, pin| bits | 1 << pin);
+        let cols = SENSE_PINS
+            .iter()
+            .fold(1 << OTHER_COL_P0, |bits, pin| bits | 1 << pin);
+        p0.outclr.write(|w| unsafe { w.bits(rows) });
+        p0.outset.write(|w| unsafe { w.bits(cols) });
+        p1.outset.write(|w| unsafe { w.bits(1 << OTHER_COL_P1) });
+        delay.delay_us(PRECHARGE_US);
+
+        for pin in SENSE_PINS {
+            p0.pin_cnf[pin].write(|w| w.dir().input().input().disconnect());
+        }
+        delay.delay_us(DISCHARGE_US);
+        let reading = Self::sample(saadc);
+        for pin in SENSE_PINS {
+            p0.pin_cnf[pin].write(|w| w.dir().output().input().disconnect());
+        }
+
+        // SAFETY: the display state is untouched, so its handler can carry on refreshing
+        unsafe { NVIC::unmask(pac::Interrupt::TIMER2) };
+
+        self.average = Some(match self.average {
+            None => reading << SMOOTHING_SHIFT,
+            Some(average) => average - (average >> SMOOTHING_SHIFT) + reading,
+        });
+    }
+
+    /// Read the sensing columns once and return their mean.
+    fn sample(saadc: &SAADC) -> u32 {
+        let mut results = [0i16; SENSE_PINS.len()];
+        saadc.resolution.write(|w| w.val()._10bit());
+        for channel in saadc.ch.iter() {
+            channel.pselp.write(|w| w.pselp().nc());
+        }
+        for channel in &saadc.ch[..SENSE_PINS.len()] {
+            channel.config.write(|w| {
+                w.resp().bypass().resn().bypass();
+                w.gain().gain1_6().refsel().internal();
+                w.tacq()._10us().mode().se()
+            });
+        }
+        saadc.ch[0].pselp.write(|w| w.pselp().analog_input4());
+        saadc.ch[1].pselp.write(|w| w.pselp().analog_input7());
+        saadc.ch[2].pselp.write(|w| w.pselp().analog_input6());
+        saadc.enable.write(|w| w.enable().enabled());
+        saadc
+            .result
+            .ptr
+            .write(|w| unsafe { w.ptr().bits(results.as_mut_ptr() as u32) });
+        saadc
+            .result
+            .maxcnt
+            .write(|w| unsafe { w.maxcnt().bits(results.len() as u16) });
+        saadc.events_end.reset();
+        saadc.events_started.reset();
+        saadc.tasks_start.write(|w| unsafe { w.bits(1) });
+        while saadc.events_started.read().bits() == 0 {}
+        saadc.tasks_sample.write(|w| unsafe { w.bits(1) });
+        while saadc.events_end.read().bits() == 0 {}
+        saadc.events_end.reset();
+        // the results were written by EasyDMA behind the compiler's back
+        compiler_fence(Ordering::SeqCst);
+        saadc.enable.write(|w| w.enable().disabled());
+
+        // negative results are noise around 0V
+        results
+            .iter()
+            .map(|result| result.max(&0).unsigned_abs() as u32)
+            .sum::<u32>()
+            / results.len() as u32
+    }
+
+    /// The display brightness suited to the light measured so far: full brightness in a bright
+    /// room down to the minimum in the dark, and full brightness before the first measurement.
+    pub fn brightness(&self) -> u8 {
+        let Some(average) = self.average else {
+            return MAX_BRIGHTNESS;
+        };
+        let reading = (average >> SMOOTHING_SHIFT).clamp(BRIGHT_READING, DARK_READING);
+        let span = (MAX_BRIGHTNESS - self.min_brightness) as u32;
+        let dimmed = (reading - BRIGHT_READING) * span / (DARK_READING - BRIGHT_READING);
+        MAX_BRIGHTNESS - dimmed as u8
+    }
+}
@@ -5,267 +5,626 @@
 //!
 //! Play Conway's Game of Life (GOL) on the Microbit V2 (MB2) 5x5 LED matrix
 //!
-//! The Rust code present here can be summarized as follows (top to bottom order):
-//! 1. Defines a set of constants dictating the LED board size and refresh rates
-//! 2. Defines and implements a convience trait for the 2 button Microbit InputPin structs
-//! 3. Defines helper functions which randomize or complement the current board state
-//! 4. Defines and implements a helper struct for simplifying the the refresh rate criteria
-//!    (see below for more info on this)
-//! 5. Defines the Microbit entry point event loop where
+//! The game logic itself (the board size and refresh rate constants, the btn trait, the helpers which randomize or
+//! complement the board, the refresh rate timer, and the Game) lives in the `gol` library (lib.rs) so that it can be
+//! unit tested on the host. The Rust code present here can be summarized as follows (top to bottom order):
+//! 1. Declares the drivers for the MB2's peripherals (display, sensors, sleep, and watchdog)
+//! 2. Defines the constants of the features which only the firmware uses (display effects and sensors)
+//! 3. Defines helper functions which log the board and the frame rate over RTT
+//! 4. Defines the Microbit entry point event loop where
 //!     - The required MB2 peripherals are captured
-//!     - States are initialized
-//!     - Event loop with UI (btn controls) begins
+//!     - The Game (game.rs), which owns all of the mutable GOL state, is initialized
+//!     - Event loop begins: each frame the board is rendered, the btns and accelerometer are read,
+//!       and the Game is updated with them (see Game::update for the UI logic)
 //!
 //! This implementation of the Game of Life and UI obeys the following Specifications:
 //! 1. The display refresh rate is 100ms (10 frames per second)
 //! 2. The GOL is initialized to a random state
-//! 3. While the MB2 A btn is pressed, the state will be re-randomized
-//! 4. If the B btn is pressed, the state will be complimented (on -> off and off -> on).
-//!    A 500ms cooldown period will occur between every compliment action
-//! 5. If the GOL state is all zeros ("dead" state), then a 500ms timer will begin.
-//!    If no other btn is pressed during that 500ms, the GOL restarts with a random starting state
+//! 3. While the MB2 A btn is pressed (or the MB2 is being shaken), the state will be re-randomized
+//! 4. If the B btn is pressed, the state will be complemented (on -> off and off -> on).
+//!    A 500ms cooldown period will occur between every complement action. Holding B complements only once,
+//!    again every 500ms, or shows the complement only until B is released, as set in the settings menu (Spec 7)
+//! 5. If the GOL state is all zeros ("dead" state), or its next generation would be, or (with the default `cycle-restart`
+//!    feature) it repeats one of its last 8 generations, then a 500ms timer will begin.
+//!    If no other btn is pressed during that 500ms, the number of generations the board survived scrolls across the
+//!    grid and then the GOL restarts with a random starting state (pressing a btn during the scroll restarts at once)
 //! 6. Otherwise a normal GOL step is taken according to Conway's GOL rules
+//! 7. Tapping the A and B btns together opens (and closes) a settings menu, in which the board is frozen. Each press of A
+//!    scrolls the next setting and its value across the grid and each press of B changes it: the speed (Spec 24), the
+//!    density of randomized boards, the brightness (Spec 23), the edges of the grid (wrapped, bounded, Möbius, or
+//!    reflected), what holding B does (Spec 4), and the stepping rule, which cycles through Conway (B3/S23), HighLife
+//!    (B36/S23), Seeds (B2/S), Day & Night (B3678/S34678) and then a "gravity" mode where lit cells fall one step per
+//!    frame towards whichever edge of the MB2 is tilted down, a "canvas" mode (Spec 13), a "teams" mode (Spec 14), a
+//!    "supersample" mode (Spec 16), Star Wars (345/2/4) and Brian's Brain (/2/3) "generations" modes (Spec 17), a
+//!    "wireworld" mode (Spec 18), a Langton's "ants" mode (Spec 19), an "elementary" mode (Spec 20), an "evolving" mode
+//!    (Spec 21), and a "pour" mode (Spec 26). The elementary and evolving modes scroll their name across the grid once
+//!    the menu closes on them
+//! 8. Holding A and then tapping B (an "A first" chord) cycles the symmetry applied to randomized boards:
+//!    none, horizontal mirror, vertical mirror, quad mirror, and 90 degree rotational
+//! 9. Holding the A and B btns together for 500ms or more shows the live population as a bar on the
+//!    rightmost LED column (one LED per 5 live cells) until released
+//! 10. Holding B and then tapping A (a "B first" chord) cycles what the B btn does: the full complement of
+//!     Spec 4, a "neighborhood" complement which only lights unlit cells touching a lit cell, and complements
+//!     masked to the center 3x3, the top-left quadrant, and the border
+//! 11. Holding the A btn for 1s freezes the board in a single-step mode: each further press of A advances
+//!     exactly one generation and pressing (then releasing) B returns to normal play. While in this mode
+//!     the dead-state restart of Spec 5 is suppressed so that a dying board can be studied to the end
+//! 12. Holding A and then B (an "A first" chord) for 500ms saves the current board to flash. Holding only B
+//!     while the MB2 powers on restores the saved board instead of starting on a random one
+//! 13. The canvas mode steps Conway's rules on a larger 32x32 toroidal board and shows a 5x5 window of it. Each
+//!     press of A pans the window one column right, each press of B one row down, and tilting the MB2 pans it
+//!     towards the lowered edge
+//! 14. The teams mode steps Conway's rules on a board of two teams: holding A seeds the left side with team 0,
+//!     holding B seeds the right side with team 1, shaking seeds the whole board with both teams mixed (as in the
+//!     Immigration game), and each newborn cell joins its neighbors' majority team
+//! 15. Holding the A and B btns while the MB2 powers on runs a self-test lighting each LED in turn before the game starts.
+//!     The btns held at power-up (Spec 12, 15, and 28) are shown as an icon until they are released
+//! 16. The supersample mode steps Conway's rules on a 10x10 toroidal board and shows each 2x2 block of it as one LED,
+//!     as bright as the block is full. Holding A (or shaking) re-randomizes it
+//! 17. The generations mode steps a rule of the Generations family, whose cells fade through dying states before
+//!     disappearing: Star Wars (345/2/4), where a cell that does not survive takes 2 generations to die, and Brian's
+//!     Brain (/2/3), where every firing cell spends 1 generation refractory. Holding A (or shaking) re-randomizes it
+//! 18. The wireworld mode runs Wireworld circuits, starting on a clock: an electron circulating on a loop of wire. Holding A
+//!     for 1s switches between running and editing. While editing, a blinking cursor is moved by tapping A and pressing
+//!     B turns the cell under it into a wire, an electron, or back to empty. While running, pressing B fires an electron
+//!     from the cursor. Shaking restores the clock
+//! 19. The ants mode runs Langton's Ant on a toroidal grid: each ant turns right on an unlit cell and left on a lit one,
+//!     flips it, and steps forwards. Each press of A adds an ant (up to 4), each press of B removes the newest, and
+//!     shaking resets the grid to a single ant
+//! 20. The elementary mode runs a 1D elementary automaton (Rule 30, 45, 90, 110, 150 or 184) as a waterfall: each new
+//!     generation is computed from the top row and pushed in above it, scrolling the older ones down. Each press of A
+//!     selects the next rule and scrolls it (e.g. "RULE 110") across the grid, each press of B (or shaking) re-seeds the top row
+//! 21. The evolving mode starts on Conway's rules, and every 50 generations toggles one random birth (1-8) or survival (0-8)
+//!     count of its rule and scrolls the new rule in B/S notation across the grid. Holding A (or shaking) re-randomizes the
+//!     board (keeping the rule), and it restarts 500ms after it dies out under the current rule. The host can start it on
+//!     another rule over RTT with a line `rule=B36/S23`
+//! 22. After 60s without a btn press or a shake the display dims one brightness level every 500ms down to 2 of 9, and
+//!     returns to full brightness on the next press or shake
+//! 23. The display brightness is one of 5 settings (full brightness at power-up), set in the settings menu (Spec 7) or from
+//!     the host over RTT with a line `brightness=N`; the idle dimming never brightens past it
+//! 24. The board takes a step every 100ms, independent of the frame rate, and the host can change this over RTT with a
+//!     line `step_ms=N` (10 to 10000): longer holds each generation for several frames (slow motion), shorter computes
+//!     several generations per frame and shows the last (fast forward)
+//! 25. Pressing A twice within 400ms (a double press), or touching the gold logo, pauses the board, and either again resumes it. The A btn's
+//!     presses are classified as short, long (1s, Spec 11), or double presses so that one btn can carry several actions
+//! 26. The pour mode steps Conway's rules, and while the MB2 is tilted it lights 2 random cells along the lowest edge every frame,
+//!     so a dying board can be kept alive by hand. Holding A (or shaking) re-randomizes it
+//! 27. With the `microphone` cargo feature, a loud sound (a clap or a whistle) lights 1 to 10 random cells of a Life or pour
+//!     board in play, more the louder it is
+//! 28. Holding only the A btn while the MB2 powers on starts a demo mode, which selects the next step mode every 15s and
+//!     scrolls its name, until any btn, logo touch, or shake ends it
+//! 29. With the `potentiometer` cargo feature, a potentiometer wired to the edge connector (its wiper on ring 0, its ends on
+//!     3V and GND) sets the step interval of Spec 24 continuously from 50ms to 2s whenever it is turned
 
 #![no_main]
 #![no_std]
 
-mod life;
+mod display;
+mod light;
+#[cfg(feature = "neopixel")]
+mod neopixel;
+mod postmortem;
+mod pot;
+#[cfg(feature = "ppi-display")]
+mod scan;
+mod sleep;
+mod sound;
+mod touch;
+mod watchdog;
 
 use cortex_m_rt::entry;
-use embedded_hal::digital::InputPin;
-use microbit::hal::gpio::p0::{P0_14, P0_23};
-use microbit::{Board, display::blocking::Display, hal::Rng, hal::timer::Timer};
+use lsm303agr::{AccelMode, AccelOutputDataRate, AccelScale, Lsm303agr};
+use microbit::hal::twim::{self, Twim};
+use microbit::{Board, hal::timer::Timer};
 
-use panic_rtt_target as _;
-use rtt_target::rtt_init_print;
+use rtt_target::{rtt_init, set_print_channel};
 
-/// The MB2 has 5 LED rows and 5 LED columns
-const ROW_COUNT: usize = 5;
-/// Type definition defining the LEDState to be a 5x5 array of u8 variables
-type LEDState = [[u8; ROW_COUNT]; ROW_COUNT];
-/// Spec 1: 10 frames per second refresh rate (100ms)
-const REFRESH_RATE_MS: u32 = 100;
-/// Per Spec 5: a "dead" state waits 500ms
-const DEATH_RESET_RATE_MS: u32 = 500;
-/// Per Spec 4: a complement action can only occur 1 time per 500ms
-const COMPLEMENT_RESET_RATE_MS: u32 = 500;
+#[cfg(feature = "temp-seed")]
+use gol::random;
+#[cfg(feature = "record")]
+use gol::record;
+use gol::render::Renderer;
+#[cfg(feature = "neopixel")]
+use gol::ws2812;
+use gol::{
+    ButtonPress, COL_COUNT, DEFAULT_BRIGHTNESS, LEDState, REFRESH_RATE_MS, ROW_COUNT, ResetTimer,
+    live_cells, ms_to_frames, pack_board, time_left_us, unpack_board,
+};
+use gol::{
+    age, boot, collapse, events, fade, fault, fps, game, generations, gravity, heatmap, highlight,
+    hud, idle, input, intro, life, orientation, persist, remote, render, selftest, shake, stats,
+    supersample, teams, trail,
+};
 
-/// ButtonPress Trait
-///
-/// Defines a convience trait that can extend the methods available to the HAL GPIO pins.
-/// Requires that the implementors of the ButtonPress trait also implement the Hal::digital::InputPin
-/// trait for meaningful implementation
-trait ButtonPress: InputPin {
-    /// fn pressed(&mut self) -> bool : Abstract!
-    ///
-    /// Must be defined by the implementor. Should return true if the button is pressed and false otherwise.
-    /// Alternatively, this function could be interpreted as returning true if the user is influence the
-    /// InputPin to be in a state other than it's Reset state
-    fn pressed(&mut self) -> bool;
-}
+/// With the `microphone` cargo feature a clap or a whistle spawns live cells, more the louder it is (sound.rs, Spec 27)
+const MICROPHONE_ENABLED: bool = cfg!(feature = "microphone");
+/// With the `potentiometer` cargo feature a knob on edge connector ring 0 sets the step interval (pot.rs, Spec 29)
+const POTENTIOMETER_ENABLED: bool = cfg!(feature = "potentiometer");
+/// With the `auto-brightness` cargo feature the display dims in a dark room, down to AMBIENT_MIN_BRIGHTNESS, measuring the
+/// light through the LEDs once every LIGHT_SAMPLE_MS (light.rs). It needs the default nonblocking display
+const AUTO_BRIGHTNESS_ENABLED: bool = cfg!(feature = "auto-brightness")
+    && !cfg!(any(
+        feature = "blocking-display",
+        feature = "ppi-display",
+        feature = "neopixel"
+    ));
+const AMBIENT_MIN_BRIGHTNESS: u8 = 2;
+const LIGHT_SAMPLE_MS: u32 = 1_000;
+/// With the `orientation` cargo feature the board is turned to stay upright however the MB2 is held (orientation.rs)
+const ORIENTATION_ENABLED: bool = cfg!(feature = "orientation");
+/// Spec 22: after IDLE_DIM_MS without a btn press or a shake the display dims by one brightness level every
+/// IDLE_DIM_STEP_MS, down to IDLE_BRIGHTNESS (of fade::MAX_BRIGHTNESS)
+const IDLE_DIM_MS: u32 = 60_000;
+const IDLE_DIM_STEP_MS: u32 = 500;
+const IDLE_BRIGHTNESS: u8 = 2;
+/// Play the short intro animation (intro.rs) at power-up before the first random board
+const INTRO_ENABLED: bool = true;
+/// With the `trail` cargo feature, recently dead cells leave a fading trail instead of the generation crossfade
+const TRAIL_ENABLED: bool = cfg!(feature = "trail");
+/// With the `dissolve` cargo feature, the generation crossfade is replaced by a dissolve: the changing cells flip one
+/// at a time in a scattered order over the frame (see fade::Frame::Dissolve)
+const DISSOLVE_ENABLED: bool = cfg!(feature = "dissolve");
+/// With the `highlight` cargo feature, newly born cells flash brightest after each Life step instead of the crossfade
+const HIGHLIGHT_ENABLED: bool = cfg!(feature = "highlight");
+/// With the `age` cargo feature, live cells are shaded by age (age.rs) instead of the crossfade
+const AGE_ENABLED: bool = cfg!(feature = "age");
+/// Brightness of the oldest cells with the `age` feature (0..=fade::MAX_BRIGHTNESS), so they do not all glare at full blast
+const AGE_MAX_BRIGHTNESS: u8 = 7;
+/// With the `age-fade` cargo feature, newborn cells are shown at full brightness and fade as they age (age.rs) instead
+/// of the crossfade, down to AGE_MIN_BRIGHTNESS (0..=fade::MAX_BRIGHTNESS) for the oldest
+const AGE_FADE_ENABLED: bool = cfg!(feature = "age-fade");
+const AGE_MIN_BRIGHTNESS: u8 = 2;
+/// How often the measured frame rate is reported with the `fps-log` feature
+const FPS_REPORT_MS: u32 = 1_000;
 
-/// Implementation of ButtonPress trait for bus 0, pin 14 (the A btn)
-impl<T> ButtonPress for P0_14<T>
-where
-    P0_14<T>: InputPin,
-{
-    /// Returns true if the voltage on the bus 0 pin 14 is equal to ground.
-    /// The A btn is pressed when the voltage is equal to ground because this btn is a Momentary switch
-    /// (Normally Open) and so the pressed state completes the circuit (see the nRF52820 schematic)
-    ///
-    /// To protect against potential bounce problems, the voltage state is queried 3 times
-    fn pressed(&mut self) -> bool {
-        // protect against bounce:
-        self.is_low().unwrap() & self.is_low().unwrap() & self.is_low().unwrap()
+/// fn log_state(&LEDState, &stats::Stats)
+///
+/// Emits the board over RTT as a single compact line: 25 characters ('#' for a lit LED and '.' for
+/// an unlit LED) in top-left to bottom-right order, followed by the generation number, the population,
+/// births, and deaths, and the population averaged over the last stats::WINDOW steps.
+/// Only compiled in with the `rtt-log` cargo feature. rtt-target's default channel mode skips
+/// output when the host is not draining the buffer, so a line costs a short memcpy rather than
+/// ever blocking the 100ms frame
+#[cfg(feature = "rtt-log")]
+fn log_state(state: &LEDState, stats: &stats::Stats) {
+    let mut line = [b'.'; ROW_COUNT * COL_COUNT];
+    for (row, col) in live_cells(state) {
+        line[row * COL_COUNT + col] = b'#';
     }
-}
 
-/// Implementation of ButtonPress trait for bus 0, pin 23 (the B btn)
-impl<T> ButtonPress for P0_23<T>
-where
-    P0_23<T>: InputPin,
-{
-    /// Returns true if the voltage on the bus 0 pin 23 is equal to ground.
-    /// The B btn is pressed when the voltage is equal to ground because this btn is a Momentary switch
-    /// (Normally Open) and so the pressed state completes the circuit (see the nRF52820 schematic)
-    ///
-    /// To protect against potential bounce problems, the voltage state is queried 3 times
-    fn pressed(&mut self) -> bool {
-        // protect against bounce:
-        self.is_low().unwrap() & self.is_low().unwrap() & self.is_low().unwrap()
-    }
+    // line only ever contains ASCII so this conversion cannot fail
+    let line = core::str::from_utf8(&line).unwrap_or_default();
+    let sample = stats.current();
+    rtt_target::rprintln!(
+        "{} {} pop {} +{} -{} avg {:.1}",
+        line,
+        sample.generation,
+        sample.population,
+        sample.births,
+        sample.deaths,
+        stats.averages().population
+    );
 }
 
-/// fn randomize_state(&mut Rng, &mut LEDState)
-///
-/// Takes a mutable reference to the Hal hardware random number generator (Rng) and
-/// a mutable references to the 5x5 array LEDState which is altered in-place.
+/// fn log_state(&LEDState, &stats::Stats)
 ///
-/// A random u32 is drawn from the MB2 random number generator and is used to set
-/// the LEDState array by taking the right-most 25 bits (25 MSB on an LSB architecture)
-/// and assigning them to the LEDState in order (top-left to bottom-right).
-fn randomize_state(random_gen: &mut Rng, state: &mut LEDState) {
-    const LED_COUNT: usize = ROW_COUNT * ROW_COUNT;
-    let random_number: u32 = random_gen.random_u32();
+/// No-op stand in when the `rtt-log` feature is disabled so that release builds stay lean
+#[cfg(not(feature = "rtt-log"))]
+fn log_state(_state: &LEDState, _stats: &stats::Stats) {}
 
-    for i in 0..LED_COUNT {
-        let row: usize = i / ROW_COUNT;
-        let col: usize = i % ROW_COUNT;
-
-        //extract bit at ith position as 1 or 0 then cast as u8
-        let bit: u8 = ((random_number & 1 << i) >> i) as u8;
-        state[row][col] = bit;
-    }
+/// fn log_fps(&fps::Report)
+///
+/// Emits the measured frame rate over RTT along with the average frame time and the part of it
+/// spent working rather than waiting for the next frame (the per-frame overhead).
+/// Only compiled in with the `fps-log` cargo feature
+#[cfg(feature = "fps-log")]
+fn log_fps(report: &fps::Report) {
+    rtt_target::rprintln!(
+        "fps {} frame {}us logic {}us",
+        report.fps(),
+        report.frame_us,
+        report.logic_us
+    );
 }
 
-/// fn complement_state(&mut LEDState)
+/// fn log_fps(&fps::Report)
 ///
-/// Takes a mutable reference to the current LEDState and alters it in-place
+/// No-op stand in when the `fps-log` feature is disabled
+#[cfg(not(feature = "fps-log"))]
+fn log_fps(_report: &fps::Report) {}
+
+/// fn clamp_accel((i32, i32, i32)) -> (i16, i16, i16)
 ///
-/// Given the current LEDState, iterate through each LED Diode and flip its state
-/// (on->off and off->on). Each LED is mutably iterated through and its state is
-/// complemented using XOR boolean logic
-fn complement_state(state: &mut LEDState) {
-    for row in state.iter_mut() {
-        for item in row.iter_mut() {
-            *item ^= 1;
-        }
-    }
+/// Narrows an (x, y, z) accelerometer reading in mg, as produced by the lsm303agr driver,
+/// into the i16 samples consumed by the ShakeDetector (saturating rather than wrapping)
+fn clamp_accel(mg: (i32, i32, i32)) -> (i16, i16, i16) {
+    let clamp = |v: i32| v.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+    (clamp(mg.0), clamp(mg.1), clamp(mg.2))
 }
 
-/// ResetTimer Struct
+/// fn wait_until(&mut impl Renderer, &mut Sleeper, &Timer<TIMER1, Periodic>, u32, u32)
 ///
-/// The ResetTimer struct tracks a current loop count (multiple of the REFRESH_RATE_MS) and a
-/// total loop count (also a multiple of REFRESH_RATE_MS) to determine when a period of time has elapsed.
-struct ResetTimer<T> {
-    btimer: Timer<T>,
-    total: u32,
+/// Waits until offset_us microseconds after start on clock. A renderer which has to scan the matrix itself
+/// (the `blocking-display` feature) is kept lit by whole scans until less than one is left, and the rest of
+/// the wait is slept through
+fn wait_until(
+    renderer: &mut impl render::Renderer,
+    sleeper: &mut sleep::Sleeper,
+    clock: &Timer<microbit::pac::TIMER1, microbit::hal::timer::Periodic>,
+    start: u32,
+    offset_us: u32,
+) {
+    while time_left_us(start, clock.read(), offset_us) >= render::SCAN_MS * 1_000
+        && renderer.hold(render::SCAN_MS)
+    {}
+    sleeper.sleep_until(clock, start, offset_us);
 }
 
-/// Implt ResetTimer
+/// fn init_peripherals() -> Result<Board, InitError>
 ///
-/// Provides method to initalize the reset timer, reset its counting, update the clock,
-/// and check if the timer has expired
-impl<T> ResetTimer<T>
-where
-    T: microbit::hal::timer::Instance,
-{
-    //timer can apparently count up to ~4000 seconds before overflow (~1hr)
-    const TICKS_PER_MS: u32 = 1_000_000 / 1_000;
-    const TIMER_START_CYCLES: u32 = !0; //~4 billion
-
-    /// fn new(u32, u32) -> Self
-    ///
-    /// Returns a new ResetTimer instance initialized to ms_total total milliseconds (the expiration time)
-    /// and initialized to a current start time. The timer will instantly begin so that it get's out of it's dual 
-    /// purpose 0 state. Use reset() to restart the timer
-    fn new(timer: Timer<T>, ms_total: u32) -> Self {
-        if ms_total >= 4_294_967 {
-            panic!("hw max timer duration is 4,294.967 seconds");
-        }
-
-        let mut instance = ResetTimer {
-            btimer: timer,
-            total: ms_total,
-        };
-
-        instance.btimer.start(ResetTimer::<T>::TIMER_START_CYCLES);
-        instance
-    }
-
-    /// fn reset(&mut self)
-    ///
-    /// reset the timer to it's starting state (furthest from expired)
-    fn reset(&mut self) {
-        self.btimer.start(ResetTimer::<T>::TIMER_START_CYCLES);
-    }
-
-    /// fn tick(&mut self, bool) -> bool
-    ///
-    /// This method will update the timer's count, returning true if this update
-    /// has caused the timer to expire and false otherwise. If reset_if_finished is true,
-    /// then the internal timer state will reset if this function returns true
-    fn tick(&mut self, reset_if_finished: bool) -> bool {
-        let is_finished = self.finished();
-        if is_finished && reset_if_finished {
-            self.reset();
-        }
-
-        is_finished
-    }
-
-    /// fn finsihed(&self) -> bool
-    ///
-    /// This method will return true if the timer has expired and false otherwise
-    fn finished(&self) -> bool {
-        let cycles = self.btimer.read();
-        let ms = cycles / ResetTimer::<T>::TICKS_PER_MS;
-
-        //cycles == 0 means timer has expired (approx 1 hr elapsed)
-        ms >= self.total || cycles == 0
-    }
+/// Take the MB2's peripherals, failing (rather than panicking) if they were already taken so that
+/// main can report the failure through fault::halt
+fn init_peripherals() -> Result<Board, fault::InitError> {
+    Board::take().ok_or(fault::InitError::BoardTaken)
 }
 
 /// Main entry point for the MB2
 ///
 /// The following outlines the steps process of this embeded program:
-/// 1. Initialize structs and grab handles to MB2 peripherals that will be used
-/// 2. Initialize the LED GOL state to a random starting board
+/// 1. Initialize structs and grab handles to MB2 peripherals that will be used. If this fails, the
+///    reason is logged over RTT, an X is shown on the LEDs if they are up, and the MB2 halts (fault.rs)
+/// 2. Initialize the Game, which starts on a random board
 /// 3. Event Loop
-///     1. Display the GOL state on the LEDs for REFRESH_RATE_MS duration
-///     2. If A btn is pressed, re-randomize the GOL state
-///     3. Else if B btn is pressed and the complement_timer has expired, complement the current board
-///        (and reset the complement timer). If complement_timer has not expired then the GOL state remains unchanged
-///     4. If the GOL state is done ("dead") and the reset_timer is expired, re-randomize the GOL state. If the
-///        reset_timer is not expired then the GOL state remains unchanged
-///     5. If 2-4 have not occured during this frame, then a GOL step is taken to update the GOL state as defined
-///        in life.rs module
-///     6. The compelent_timer is updated every frame (note the rest_timer is only updated each "dead" frame)
+///     1. Load any board pushed by the host over RTT
+///     2. Hand the GOL state to the nonblocking display (HUD, trail, heatmap, age, highlight, or crossfade as configured)
+///     3. Read the accelerometer (shake and tilt) and the A and B btns (from the events queued since the last frame)
+///     4. Update the Game with these inputs, which randomizes, complements, restarts, or steps the board
+///        as described by Game::update in game.rs. A restart first plays the collapse animation
+///     5. Step any crossfade through the rest of the frame, sampling the btns at each sub-frame, so that frames
+///        start exactly REFRESH_RATE_MS apart
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
+    // up channel 0 carries panic output (and rtt-log frames), down channel 0 accepts boards from the host
+    let channels = rtt_init! {
+        up: {
+            0: {
+                size: 1024,
+                name: "Terminal"
+            }
+        }
+        down: {
+            0: {
+                size: 64,
+                name: "Terminal"
+            }
+        }
+    };
+    set_print_channel(channels.up.0);
+    let mut host_input = channels.down.0;
+    let mut line_reader = remote::LineReader::new();
 
     // initialize structs and grab handles to MB2 peripherals
-    let board = Board::take().unwrap();
+    let board = init_peripherals().unwrap_or_else(|error| fault::halt(error, None));
     let mut timer = Timer::new(board.TIMER0);
-    let mut display = Display::new(board.display_pins);
-    let mut random_gen = Rng::new(board.RNG); //hardware trigger
-    let mut reset_timer = ResetTimer::new(Timer::new(board.TIMER1), DEATH_RESET_RATE_MS);
-    let mut complement_timer = ResetTimer::new(Timer::new(board.TIMER2), COMPLEMENT_RESET_RATE_MS); // initialized to a finished() == true state
+    // Timer1 free-runs at 1MHz (wrapping every ~71 minutes) to measure the real frame time
+    let mut clock = Timer::periodic(board.TIMER1);
+    clock.start(u32::MAX);
+    let mut frame_meter = fps::FrameMeter::new(ms_to_frames(FPS_REPORT_MS, REFRESH_RATE_MS));
+    // with the `temp-seed` cargo feature the game is driven by xorshift32 seeded from the temperature
+    // sensor instead, so the same temperature always gives the same sequence of boards
+    #[cfg(not(feature = "temp-seed"))]
+    let random_gen = microbit::hal::Rng::new(board.RNG); //hardware trigger
+    #[cfg(feature = "temp-seed")]
+    let random_gen = random::XorShift32::new(random::read_temp_seed(
+        &mut microbit::hal::Temp::new(board.TEMP),
+    ));
+    let mut flash = persist::Nvmc::new(board.NVMC);
+    // a hung display or I2C bus resets the MB2 after 2s (20 frames) without a feed
+    let mut watchdog = watchdog::Watchdog::start(board.WDT);
+
+    // Configure the accelerometer on the internal I2C bus for shake detection (range raised to +/-8g
+    // so that shakes are not clipped at the default 2g)
+    let i2c = Twim::new(
+        board.TWIM0,
+        board.i2c_internal.into(),
+        twim::Frequency::K100,
+    );
+    let mut accelerometer = Lsm303agr::new_with_i2c(i2c);
+    // frames are drawn on the onboard LED matrix (refreshed in the background from the Timer2
+    // interrupt) through the Renderer trait so that another output backend can be swapped in here.
+    // It is set up before the accelerometer so that a failure there can still be shown on it
+    let mut nvic = board.NVIC;
+    #[cfg(not(any(
+        feature = "blocking-display",
+        feature = "ppi-display",
+        feature = "neopixel"
+    )))]
+    let display = display::Greyscale::new(board.TIMER2, board.display_pins, &mut nvic);
+    // with the `ppi-display` cargo feature the rows are switched by TIMER4 in hardware instead (scan.rs)
+    #[cfg(all(
+        feature = "ppi-display",
+        not(any(feature = "blocking-display", feature = "neopixel"))
+    ))]
+    let display = scan::Scanned::new(
+        board.TIMER4,
+        board.GPIOTE,
+        board.PPI,
+        board.display_pins,
+        &mut nvic,
+    );
+    // with the `blocking-display` cargo feature the matrix is instead scanned (and dithered) from the
+    // main loop whenever it waits, see wait_until
+    #[cfg(all(feature = "blocking-display", not(feature = "neopixel")))]
+    let display = display::Dithered::new(board.TIMER2, board.display_pins);
+    // with the `neopixel` cargo feature frames go to an external WS2812 panel on ring 1 instead, sent by PWM0 (neopixel.rs)
+    #[cfg(feature = "neopixel")]
+    let display = ws2812::Panel::new(neopixel::PwmLink::new(board.PWM0, board.edge.e01));
+    let mut renderer = render::Dimmed::new(render::Rotated::new(display), DEFAULT_BRIGHTNESS);
+    let mut orientation = orientation::OrientationDetector::new();
+    // the SAADC is shared by the ambient light sensor, the microphone, and the speed knob, each setting it up for its
+    // own readings
+    let saadc = board.ADC;
+    let mut light = light::LightSensor::new(AMBIENT_MIN_BRIGHTNESS);
+    // with the `microphone` feature a loud sound spawns live cells (sound.rs). Powering the microphone lights its LED,
+    // so it is left off otherwise
+    let mut microphone = MICROPHONE_ENABLED.then(|| sound::Microphone::new(board.microphone_pins));
+    // with the `potentiometer` feature a knob wired to the edge connector sets the speed (pot.rs)
+    let mut knob = POTENTIOMETER_ENABLED.then(|| pot::Potentiometer::new(board.edge.e00));
+    let mut light_timer = ResetTimer::new(ms_to_frames(LIGHT_SAMPLE_MS, REFRESH_RATE_MS));
+    let mut idle = idle::IdleDimmer::new(
+        ms_to_frames(IDLE_DIM_MS, REFRESH_RATE_MS),
+        ms_to_frames(IDLE_DIM_STEP_MS, REFRESH_RATE_MS),
+        IDLE_BRIGHTNESS,
+    );
+    if accelerometer
+        .init()
+        .and_then(|()| {
+            accelerometer.set_accel_mode_and_odr(
+                &mut timer,
+                AccelMode::Normal,
+                AccelOutputDataRate::Hz50,
+            )
+        })
+        .and_then(|()| accelerometer.set_accel_scale(AccelScale::G8))
+        .is_err()
+    {
+        fault::halt(fault::InitError::Accelerometer, Some(&mut renderer));
+    }
+    let mut shake_detector =
+        shake::ShakeDetector::new(shake::SHAKE_THRESHOLD_MG, shake::SHAKE_FRAMES);
 
     // Configure buttons
     let mut button_a = board.buttons.button_a;
     let mut button_b = board.buttons.button_b;
 
-    let mut state: LEDState = [[0; 5]; 5]; // initialize to all zeros
-    randomize_state(&mut random_gen, &mut state); //Spec 2: starts with a random board
+    // between frames the CPU sleeps until Timer3 (or a display refresh) wakes it
+    let mut sleeper = sleep::Sleeper::new(board.TIMER3, &mut nvic);
+
+    // the btns held at power-up select how the MB2 starts (boot.rs). The selected mode's icon stays up
+    // until they are released, feeding the watchdog meanwhile since they may be held for any time
+    let boot_mode = boot::BootMode::select(button_a.pressed(), button_b.pressed());
+    boot_mode.show(&mut renderer, &mut timer, || {
+        watchdog.feed();
+        !button_a.pressed() && !button_b.pressed()
+    });
+    // show the splash animation first (skipped by pressing either btn) so it is clear the MB2 booted,
+    // or with A+B held at power-up, walk a pixel across every LED to check the matrix instead
+    if boot_mode == boot::BootMode::SelfTest {
+        selftest::SELF_TEST.play(&mut renderer, &mut timer, || false);
+    } else if INTRO_ENABLED && boot_mode == boot::BootMode::Normal {
+        intro::INTRO.play(&mut renderer, &mut timer, || {
+            button_a.pressed() || button_b.pressed()
+        });
+    }
+
+    let mut game = game::Game::new(random_gen); //Spec 2: starts with a random board
+
+    // holding B while the MB2 powers on (or is reset) restores the board saved to flash, if any, and
+    // holding A starts the demo mode
+    if boot_mode == boot::BootMode::Saved
+        && let Some(bits) = persist::load_board(&flash)
+    {
+        game.load(unpack_board(bits));
+    } else if boot_mode == boot::BootMode::Demo {
+        game.start_demo();
+    }
+    // from here on the btns are debounced by sampling them every sub-frame (input.rs), and their presses and releases
+    // are queued as events until the next frame reads them, so even a tap shorter than a frame is seen (events.rs)
+    let mut button_a = input::Debounced::new(button_a);
+    let mut button_b = input::Debounced::new(button_b);
+    // the touch logo is a third btn, debounced the same way (touch.rs). It is calibrated here, untouched
+    let mut logo = input::Debounced::new(touch::TouchLogo::new(board.pins.p1_04));
+    let mut event_queue =
+        heapless::spsc::Queue::<events::Event, { events::EVENT_QUEUE_LEN }>::new();
+    let (mut events_in, mut events_out) = event_queue.split();
+    let mut inputs = events::Frame::new();
+    let mut trail = trail::Trail::new();
+    let mut heatmap = heatmap::Heatmap::new();
+    // with the `record` cargo feature every new board is streamed over RTT for the host (record.rs)
+    #[cfg(feature = "record")]
+    let mut recorder = record::RunRecorder::start();
+
+    // Frames start on a fixed REFRESH_RATE_MS grid measured by Timer1, each split into
+    // fade::SUB_FRAMES sub-frames at which the displayed image may change
+    const REFRESH_RATE_US: u32 = REFRESH_RATE_MS * 1_000;
+    const SUB_FRAME_US: u32 = REFRESH_RATE_US / fade::SUB_FRAMES;
+    let mut frame_start = clock.read();
 
     loop {
-        display.show(&mut timer, state, REFRESH_RATE_MS);
+        // the watchdog is fed here once per frame (and otherwise only by the boot menu), outside of every blocking call
+        watchdog.feed();
+
+        // apply any complete command line (a board, a mutation rate, a brightness, a step interval, or a rule) pushed by the host since the last frame
+        line_reader.poll(&mut host_input, |command| match command {
+            remote::Command::Board(loaded) => game.load(loaded),
+            remote::Command::MutationRate(rate) => game.set_mutation_rate(rate),
+            remote::Command::Brightness(setting) => game.set_brightness(setting),
+            remote::Command::StepInterval(step_ms) => game.set_step_interval(step_ms),
+            remote::Command::Rule(rule) => game.set_rule(rule),
+        });
+        // Spec 29: turning the knob sets the step interval, until the host or the settings menu sets another
+        if let Some(step_ms) = knob.as_mut().and_then(|knob| knob.read(&saadc)) {
+            game.set_step_interval(step_ms);
+        }
+
+        let state = *game.state();
+        log_state(&state, game.stats());
+        postmortem::record(&state, game.generation());
+        #[cfg(feature = "record")]
+        recorder.record(game.generation(), pack_board(&state));
+
+        // while the A+B chord is held, overlay the population bar on the rightmost column. An empty
+        // board (once the step that emptied it has faded out) pulses its center pixel, brighter and
+        // brighter, while the dead-board restart counts down. Otherwise draw the trail (if enabled),
+        // the heatmap (if switched on), shade cells by age (if enabled), highlight births (if
+        // enabled), or crossfade from the previous generation if a step was just taken
+        trail.update(&state);
+        // the heatmap keeps counting while it is switched off, so it is up to date when switched on
+        if game.stepped_from().is_some() {
+            heatmap.update(&state);
+        }
+        let frame = if game.show_hud() {
+            fade::Frame::Board(hud::with_population_bar(&state))
+        } else if life::done(&state) && game.stepped_from().is_none() {
+            fade::Frame::Empty {
+                countdown: game.restart_progress(),
+            }
+        } else if let Some(teams) = game.teams() {
+            fade::Frame::Levels(teams::levels(teams))
+        } else if let Some(fine) = game.supersampled() {
+            fade::Frame::Levels(supersample::downsample(fine))
+        } else if let Some((cells, rule)) = game.generations() {
+            fade::Frame::Levels(generations::levels(cells, rule.states))
+        } else if let Some(wireworld) = game.wireworld() {
+            fade::Frame::Levels(wireworld.levels())
+        } else if let Some(colony) = game.colony() {
+            fade::Frame::Levels(colony.levels())
+        } else if TRAIL_ENABLED {
+            fade::Frame::Levels(trail.levels(&state))
+        } else if game.show_heatmap() {
+            fade::Frame::Levels(heatmap.levels(&state))
+        } else if AGE_ENABLED {
+            fade::Frame::Levels(age::levels(game.ages(), AGE_MAX_BRIGHTNESS))
+        } else if AGE_FADE_ENABLED {
+            fade::Frame::Levels(age::fading_levels(game.ages(), AGE_MIN_BRIGHTNESS))
+        } else if let Some(step) = game.classified().filter(|_| HIGHLIGHT_ENABLED) {
+            fade::Frame::Levels(highlight::levels(step))
+        } else if let Some(prev) = game.stepped_from().filter(|_| DISSOLVE_ENABLED) {
+            fade::Frame::Dissolve {
+                prev: *prev,
+                next: state,
+            }
+        } else if let Some(prev) = game.stepped_from() {
+            fade::Frame::Crossfade {
+                prev: *prev,
+                next: state,
+            }
+        } else {
+            fade::Frame::Board(state)
+        };
+        match &frame {
+            fade::Frame::Board(board) => renderer.render(board),
+            _ => renderer.render_levels(&frame.levels_at(0.0)),
+        }
 
-        if button_a.pressed() {
-            reset_timer.reset();
-            randomize_state(&mut random_gen, &mut state); //Spec 3: while btn A pressed, randomize every frame
-        } else if button_b.pressed() {
-            reset_timer.reset();
+        // a failed read is treated as "not shaking" and "lying flat" rather than halting the game
+        let accel = accelerometer
+            .acceleration()
+            .ok()
+            .map(|accel| clamp_accel(accel.xyz_mg()));
+        if accel.is_some_and(|accel| shake_detector.update(accel)) {
+            events_in.enqueue(events::Event::Shake).ok();
+        }
+        let (x, y, _) = accel.unwrap_or((0, 0, 0));
+        let mut down = gravity::Direction::from_accel(x, y);
+        if ORIENTATION_ENABLED {
+            // the next frame is drawn turned upright, and the tilt is passed on in the board's own
+            // directions so that e.g. the gravity mode's cells fall towards the edge that is down on screen
+            let upright = orientation.update(down);
+            renderer.inner_mut().set_orientation(upright);
+            down = upright.to_board(down);
+        }
+        // everything the inputs did since the last frame
+        inputs.drain(&mut events_out);
+        game.set_motion(inputs.shaken(), down);
+        game.set_logo(inputs.pressed(events::Button::Logo));
+        let level = microphone.as_mut().map_or(0, sound::Microphone::take_level);
+        game.set_burst(sound::burst_cells(level));
 
-            //Spec 4: If B btn pressed, complement state, then ignore B btn for 500 ms
-            if complement_timer.finished() {
-                complement_state(&mut state);
-                complement_timer.reset();
+        game.update(
+            inputs.pressed(events::Button::A),
+            inputs.pressed(events::Button::B),
+        );
+        // Spec 23: the brightness setting is chosen in the settings menu or over RTT
+        renderer.set_setting(game.brightness());
+        // Spec 22: any input restores full brightness, otherwise the display dims once idle for long enough
+        idle.update(inputs.any());
+        // and with the `auto-brightness` feature the ambient light can dim it further
+        renderer.set_brightness(idle.brightness().min(light.brightness()));
+        // a restarted board collapses away before the new one is shown (skipped by either btn, whose
+        // press is not queued so that the game does not also act on it). The frame overruns the grid
+        // by the 400ms this takes, well inside the watchdog timeout
+        if game.restarted() {
+            collapse::COLLAPSE.play(&mut renderer, &mut timer, || {
+                events::poll_releases(&mut events_in, events::Button::A, &mut button_a);
+                events::poll_releases(&mut events_in, events::Button::B, &mut button_b);
+                button_a.pressed() || button_b.pressed()
+            });
+        }
+        if game.save_requested() {
+            persist::save_board(&mut flash, pack_board(game.state()));
+        }
+
+        // the display keeps refreshing in the background (or is scanned by wait_until), so the rest of the frame is only spent
+        // stepping a crossfade through its sub-frames, sampling the btns at each one so that no tap is
+        // missed, and then sleeping until the end of the frame. A frame that overran the grid (e.g. while
+        // saving to flash) restarts the grid from now
+        let logic_end = clock.read();
+        if time_left_us(frame_start, logic_end, REFRESH_RATE_US) > 0 {
+            for phase in 1..fade::SUB_FRAMES {
+                wait_until(
+                    &mut renderer,
+                    &mut sleeper,
+                    &clock,
+                    frame_start,
+                    phase * SUB_FRAME_US,
+                );
+                events::poll(&mut events_in, events::Button::A, &mut button_a);
+                events::poll(&mut events_in, events::Button::B, &mut button_b);
+                events::poll(&mut events_in, events::Button::Logo, &mut logo);
+                if let Some(microphone) = &mut microphone {
+                    microphone.sample(&saadc);
+                }
+                if frame.animated() {
+                    let t = phase as f32 / (fade::SUB_FRAMES - 1) as f32;
+                    renderer.render_levels(&frame.levels_at(t));
+                }
+            }
+            // the ambient light is measured in the last sub-frame, which it blanks for a few ms
+            if AUTO_BRIGHTNESS_ENABLED && light_timer.tick(true) {
+                light.measure(&saadc, &mut timer);
             }
-        } else if life::done(&state) {
-            // Spec 5: if all cells "dead", count 500 ms. If no user input after 500 ms, randomize state
-            if reset_timer.tick(true) {
-                randomize_state(&mut random_gen, &mut state);
+            wait_until(
+                &mut renderer,
+                &mut sleeper,
+                &clock,
+                frame_start,
+                REFRESH_RATE_US,
+            );
+            events::poll(&mut events_in, events::Button::A, &mut button_a);
+            events::poll(&mut events_in, events::Button::B, &mut button_b);
+            events::poll(&mut events_in, events::Button::Logo, &mut logo);
+            if let Some(microphone) = &mut microphone {
+                microphone.sample(&saadc);
             }
+            frame_start = frame_start.wrapping_add(REFRESH_RATE_US);
         } else {
-            // Spec 6: If not A btn press, not B btn press, and not all cells "dead", take GOL step
-            reset_timer.reset();
-            life::life(&mut state);
+            frame_start = logic_end;
         }
 
-        // tick complement_timer: at least 500 ms between complement action
-        complement_timer.tick(false);
+        let now = clock.read();
+        if let Some(report) = frame_meter.record(now, now.wrapping_sub(logic_end)) {
+            log_fps(&report);
+        }
     }
 }
@@ -0,0 +1,200 @@
+//! The on-device settings menu.
+//!
+//! Tapping the A and B btns together opens the menu, in place of the board, and tapping them
+//! together again closes it. While it is open each press of A moves on to the next setting and
+//! each press of B changes the setting shown to its next value, wrapping around. The setting
+//! and its value scroll across the grid as text (e.g. "SPEED 200"), over and over until the
+//! next press, so a setting can be read however long ago it was picked.
+//!
+//! The menu only keeps track of which setting is shown; the settings themselves live in the Game
+//! (main() applies its brightness setting to the display), and this module only lists the values
+//! each one cycles through.
+
+use crate::MUTATION_ENABLED;
+use crate::life::EdgeMode;
+
+/// Step intervals (Spec 24) the speed setting cycles through, in milliseconds
+pub const SPEEDS_MS: [u32; 5] = [50, 100, 200, 500, 1_000];
+/// Densities of randomized boards the density setting cycles through, in percent
+pub const DENSITIES_PERCENT: [u8; 5] = [20, 35, 50, 65, 80];
+/// Mutation rates the mutation setting cycles through, in permille
+pub const MUTATION_RATES_PERMILLE: [u16; 5] = [0, 5, 10, 25, 50];
+
+/// A setting of the menu, in the order A moves through them
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Item {
+    /// The time between generations
+    Speed,
+    /// The step mode (game::StepMode)
+    Rule,
+    /// How many cells a randomized board lights
+    Density,
+    /// The display brightness setting (Spec 23)
+    Brightness,
+    /// How neighbors are found across the edges of the grid
+    Boundary,
+    /// What holding the B btn does (game::ComplementPolicy)
+    Complement,
+    /// The chance of each cell flipping after a Life step, only listed with the `mutation` cargo
+    /// feature
+    Mutation,
+    /// Whether the heatmap (heatmap.rs) is drawn in place of the generation crossfade
+    Heatmap,
+}
+
+impl Item {
+    /// The setting after this one, wrapping around.
+    fn next(self) -> Self {
+        match self {
+            Item::Speed => Item::Rule,
+            Item::Rule => Item::Density,
+            Item::Density => Item::Brightness,
+            Item::Brightness => Item::Boundary,
+            Item::Boundary => Item::Complement,
+            Item::Complement if MUTATION_ENABLED => Item::Mutation,
+            Item::Complement | Item::Mutation => Item::Heatmap,
+            Item::Heatmap => Item::Speed,
+        }
+    }
+
+    /// The name scrolled before the setting's value.
+    pub fn label(self) -> &'static str {
+        match self {
+            Item::Speed => "SPEED",
+            Item::Rule => "RULE",
+            Item::Density => "DENSITY",
+            Item::Brightness => "BRIGHT",
+            Item::Boundary => "EDGE",
+            Item::Complement => "B",
+            Item::Mutation => "MUTATE",
+            Item::Heatmap => "HEAT",
+        }
+    }
+}
+
+/// Whether the menu is open, and which setting it shows
+pub struct Menu {
+    open: bool,
+    item: Item,
+}
+
+impl Menu {
+    /// Start closed, on the first setting.
+    pub const fn new() -> Self {
+        Menu {
+            open: false,
+            item: Item::Speed,
+        }
+    }
+
+    /// Open the menu if it is closed or close it if it is open, returning whether it is now open.
+    /// It reopens on the setting it was closed on.
+    pub fn toggle(&mut self) -> bool {
+        self.open = !self.open;
+        self.open
+    }
+
+    /// `true` while the menu is open.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// The setting shown.
+    pub fn item(&self) -> Item {
+        self.item
+    }
+
+    /// Move on to the next setting.
+    pub fn next_item(&mut self) {
+        self.item = self.item.next();
+    }
+}
+
+impl Default for Menu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The speed after `step_ms`: the next longer step interval of SPEEDS_MS, wrapping around to the
+/// shortest (a step interval set over RTT need not be one of them).
+pub fn next_speed(step_ms: u32) -> u32 {
+    SPEEDS_MS
+        .into_iter()
+        .find(|speed| *speed > step_ms)
+        .unwrap_or(SPEEDS_MS[0])
+}
+
+/// The density after `percent`, like next_speed().
+pub fn next_density(percent: u8) -> u8 {
+    DENSITIES_PERCENT
+        .into_iter()
+        .find(|density| *density > percent)
+        .unwrap_or(DENSITIES_PERCENT[0])
+}
+
+/// The mutation rate after `rate_permille`, like next_speed().
+pub fn next_mutation_rate(rate_permille: u16) -> u16 {
+    MUTATION_RATES_PERMILLE
+        .into_iter()
+        .find(|rate| *rate > rate_permille)
+        .unwrap_or(MUTATION_RATES_PERMILLE[0])
+}
+
+/// The name scrolled for a setting that is switched on or off.
+pub fn on_off(on: bool) -> &'static str {
+    if on { "ON" } else { "OFF" }
+}
+
+/// The edge mode after `edge_mode`, wrapping around.
+pub fn next_edge(edge_mode: EdgeMode) -> EdgeMode {
+    match edge_mode {
+        EdgeMode::Toroidal => EdgeMode::Bounded,
+        EdgeMode::Bounded => EdgeMode::Mobius,
+        EdgeMode::Mobius => EdgeMode::Reflect,
+        EdgeMode::Reflect => EdgeMode::Toroidal,
+    }
+}
+
+/// The name scrolled for `edge_mode`.
+pub fn edge_name(edge_mode: EdgeMode) -> &'static str {
+    match edge_mode {
+        EdgeMode::Toroidal => "TORUS",
+        EdgeMode::Bounded => "BOUNDED",
+        EdgeMode::Mobius => "MOBIUS",
+        EdgeMode::Reflect => "REFLECT",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_cycles_through_every_setting_and_back() {
+        let mut menu = Menu::new();
+        let mut items = vec![menu.item()];
+        loop {
+            menu.next_item();
+            if menu.item() == Item::Speed {
+                break;
+            }
+            items.push(menu.item());
+        }
+        assert_eq!(items.len(), if MUTATION_ENABLED { 8 } else { 7 });
+        // the mutation setting only shows with the feature that uses it
+        assert_eq!(items.contains(&Item::Mutation), MUTATION_ENABLED);
+    }
+
+    #[test]
+    fn mutation_rates_go_up_and_wrap_around() {
+        let mut rates = vec![MUTATION_RATES_PERMILLE[0]];
+        for _ in 0..MUTATION_RATES_PERMILLE.len() {
+            rates.push(next_mutation_rate(*rates.last().unwrap()));
+        }
+        assert_eq!(rates, [0, 5, 10, 25, 50, 0]);
+        // a rate set over RTT moves on to the next higher one of the menu's
+        assert_eq!(next_mutation_rate(4), 5);
+        assert_eq!(next_mutation_rate(1000), 0);
+    }
+}
@@ -0,0 +1,72 @@
+//! An external WS2812 ("NeoPixel") panel on edge connector ring 1, driven by PWM0.
+//!
+//! The panel's data line is wired to ring 1 (P0.03) and it is powered from the 3V and GND rings.
+//! PWM0 counts at 16MHz up to ws2812::PERIOD_TICKS, so every period is one bit, and EasyDMA feeds
+//! it one duty word per period from a buffer in RAM (ws2812::encode), leaving the CPU free while
+//! the ~0.8ms frame goes out. A new frame waits for the last one to finish before it is copied
+//! into the buffer, so a frame is never changed while it is being sent.
+
+use core::sync::atomic::{Ordering, compiler_fence};
+
+use microbit::hal::gpio::{Disconnected, Level, Output, Pin, PushPull, p0};
+use microbit::pac::PWM0;
+
+use crate::ws2812::{FRAME_WORDS, PERIOD_TICKS, Transmit};
+
+/// PWM0 sending frames to the panel.
+pub struct PwmLink {
+    pwm: PWM0,
+    /// Owned so that nothing else configures ring 1
+    _data: Pin<Output<PushPull>>,
+    /// The frame EasyDMA reads from, which has to live in RAM for as long as it is being sent
+    words: &'static mut [u16; FRAME_WORDS],
+    /// Whether a frame has been started (and so may still be being sent)
+    started: bool,
+}
+
+impl PwmLink {
+    /// Set up PWM0 to send frames out of `pin` (ring 1), which starts low.
+    pub fn new(pwm: PWM0, pin: p0::P0_03<Disconnected>) -> Self {
+        let data = pin.into_push_pull_output(Level::Low).degrade();
+        pwm.psel.out[0].write(|w| unsafe { w.bits(data.psel_bits()) });
+        pwm.enable.write(|w| w.enable().enabled());
+        pwm.mode.write(|w| w.updown().up());
+        pwm.prescaler.write(|w| w.prescaler().div_1());
+        pwm.countertop
+            .write(|w| unsafe { w.countertop().bits(PERIOD_TICKS) });
+        pwm.loop_.write(|w| w.cnt().disabled());
+        pwm.decoder
+            .write(|w| w.load().common().mode().refresh_count());
+        pwm.seq0.refresh.write(|w| unsafe { w.bits(0) });
+        pwm.seq0.enddelay.write(|w| unsafe { w.bits(0) });
+
+        let words = cortex_m::singleton!(: [u16; FRAME_WORDS] = [0; FRAME_WORDS])
+            .expect("PwmLink::new is only called once");
+        PwmLink {
+            pwm,
+            _data: data,
+            words,
+            started: false,
+        }
+    }
+}
+
+impl Transmit for PwmLink {
+    fn transmit(&mut self, words: &[u16; FRAME_WORDS]) {
+        if self.started {
+            while self.pwm.events_seqend[0].read().bits() == 0 {}
+        }
+        self.pwm.events_seqend[0].reset();
+        self.words.copy_from_slice(words);
+        // the buffer has to be written out before EasyDMA starts reading it
+        compiler_fence(Ordering::SeqCst);
+        let ptr = self.words.as_ptr() as u32;
+        self.pwm.seq0.ptr.write(|w| unsafe { w.bits(ptr) });
+        self.pwm
+            .seq0
+            .cnt
+            .write(|w| unsafe { w.cnt().bits(FRAME_WORDS as u16) });
+        self.pwm.tasks_seqstart[0].write(|w| unsafe { w.bits(1) });
+        self.started = true;
+    }
+}
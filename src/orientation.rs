@@ -0,0 +1,129 @@
+//! Keeping the board upright however the MB2 is held.
+//!
+//! The edge of the grid that is lowest (gravity::Direction, from the accelerometer the main loop
+//! already reads every frame) tells which way up the MB2 is being held. Once the same edge has
+//! been lowest for ORIENTATION_FRAMES frames the rendered frame is rotated in 90 degree steps to
+//! bring the board's bottom row to that edge. Lying flat keeps the last orientation, so setting
+//! the MB2 down on a table does not spin the board.
+
+use crate::fade::Levels;
+use crate::gravity::Direction;
+use crate::{COL_COUNT, ROW_COUNT};
+
+/// Frames the same edge has to stay lowest before the board is turned to it, so that passing
+/// tilts (e.g. while playing the gravity mode) do not spin the board
+pub const ORIENTATION_FRAMES: u8 = 5;
+
+/// Which edge of the grid the board's bottom row is drawn on
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Orientation {
+    /// The bottom row, as the MB2 is normally held
+    Upright,
+    /// The left column: the board is turned 90 degrees clockwise
+    Left,
+    /// The right column: the board is turned 90 degrees counterclockwise
+    Right,
+    /// The top row: the board is turned 180 degrees
+    UpsideDown,
+}
+
+impl Orientation {
+    /// The orientation that puts the bottom row on the lowest edge `down`, if any.
+    fn from_down(down: Direction) -> Option<Self> {
+        match down {
+            Direction::Down => Some(Orientation::Upright),
+            Direction::Left => Some(Orientation::Left),
+            Direction::Right => Some(Orientation::Right),
+            Direction::Up => Some(Orientation::UpsideDown),
+            Direction::None => None,
+        }
+    }
+
+    /// Where the cell at (row, col) of the board is drawn on the grid.
+    fn place(self, row: usize, col: usize) -> (usize, usize) {
+        match self {
+            Orientation::Upright => (row, col),
+            Orientation::Left => (col, COL_COUNT - 1 - row),
+            Orientation::Right => (ROW_COUNT - 1 - col, row),
+            Orientation::UpsideDown => (ROW_COUNT - 1 - row, COL_COUNT - 1 - col),
+        }
+    }
+
+    /// Turn a direction on the grid into the same direction on the board as it is drawn, so that
+    /// e.g. the gravity mode's cells fall towards whichever edge is down on screen.
+    pub fn to_board(self, direction: Direction) -> Direction {
+        use Direction::*;
+        match (self, direction) {
+            (_, None) | (Orientation::Upright, _) => direction,
+            (Orientation::Left, Left) => Down,
+            (Orientation::Left, Down) => Right,
+            (Orientation::Left, Right) => Up,
+            (Orientation::Left, Up) => Left,
+            (Orientation::Right, Right) => Down,
+            (Orientation::Right, Down) => Left,
+            (Orientation::Right, Left) => Up,
+            (Orientation::Right, Up) => Right,
+            (Orientation::UpsideDown, Up) => Down,
+            (Orientation::UpsideDown, Down) => Up,
+            (Orientation::UpsideDown, Left) => Right,
+            (Orientation::UpsideDown, Right) => Left,
+        }
+    }
+
+    /// Draw `levels` in this orientation.
+    pub fn rotate(self, levels: &Levels) -> Levels {
+        let mut rotated: Levels = [[0; COL_COUNT]; ROW_COUNT];
+        for (row, line) in levels.iter().enumerate() {
+            for (col, level) in line.iter().enumerate() {
+                let (to_row, to_col) = self.place(row, col);
+                rotated[to_row][to_col] = *level;
+            }
+        }
+        rotated
+    }
+}
+
+/// Tracks which way up the MB2 is held
+pub struct OrientationDetector {
+    orientation: Orientation,
+    candidate: Orientation,
+    frames: u8,
+}
+
+impl OrientationDetector {
+    /// Start upright.
+    pub const fn new() -> Self {
+        OrientationDetector {
+            orientation: Orientation::Upright,
+            candidate: Orientation::Upright,
+            frames: 0,
+        }
+    }
+
+    /// Feed one frame's lowest edge and return the orientation to draw in.
+    pub fn update(&mut self, down: Direction) -> Orientation {
+        match Orientation::from_down(down) {
+            Some(candidate) if candidate == self.orientation => self.frames = 0,
+            Some(candidate) if candidate == self.candidate => {
+                self.frames += 1;
+                if self.frames >= ORIENTATION_FRAMES {
+                    self.orientation = candidate;
+                    self.frames = 0;
+                }
+            }
+            Some(candidate) => {
+                self.candidate = candidate;
+                self.frames = 1;
+            }
+            // lying flat
+            None => self.frames = 0,
+        }
+        self.orientation
+    }
+}
+
+impl Default for OrientationDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,83 @@
+//! Pacing the simulation independently of the frame rate.
+//!
+//! The main loop runs a frame every REFRESH_RATE_MS, reading the btns and drawing the board, and
+//! the display refreshes itself far faster than that in the background. How often the board steps
+//! is set separately as a step interval: `Pace` collects each frame's time and hands out the steps
+//! that have fallen due, none on most frames of a slow-motion interval longer than a frame and
+//! several per frame when fast-forwarding with one shorter than a frame.
+
+/// Hands out steps once per step interval, measured in frames of known length
+pub struct Pace {
+    step_ms: u32,
+    /// Time collected towards the next step
+    owed_ms: u32,
+}
+
+impl Pace {
+    /// Step every `step_ms` (at least 1ms).
+    pub const fn new(step_ms: u32) -> Self {
+        Pace {
+            step_ms: if step_ms == 0 { 1 } else { step_ms },
+            owed_ms: 0,
+        }
+    }
+
+    /// The time between steps, in milliseconds.
+    pub fn step_ms(&self) -> u32 {
+        self.step_ms
+    }
+
+    /// Step every `step_ms` (at least 1ms) from now on.
+    pub fn set_step_ms(&mut self, step_ms: u32) {
+        self.step_ms = step_ms.max(1);
+        self.owed_ms = self.owed_ms.min(self.step_ms);
+    }
+
+    /// Count a frame of `frame_ms` and return the number of steps now due, at most `max`. Steps
+    /// past `max` are dropped rather than owed, so a board that cannot keep up slows down instead
+    /// of falling ever further behind.
+    pub fn tick(&mut self, frame_ms: u32, max: u32) -> u32 {
+        self.owed_ms += frame_ms;
+        let due = self.owed_ms / self.step_ms;
+        self.owed_ms %= self.step_ms;
+        due.min(max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_step_per_frame_at_the_frame_rate() {
+        let mut pace = Pace::new(100);
+        for _ in 0..5 {
+            assert_eq!(pace.tick(100, 10), 1);
+        }
+    }
+
+    #[test]
+    fn slow_motion_steps_every_few_frames() {
+        let mut pace = Pace::new(250);
+        let steps: [u32; 5] = core::array::from_fn(|_| pace.tick(100, 10));
+        assert_eq!(steps, [0, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn fast_forward_is_capped_and_not_owed() {
+        let mut pace = Pace::new(10);
+        assert_eq!(pace.tick(100, 4), 4);
+        // the 6 steps over the cap were dropped, not carried into the next frame
+        assert_eq!(pace.tick(100, 20), 10);
+    }
+
+    #[test]
+    fn a_shorter_interval_does_not_release_the_time_owed_at_once() {
+        let mut pace = Pace::new(1_000);
+        assert_eq!(pace.tick(900, 10), 0);
+        pace.set_step_ms(100);
+        assert_eq!(pace.step_ms(), 100);
+        assert_eq!(pace.tick(0, 10), 1);
+        assert_eq!(Pace::new(0).step_ms(), 1);
+    }
+}
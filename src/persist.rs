@@ -0,0 +1,177 @@
+//! Saving a board to non-volatile flash so it survives a power cycle.
+//!
+//! A saved board occupies the first two words of a reserved flash page: a magic/version word
+//! followed by the board packed into 25 bits (see `pack_board`). Flash bits can only be cleared by
+//! a write, so the page is erased (set to all ones) before every save. An erased page, a foreign
+//! magic word, or bits set above the 25th all read back as no saved board.
+//!
+//! The page logic is written against the small `FlashPage` trait so that it can be exercised off
+//! target; only the `Nvmc` implementation touches the nRF52833 flash controller.
+
+/// Identifies a page holding a saved board ("GOL" followed by the format version, 1)
+const MAGIC: u32 = 0x474F_4C01;
+/// Number of LEDs (and so meaningful bits) in a packed board
+const LED_COUNT: usize = crate::ROW_COUNT * crate::COL_COUNT;
+
+/// Word offsets within the page
+const MAGIC_WORD: usize = 0;
+const BOARD_WORD: usize = 1;
+
+/// A single erasable page of word-writable flash.
+pub trait FlashPage {
+    /// Read the word at `offset` (in words) from the start of the page.
+    fn read_word(&self, offset: usize) -> u32;
+    /// Reset every bit of the page to 1.
+    fn erase(&mut self);
+    /// Write `word` at `offset` (in words), which only clears bits.
+    fn write_word(&mut self, offset: usize, word: u32);
+}
+
+/// Erase `page` and store the packed board `bits` on it.
+pub fn save_board(page: &mut impl FlashPage, bits: u32) {
+    page.erase();
+    page.write_word(BOARD_WORD, bits);
+    // written last so a save interrupted by power loss never looks valid
+    page.write_word(MAGIC_WORD, MAGIC);
+}
+
+/// Return the packed board stored on `page`, or `None` if it is blank or invalid.
+pub fn load_board(page: &impl FlashPage) -> Option<u32> {
+    decode(page.read_word(MAGIC_WORD), page.read_word(BOARD_WORD))
+}
+
+/// Validate the magic word and the packed board read back from flash.
+fn decode(magic: u32, bits: u32) -> Option<u32> {
+    if magic != MAGIC || bits >> LED_COUNT != 0 {
+        return None;
+    }
+    Some(bits)
+}
+
+/// The last 4KB page of the nRF52833's 512KB of flash, which memory.x leaves out of the FLASH
+/// region so the program image is never linked into it
+#[cfg(target_os = "none")]
+const PAGE_ADDR: usize = 0x0007_F000;
+
+/// The reserved flash page, written through the NVMC peripheral.
+#[cfg(target_os = "none")]
+pub struct Nvmc {
+    nvmc: microbit::pac::NVMC,
+}
+
+#[cfg(target_os = "none")]
+impl Nvmc {
+    /// Take ownership of the NVMC peripheral.
+    pub fn new(nvmc: microbit::pac::NVMC) -> Self {
+        Nvmc { nvmc }
+    }
+
+    /// Block until the NVMC has finished the last erase or write.
+    fn wait_ready(&self) {
+        while self.nvmc.ready.read().ready().bit_is_clear() {}
+    }
+}
+
+#[cfg(target_os = "none")]
+impl FlashPage for Nvmc {
+    fn read_word(&self, offset: usize) -> u32 {
+        // SAFETY: the page is mapped flash inside the nRF52833's address space and always readable
+        unsafe { core::ptr::read_volatile((PAGE_ADDR as *const u32).add(offset)) }
+    }
+
+    fn erase(&mut self) {
+        self.nvmc.config.write(|w| w.wen().een());
+        // SAFETY: PAGE_ADDR is the start of a flash page not used by the program image
+        self.nvmc
+            .erasepage()
+            .write(|w| unsafe { w.bits(PAGE_ADDR as u32) });
+        self.wait_ready();
+        self.nvmc.config.write(|w| w.wen().ren());
+    }
+
+    fn write_word(&mut self, offset: usize, word: u32) {
+        self.nvmc.config.write(|w| w.wen().wen());
+        // SAFETY: writes are enabled and the target word lies inside the reserved page
+        unsafe { core::ptr::write_volatile((PAGE_ADDR as *mut u32).add(offset), word) };
+        self.wait_ready();
+        self.nvmc.config.write(|w| w.wen().ren());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A page of RAM that behaves like flash: erasing sets every bit and writing only clears bits
+    struct MockPage([u32; 1024]);
+
+    impl MockPage {
+        fn erased() -> Self {
+            MockPage([u32::MAX; 1024])
+        }
+    }
+
+    impl FlashPage for MockPage {
+        fn read_word(&self, offset: usize) -> u32 {
+            self.0[offset]
+        }
+
+        fn erase(&mut self) {
+            self.0 = [u32::MAX; 1024];
+        }
+
+        fn write_word(&mut self, offset: usize, word: u32) {
+            self.0[offset] &= word;
+        }
+    }
+
+    #[test]
+    fn a_saved_board_loads_back() {
+        let mut page = MockPage::erased();
+        save_board(&mut page, 0x0155_A5C3);
+        assert_eq!(load_board(&page), Some(0x0155_A5C3));
+        assert_eq!(page.read_word(MAGIC_WORD), MAGIC);
+    }
+
+    #[test]
+    fn saving_erases_the_previous_board() {
+        // without the erase, writing 0 bits over the first board's 1 bits would merge the two
+        let mut page = MockPage::erased();
+        save_board(&mut page, 0x0000_001F);
+        save_board(&mut page, 0x01F0_0000);
+        assert_eq!(load_board(&page), Some(0x01F0_0000));
+        save_board(&mut page, 0);
+        assert_eq!(load_board(&page), Some(0));
+    }
+
+    #[test]
+    fn a_blank_page_holds_no_board() {
+        assert_eq!(load_board(&MockPage::erased()), None);
+    }
+
+    #[test]
+    fn a_foreign_magic_word_holds_no_board() {
+        let mut page = MockPage::erased();
+        page.write_word(BOARD_WORD, 0x0000_0007);
+        page.write_word(MAGIC_WORD, MAGIC + 1);
+        assert_eq!(load_board(&page), None);
+    }
+
+    #[test]
+    fn bits_above_the_board_hold_no_board() {
+        let mut page = MockPage::erased();
+        page.write_word(BOARD_WORD, 1 << LED_COUNT);
+        page.write_word(MAGIC_WORD, MAGIC);
+        assert_eq!(load_board(&page), None);
+    }
+
+    #[test]
+    fn an_interrupted_save_holds_no_board() {
+        // power lost over a saved board after the erase and the board word, before the magic word
+        let mut page = MockPage::erased();
+        save_board(&mut page, 0x0000_001F);
+        page.erase();
+        page.write_word(BOARD_WORD, 0x0000_0E00);
+        assert_eq!(load_board(&page), None);
+    }
+}
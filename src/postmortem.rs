@@ -0,0 +1,58 @@
+//! Post-mortem debugging: the panic handler dumps the last board over RTT.
+//!
+//! Every frame the main loop records the displayed board (packed into 25 bits with
+//! `pack_board`) and the generation count in two atomics. If the program panics, the handler
+//! prints the panic message as before followed by the recorded board in the same `#`/`.`
+//! layout used by the `rtt-log` feature, so the configuration that preceded the crash can be
+//! loaded straight back onto the MB2 over RTT.
+
+use core::fmt::Write;
+use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicU32, Ordering, compiler_fence};
+
+use rtt_target::{ChannelMode, with_terminal_channel};
+
+use crate::{COL_COUNT, LEDState, ROW_COUNT, live_cells, pack_board, unpack_board};
+
+/// The most recently displayed board, packed
+static LAST_BOARD: AtomicU32 = AtomicU32::new(0);
+/// The generation count of the most recently displayed board
+static LAST_GENERATION: AtomicU32 = AtomicU32::new(0);
+
+/// Record the board about to be displayed and its generation count.
+pub fn record(state: &LEDState, generation: u32) {
+    LAST_BOARD.store(pack_board(state), Ordering::Relaxed);
+    LAST_GENERATION.store(generation, Ordering::Relaxed);
+}
+
+/// Print the panic message and the last recorded board over RTT, then halt.
+///
+/// Interrupts are disabled and the terminal channel is switched to blocking so the whole dump
+/// reaches the host before the processor spins forever.
+#[inline(never)]
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    cortex_m::interrupt::disable();
+
+    let state = unpack_board(LAST_BOARD.load(Ordering::Relaxed));
+    let generation = LAST_GENERATION.load(Ordering::Relaxed);
+
+    let mut line = [b'.'; ROW_COUNT * COL_COUNT];
+    for (row, col) in live_cells(&state) {
+        line[row * COL_COUNT + col] = b'#';
+    }
+    // line only ever contains ASCII so this conversion cannot fail
+    let line = core::str::from_utf8(&line).unwrap_or_default();
+
+    with_terminal_channel(|term| {
+        term.set_mode(ChannelMode::BlockIfFull);
+        let mut channel = term.write(0);
+
+        writeln!(channel, "{}", info).ok();
+        writeln!(channel, "last board: {} generation {}", line, generation).ok();
+    });
+
+    loop {
+        compiler_fence(Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,112 @@
+//! A potentiometer on the edge connector as a speed knob.
+//!
+//! The potentiometer's ends are wired to the 3V and GND rings and its wiper to ring 0 (P0.02,
+//! AIN0), so the wiper reads anywhere from 0V to the supply. It is read once per frame with the
+//! SAADC against a quarter of the supply, so a full turn spans the whole 10-bit range whatever the
+//! battery voltage. The readings are smoothed by an exponential moving average, and the step
+//! interval they map to is only handed out once the knob has turned by more than HYSTERESIS, so
+//! a noisy wiper neither jitters the speed nor overrides one set in the settings menu or over RTT
+//! until the knob is actually turned. The knob maps onto the intervals quadratically, giving the
+//! fast end as fine a control as the slow one.
+//!
+//! The SAADC is shared with the light sensor and the microphone (light.rs, sound.rs), so each
+//! reading sets up the channel it needs and leaves the SAADC disabled.
+
+use core::sync::atomic::{Ordering, compiler_fence};
+
+use microbit::hal::gpio::{Disconnected, p0};
+use microbit::pac::SAADC;
+
+/// Step interval with the knob turned fully down (towards GND)
+const MIN_STEP_MS: u32 = 50;
+/// Step interval with the knob turned fully up (towards 3V)
+const MAX_STEP_MS: u32 = 2_000;
+/// Highest 10-bit reading
+const FULL_SCALE: u32 = 1_023;
+/// Weight of a new reading in the moving average, as 1 / 2^SMOOTHING_SHIFT
+const SMOOTHING_SHIFT: u32 = 2;
+/// How far (in 10-bit counts) the smoothed reading has to move before the step interval follows
+const HYSTERESIS: u32 = 12;
+
+/// The step interval for a (smoothed) `reading` of 0 to FULL_SCALE.
+fn step_ms(reading: u32) -> u32 {
+    let reading = reading.min(FULL_SCALE);
+    MIN_STEP_MS + (MAX_STEP_MS - MIN_STEP_MS) * reading * reading / (FULL_SCALE * FULL_SCALE)
+}
+
+/// The speed knob
+pub struct Potentiometer {
+    /// Owned so that nothing else configures ring 0
+    _wiper: p0::P0_02<Disconnected>,
+    /// Moving average of the readings, scaled by 2^SMOOTHING_SHIFT, or None before the first
+    average: Option<u32>,
+    /// The smoothed reading the step interval was last handed out for
+    applied: Option<u32>,
+}
+
+impl Potentiometer {
+    /// Take over ring 0 for the wiper.
+    pub fn new(wiper: p0::P0_02<Disconnected>) -> Self {
+        Potentiometer {
+            _wiper: wiper,
+            average: None,
+            applied: None,
+        }
+    }
+
+    /// Read the knob, returning the step interval it selects if it has turned since the last one
+    /// was returned (and on the first reading, so the knob sets the speed from power-up).
+    pub fn read(&mut self, saadc: &SAADC) -> Option<u32> {
+        let reading = Self::sample(saadc);
+        let average = match self.average {
+            None => reading << SMOOTHING_SHIFT,
+            Some(average) => average - (average >> SMOOTHING_SHIFT) + reading,
+        };
+        self.average = Some(average);
+
+        let smoothed = average >> SMOOTHING_SHIFT;
+        match self.applied {
+            Some(applied) if applied.abs_diff(smoothed) <= HYSTERESIS => None,
+            _ => {
+                self.applied = Some(smoothed);
+                Some(step_ms(smoothed))
+            }
+        }
+    }
+
+    /// Read the wiper once.
+    fn sample(saadc: &SAADC) -> u32 {
+        let mut result = 0i16;
+        saadc.resolution.write(|w| w.val()._10bit());
+        for channel in saadc.ch.iter() {
+            channel.pselp.write(|w| w.pselp().nc());
+        }
+        saadc.ch[0].config.write(|w| {
+            w.resp().bypass().resn().bypass();
+            w.gain().gain1_4().refsel().vdd1_4();
+            w.tacq()._10us().mode().se()
+        });
+        saadc.ch[0].pselp.write(|w| w.pselp().analog_input0());
+
+        saadc.enable.write(|w| w.enable().enabled());
+        saadc
+            .result
+            .ptr
+            .write(|w| unsafe { w.ptr().bits(&mut result as *mut i16 as u32) });
+        saadc.result.maxcnt.write(|w| unsafe { w.maxcnt().bits(1) });
+        saadc.events_end.reset();
+        saadc.events_started.reset();
+        saadc.tasks_start.write(|w| unsafe { w.bits(1) });
+        while saadc.events_started.read().bits() == 0 {}
+        saadc.tasks_sample.write(|w| unsafe { w.bits(1) });
+        while saadc.events_end.read().bits() == 0 {}
+        saadc.events_end.reset();
+        // the result was written by EasyDMA behind the compiler's back
+        compiler_fence(Ordering::SeqCst);
+        saadc.ch[0].pselp.write(|w| w.pselp().nc());
+        saadc.enable.write(|w| w.enable().disabled());
+
+        // negative results are noise around 0V
+        result.max(0).unsigned_abs() as u32
+    }
+}
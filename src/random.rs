@@ -0,0 +1,109 @@
+//! A small deterministic pseudo random number generator.
+//!
+//! The hardware RNG is slow (each u32 takes several bias-corrected bytes from the RNG peripheral),
+//! so when many random values are needed at once a single hardware draw is used to seed this
+//! xorshift32 generator instead. The generator can also be seeded from the onboard temperature
+//! sensor, for starts that are reproducible but depend on the environment.
+
+/// Anything that can produce random u32 values. Implemented by the MB2 hardware RNG for normal
+/// play and by XorShift32 so that the game can also be driven from a reproducible seed.
+pub trait RandomSource {
+    /// Return the next random u32.
+    fn random_u32(&mut self) -> u32;
+}
+
+#[cfg(target_os = "none")]
+impl RandomSource for microbit::hal::Rng {
+    fn random_u32(&mut self) -> u32 {
+        microbit::hal::Rng::random_u32(self)
+    }
+}
+
+impl RandomSource for XorShift32 {
+    fn random_u32(&mut self) -> u32 {
+        self.next_u32()
+    }
+}
+
+/// Fold a temperature reading in the TEMP peripheral's raw units (0.25 degC steps) into a seed.
+///
+/// The reading is scrambled with the murmur3 finalizer so that neighbouring temperatures seed
+/// unrelated boards, while the same temperature always gives the same seed.
+pub fn mix_temp_seed(quarter_degrees: i32) -> u32 {
+    let mut seed = (quarter_degrees as u32) ^ 0x9E37_79B9;
+    seed ^= seed >> 16;
+    seed = seed.wrapping_mul(0x85EB_CA6B);
+    seed ^= seed >> 13;
+    seed = seed.wrapping_mul(0xC2B2_AE35);
+    seed ^= seed >> 16;
+    seed
+}
+
+/// Take one reading of the nRF52833's internal temperature sensor and fold it into a seed for
+/// XorShift32 (see `mix_temp_seed`).
+#[cfg(all(target_os = "none", feature = "temp-seed"))]
+pub fn read_temp_seed(temp: &mut microbit::hal::Temp) -> u32 {
+    mix_temp_seed(temp.measure().to_bits())
+}
+
+/// Marsaglia's xorshift32 generator (period 2^32 - 1).
+pub struct XorShift32 {
+    state: u32,
+}
+
+impl XorShift32 {
+    /// Create a generator from `seed`. xorshift32 never leaves the all-zero state, so a seed
+    /// of 0 is replaced by 1.
+    pub const fn new(seed: u32) -> Self {
+        XorShift32 {
+            state: if seed == 0 { 1 } else { seed },
+        }
+    }
+
+    /// Return the next pseudo random u32.
+    pub fn next_u32(&mut self) -> u32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_reading_always_gives_the_same_seed() {
+        for reading in [-160, -1, 0, 1, 100, 340] {
+            assert_eq!(mix_temp_seed(reading), mix_temp_seed(reading));
+        }
+        // pinned so a change to the mixing shows up here (100 is 25 degC)
+        assert_eq!(mix_temp_seed(100), 0xF7EC_AD0E);
+    }
+
+    #[test]
+    fn neighbouring_readings_give_unrelated_seeds() {
+        for reading in -200..400 {
+            let (seed, next) = (mix_temp_seed(reading), mix_temp_seed(reading + 1));
+            assert_ne!(seed, next, "{reading}");
+            // more than a handful of bits differ
+            assert!((seed ^ next).count_ones() >= 4, "{reading}");
+        }
+    }
+
+    #[test]
+    fn a_reading_of_0_seeds_a_working_generator() {
+        let seed = mix_temp_seed(0);
+        assert_ne!(seed, 0);
+        let mut rng = XorShift32::new(seed);
+        let draws: Vec<u32> = (0..8).map(|_| rng.next_u32()).collect();
+        assert!(draws.iter().all(|&draw| draw != 0));
+        assert!(draws.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn a_zero_seed_is_replaced() {
+        assert_eq!(XorShift32::new(0).next_u32(), XorShift32::new(1).next_u32());
+    }
+}
@@ -0,0 +1,157 @@
+//! Recording of a run as a stream of frames over RTT, for making GIFs or analyzing runs on the
+//! host. Enabled with the `record` cargo feature.
+//!
+//! The stream is plain text, one record per line:
+//! 1. `grid 5x5` (rows x cols) once at power-up
+//! 2. `run` before the first board of every run, i.e. whenever the board is restarted by
+//!    randomizing, complementing, loading, or the dead-board restart, so a host script can
+//!    split the stream into runs
+//! 3. `<generation> <board>` for each new board, where board is the 25-bit packing of
+//!    `pack_board` as 7 lowercase hex digits, e.g. `12 0000e00`
+//!
+//! A board that does not change from one frame to the next (e.g. while paused in single-step
+//! mode) is only recorded once.
+
+use crate::{COL_COUNT, ROW_COUNT};
+
+/// Longest frame line: a u32 generation (10 digits), a space, and 7 hex digits
+const FRAME_LINE_CAPACITY: usize = 18;
+/// Hex digits needed for the ROW_COUNT * COL_COUNT bits of a packed board
+const BOARD_DIGITS: usize = (ROW_COUNT * COL_COUNT).div_ceil(4);
+
+/// One formatted `<generation> <board>` line, without its newline.
+pub struct FrameLine {
+    buf: [u8; FRAME_LINE_CAPACITY],
+    len: usize,
+}
+
+impl FrameLine {
+    /// The line as text.
+    pub fn as_str(&self) -> &str {
+        // only ever holds ASCII digits and a space
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or_default()
+    }
+}
+
+/// Format the record for the board packed as `bits` at `generation`.
+pub fn format_frame(generation: u32, bits: u32) -> FrameLine {
+    let mut line = FrameLine {
+        buf: [0; FRAME_LINE_CAPACITY],
+        len: 0,
+    };
+
+    // decimal digits come out least significant first, so write them backwards and then shift
+    let mut digits = [0u8; 10];
+    let mut count = 0;
+    let mut rest = generation;
+    loop {
+        digits[count] = b'0' + (rest % 10) as u8;
+        count += 1;
+        rest /= 10;
+        if rest == 0 {
+            break;
+        }
+    }
+    for digit in digits[..count].iter().rev() {
+        line.buf[line.len] = *digit;
+        line.len += 1;
+    }
+
+    line.buf[line.len] = b' ';
+    line.len += 1;
+
+    for i in (0..BOARD_DIGITS).rev() {
+        let nibble = (bits >> (i * 4)) & 0xF;
+        line.buf[line.len] = b"0123456789abcdef"[nibble as usize];
+        line.len += 1;
+    }
+    line
+}
+
+/// Streams a run's frames over the RTT print channel.
+#[cfg(target_os = "none")]
+pub struct RunRecorder {
+    last: Option<(u32, u32)>,
+}
+
+#[cfg(target_os = "none")]
+impl RunRecorder {
+    /// Start recording, emitting the `grid` header line.
+    pub fn start() -> Self {
+        rtt_target::rprintln!("grid {}x{}", ROW_COUNT, COL_COUNT);
+        RunRecorder { last: None }
+    }
+
+    /// Record the board packed as `bits` at `generation` if it differs from the last frame,
+    /// preceded by a `run` marker if it starts a new run.
+    ///
+    /// A new run is detected by the generation count not having advanced: it only ever goes
+    /// up by one per step and goes back to 0 when the board is restarted.
+    pub fn record(&mut self, generation: u32, bits: u32) {
+        if self.last == Some((generation, bits)) {
+            return;
+        }
+        if self
+            .last
+            .is_none_or(|(last_generation, _)| generation <= last_generation)
+        {
+            rtt_target::rprintln!("run");
+        }
+        rtt_target::rprintln!("{}", format_frame(generation, bits).as_str());
+        self.last = Some((generation, bits));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LEDState, pack_board, unpack_board};
+
+    const EMPTY: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+
+    #[test]
+    fn frames_are_the_generation_and_the_board_in_hex() {
+        assert_eq!(format_frame(0, pack_board(&EMPTY)).as_str(), "0 0000000");
+
+        // a horizontal blinker in the middle of the grid is LEDs 11 to 13
+        let mut blinker = EMPTY;
+        blinker[2][1..4].fill(1);
+        assert_eq!(
+            format_frame(12, pack_board(&blinker)).as_str(),
+            "12 0003800"
+        );
+
+        // the top left LED is the lowest bit and the bottom right one the highest
+        let mut corners = EMPTY;
+        corners[0][0] = 1;
+        corners[ROW_COUNT - 1][COL_COUNT - 1] = 1;
+        assert_eq!(format_frame(7, pack_board(&corners)).as_str(), "7 1000001");
+    }
+
+    #[test]
+    fn the_longest_frame_fits_the_line() {
+        let full = [[1; COL_COUNT]; ROW_COUNT];
+        let line = format_frame(u32::MAX, pack_board(&full));
+        assert_eq!(line.as_str(), "4294967295 1ffffff");
+        assert_eq!(line.as_str().len(), FRAME_LINE_CAPACITY);
+    }
+
+    #[test]
+    fn a_host_can_parse_frames_back_into_boards() {
+        let mut glider = EMPTY;
+        crate::life::place_pattern(
+            &mut glider,
+            &crate::life::GLIDER,
+            (1, 1),
+            crate::life::OverflowMode::Clip,
+        );
+        let line = format_frame(305, pack_board(&glider));
+        let (generation, board) = line.as_str().split_once(' ').unwrap();
+        assert_eq!(generation.parse::<u32>(), Ok(305));
+        assert_eq!(board.len(), BOARD_DIGITS);
+        assert_eq!(
+            unpack_board(u32::from_str_radix(board, 16).unwrap()),
+            glider
+        );
+    }
+}
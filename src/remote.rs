@@ -0,0 +1,278 @@
+//! Commands pushed from the host over the RTT down channel, one per line.
+//!
+//! A board is sent as a single line in one of two formats:
+//! 1. 25 characters of `#` (lit) and `.` (unlit) in top-left to bottom-right order,
+//!    the same format written by the `rtt-log` feature
+//! 2. A hexadecimal `u32` (optionally prefixed with `0x`) whose right-most 25 bits use the
+//!    same bit-to-LED mapping as `randomize_state`
+//!
+//! A line `mutation=N` instead sets the mutation rate of the `mutation` feature to N permille
+//! (0 to 1000), and a line `brightness=N` sets the display brightness setting to N (1 to
+//! render::BRIGHTNESS_LEVELS), and a line `step_ms=N` sets the time between generations to N
+//! milliseconds (10 to 10000), and a line `rule=B.../S...` selects the evolving mode starting
+//! from the given Life-like rule (see life::Rule::from_bs).
+//!
+//! Anything else (too short, too long, invalid characters, bits set above the 25th, a rate above
+//! 1000, a brightness outside 1 to BRIGHTNESS_LEVELS, a step interval outside 10 to
+//! 10000, a malformed rule) is ignored.
+
+use crate::life::Rule;
+use crate::render::BRIGHTNESS_LEVELS;
+use crate::{COL_COUNT, LEDState, ROW_COUNT, unpack_board};
+
+/// Number of LEDs (and so characters) in a `#`/`.` board line
+const LED_COUNT: usize = ROW_COUNT * COL_COUNT;
+/// Longest line that will be buffered; anything longer is discarded up to the next newline
+const LINE_CAPACITY: usize = 32;
+/// Prefix of a line setting the mutation rate
+const MUTATION_PREFIX: &str = "mutation=";
+/// Prefix of a line setting the display brightness
+const BRIGHTNESS_PREFIX: &str = "brightness=";
+/// Prefix of a line setting the time between generations
+const STEP_INTERVAL_PREFIX: &str = "step_ms=";
+/// Prefix of a line setting the evolving mode's rule
+const RULE_PREFIX: &str = "rule=";
+/// Shortest and longest step interval accepted, in milliseconds
+const STEP_INTERVAL_RANGE: core::ops::RangeInclusive<u32> = 10..=10_000;
+
+/// A parsed line from the host
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Command {
+    /// Replace the board
+    Board(LEDState),
+    /// Set the per-LED mutation rate, in permille
+    MutationRate(u16),
+    /// Set the display brightness setting, 1..=BRIGHTNESS_LEVELS
+    Brightness(u8),
+    /// Set the time between generations, in milliseconds
+    StepInterval(u32),
+    /// Run the evolving mode from this rule
+    Rule(Rule),
+}
+
+/// Parse a single line (without its newline) into a command, returning `None` if it is malformed.
+pub fn parse_line(bytes: &[u8]) -> Option<Command> {
+    let line = core::str::from_utf8(bytes).ok()?.trim();
+
+    if let Some(rate) = line.strip_prefix(MUTATION_PREFIX) {
+        let rate = rate.parse::<u16>().ok()?;
+        return (rate <= 1_000).then_some(Command::MutationRate(rate));
+    }
+    if let Some(setting) = line.strip_prefix(BRIGHTNESS_PREFIX) {
+        let setting = setting.parse::<u8>().ok()?;
+        return (1..=BRIGHTNESS_LEVELS)
+            .contains(&setting)
+            .then_some(Command::Brightness(setting));
+    }
+    if let Some(step_ms) = line.strip_prefix(STEP_INTERVAL_PREFIX) {
+        let step_ms = step_ms.parse::<u32>().ok()?;
+        return STEP_INTERVAL_RANGE
+            .contains(&step_ms)
+            .then_some(Command::StepInterval(step_ms));
+    }
+    if let Some(rule) = line.strip_prefix(RULE_PREFIX) {
+        return Rule::from_bs(rule).map(Command::Rule);
+    }
+    parse_board(bytes).map(Command::Board)
+}
+
+/// Parse a single line (without its newline) into a board, returning `None` if it is malformed.
+pub fn parse_board(bytes: &[u8]) -> Option<LEDState> {
+    let line = core::str::from_utf8(bytes).ok()?.trim();
+
+    if line.len() == LED_COUNT && line.bytes().all(|b| b == b'#' || b == b'.') {
+        let mut state: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+        for (i, b) in line.bytes().enumerate() {
+            state[i / COL_COUNT][i % COL_COUNT] = (b == b'#') as u8;
+        }
+        return Some(state);
+    }
+
+    let digits = line
+        .strip_prefix("0x")
+        .or_else(|| line.strip_prefix("0X"))
+        .unwrap_or(line);
+    if digits.is_empty() || digits.len() > 8 {
+        return None;
+    }
+
+    let bits = u32::from_str_radix(digits, 16).ok()?;
+    if bits >> LED_COUNT != 0 {
+        return None;
+    }
+
+    Some(unpack_board(bits))
+}
+
+/// Accumulates bytes arriving from the host into lines and parses each completed line.
+///
+/// Partial lines are held across frames until their newline arrives. Lines that overflow the
+/// buffer are dropped in their entirety rather than parsed from a truncated prefix.
+pub struct LineReader {
+    buf: [u8; LINE_CAPACITY],
+    len: usize,
+    overflowed: bool,
+}
+
+impl LineReader {
+    /// Create an empty reader.
+    pub const fn new() -> Self {
+        LineReader {
+            buf: [0; LINE_CAPACITY],
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Feed one byte, returning a command when it completes a valid line.
+    pub fn push(&mut self, byte: u8) -> Option<Command> {
+        if byte == b'\n' {
+            let parsed = if self.overflowed {
+                None
+            } else {
+                parse_line(&self.buf[..self.len])
+            };
+            self.len = 0;
+            self.overflowed = false;
+            return parsed;
+        }
+
+        if self.len < LINE_CAPACITY {
+            self.buf[self.len] = byte;
+            self.len += 1;
+        } else {
+            self.overflowed = true;
+        }
+        None
+    }
+
+    /// Drain everything currently waiting on `channel`, passing each valid command received to
+    /// `handle` in the order it arrived.
+    #[cfg(target_os = "none")]
+    pub fn poll(&mut self, channel: &mut rtt_target::DownChannel, mut handle: impl FnMut(Command)) {
+        let mut chunk = [0u8; LINE_CAPACITY];
+
+        loop {
+            let count = channel.read(&mut chunk);
+            if count == 0 {
+                return;
+            }
+            for byte in &chunk[..count] {
+                if let Some(command) = self.push(*byte) {
+                    handle(command);
+                }
+            }
+        }
+    }
+}
+
+impl Default for LineReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn character_boards_parse_in_row_major_order() {
+        assert_eq!(
+            parse_board(b"#...#.#.#...#...#.#.#...#"),
+            Some([
+                [1, 0, 0, 0, 1],
+                [0, 1, 0, 1, 0],
+                [0, 0, 1, 0, 0],
+                [0, 1, 0, 1, 0],
+                [1, 0, 0, 0, 1],
+            ])
+        );
+        // surrounding whitespace (such as a carriage return) is ignored
+        assert_eq!(
+            parse_board(b" ......................... \r"),
+            Some([[0; COL_COUNT]; ROW_COUNT])
+        );
+    }
+
+    #[test]
+    fn hex_boards_use_the_unpack_mapping() {
+        assert_eq!(parse_board(b"0x0001C00"), Some(unpack_board(0x1C00)));
+        assert_eq!(parse_board(b"1c00"), Some(unpack_board(0x1C00)));
+        assert_eq!(parse_board(b"0X1FFFFFF"), Some([[1; COL_COUNT]; ROW_COUNT]));
+    }
+
+    #[test]
+    fn too_short_or_too_long_boards_are_ignored() {
+        assert_eq!(parse_board(b"#...#.#.#...#...#.#.#..."), None);
+        assert_eq!(parse_board(b"#...#.#.#...#...#.#.#...##"), None);
+        assert_eq!(parse_board(b""), None);
+        assert_eq!(parse_board(b"0x"), None);
+        assert_eq!(parse_board(b"0x000000001"), None);
+    }
+
+    #[test]
+    fn boards_with_invalid_characters_are_ignored() {
+        assert_eq!(parse_board(b"#...#.#.#...#..x#.#.#...#"), None);
+        assert_eq!(parse_board(b"#...#.#.#...#.. #.#.#...#"), None);
+        assert_eq!(parse_board(b"0x00g1C00"), None);
+        assert_eq!(parse_board(&[b'#', 0xFF, b'.']), None);
+        // the 26th bit has no LED
+        assert_eq!(parse_board(b"0x2000000"), None);
+    }
+
+    #[test]
+    fn settings_lines_parse_within_their_ranges() {
+        assert_eq!(parse_line(b"mutation=25"), Some(Command::MutationRate(25)));
+        assert_eq!(
+            parse_line(b"mutation=1000"),
+            Some(Command::MutationRate(1_000))
+        );
+        assert_eq!(parse_line(b"mutation=1001"), None);
+        assert_eq!(parse_line(b"brightness=1"), Some(Command::Brightness(1)));
+        assert_eq!(parse_line(b"brightness=0"), None);
+        assert_eq!(parse_line(b"brightness=6"), None);
+        assert_eq!(parse_line(b"step_ms=10"), Some(Command::StepInterval(10)));
+        assert_eq!(
+            parse_line(b"step_ms=10000\r"),
+            Some(Command::StepInterval(10_000))
+        );
+        assert_eq!(parse_line(b"step_ms=9"), None);
+        assert_eq!(parse_line(b"step_ms=fast"), None);
+    }
+
+    #[test]
+    fn rule_lines_parse_in_bs_notation() {
+        assert_eq!(
+            parse_line(b"rule=B36/S23"),
+            Some(Command::Rule(Rule::highlife()))
+        );
+        assert_eq!(parse_line(b"rule=b2/s"), Some(Command::Rule(Rule::seeds())));
+        assert_eq!(parse_line(b"rule=B9/S23"), None);
+        assert_eq!(parse_line(b"rule=conway"), None);
+    }
+
+    #[test]
+    fn anything_else_parses_as_a_board() {
+        assert_eq!(
+            parse_line(b"0x1f"),
+            Some(Command::Board(unpack_board(0x1F)))
+        );
+        assert_eq!(parse_line(b"speed=3"), None);
+        assert_eq!(parse_line(b""), None);
+    }
+
+    #[test]
+    fn line_reader_drops_overflowing_lines() {
+        let mut reader = LineReader::new();
+        let mut commands = Vec::new();
+        for byte in b"brightness=2\n"
+            .iter()
+            .chain(&[b'#'; 40])
+            .chain(b"\nbrightness=3\n")
+        {
+            commands.extend(reader.push(*byte));
+        }
+        assert_eq!(commands, [Command::Brightness(2), Command::Brightness(3)]);
+    }
+}
@@ -0,0 +1,186 @@
+//! Output backends for the board.
+//!
+//! The main loop draws each frame through the `Renderer` trait rather than the onboard LED
+//! matrix directly, so another display (e.g. the external 5x5 WS2812 panel of ws2812.rs) can be
+//! swapped in by implementing the trait. Rendering never blocks: a backend only
+//! latches the new image and keeps showing it until the next call.
+//!
+//! `Rotated` wraps any backend to draw everything turned to an orientation::Orientation, and
+//! `Dimmed` wraps any backend to scale everything drawn through it to a global brightness: the
+//! user's brightness setting (one of BRIGHTNESS_LEVELS), further turned down by the idle dimming
+//! (idle.rs) while nobody is using the MB2.
+//!
+//! The backends for the onboard LED matrix itself drive the MB2's peripherals, so they are part
+//! of the firmware rather than of this library (display.rs).
+
+use crate::LEDState;
+use crate::fade::{self, Levels};
+use crate::orientation::Orientation;
+
+/// How long one scan of the whole matrix takes on a backend that has to scan it (1ms per row)
+pub const SCAN_MS: u32 = 5;
+
+/// A display the board can be drawn on.
+pub trait Renderer {
+    /// Show per-cell brightness `levels` (0..=fade::MAX_BRIGHTNESS) until the next call.
+    ///
+    /// Backends without brightness control should light every non-zero level fully.
+    fn render_levels(&mut self, levels: &Levels);
+
+    /// Show `state` until the next call.
+    fn render(&mut self, state: &LEDState) {
+        self.render_levels(&fade::levels(state));
+    }
+
+    /// Keep the image lit for `ms` by scanning the matrix, returning `false` straight away (for
+    /// the caller to sleep or delay instead) if the backend refreshes itself in the background.
+    fn hold(&mut self, _ms: u32) -> bool {
+        false
+    }
+}
+
+/// Number of user brightness settings, 1 (dimmest) to BRIGHTNESS_LEVELS (full brightness)
+pub const BRIGHTNESS_LEVELS: u8 = 5;
+
+/// A backend whose output is turned to an orientation.
+pub struct Rotated<R> {
+    inner: R,
+    orientation: Orientation,
+}
+
+impl<R: Renderer> Rotated<R> {
+    /// Draw through `inner` upright.
+    pub fn new(inner: R) -> Self {
+        Rotated {
+            inner,
+            orientation: Orientation::Upright,
+        }
+    }
+
+    /// Turn everything drawn from now on to `orientation`.
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+    }
+}
+
+impl<R: Renderer> Renderer for Rotated<R> {
+    fn render_levels(&mut self, levels: &Levels) {
+        self.inner.render_levels(&self.orientation.rotate(levels));
+    }
+
+    fn hold(&mut self, ms: u32) -> bool {
+        self.inner.hold(ms)
+    }
+}
+
+/// A backend whose output is scaled to a global brightness.
+pub struct Dimmed<R> {
+    inner: R,
+    /// The user's setting, 1..=BRIGHTNESS_LEVELS
+    setting: u8,
+    /// The idle dimming's brightness, 0..=fade::MAX_BRIGHTNESS
+    brightness: u8,
+}
+
+impl<R: Renderer> Dimmed<R> {
+    /// Draw through `inner` at brightness setting `setting` (clamped to 1..=BRIGHTNESS_LEVELS).
+    pub fn new(inner: R, setting: u8) -> Self {
+        let mut dimmed = Dimmed {
+            inner,
+            setting: BRIGHTNESS_LEVELS,
+            brightness: fade::MAX_BRIGHTNESS,
+        };
+        dimmed.set_setting(setting);
+        dimmed
+    }
+
+    /// The wrapped backend.
+    pub fn inner_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Change the user's brightness setting (clamped to 1..=BRIGHTNESS_LEVELS).
+    pub fn set_setting(&mut self, setting: u8) {
+        self.setting = setting.clamp(1, BRIGHTNESS_LEVELS);
+    }
+
+    /// Scale everything drawn from now on by `brightness` / fade::MAX_BRIGHTNESS, or by the
+    /// user's setting if that is dimmer.
+    pub fn set_brightness(&mut self, brightness: u8) {
+        self.brightness = brightness.min(fade::MAX_BRIGHTNESS);
+    }
+}
+
+impl<R: Renderer> Renderer for Dimmed<R> {
+    fn render_levels(&mut self, levels: &Levels) {
+        let max = fade::MAX_BRIGHTNESS as u16;
+        let setting = (self.setting as u16 * max).div_ceil(BRIGHTNESS_LEVELS as u16);
+        let scale = setting.min(self.brightness as u16);
+        let mut dimmed = *levels;
+        for level in dimmed.iter_mut().flatten() {
+            // round up so that a dim cell never disappears entirely
+            *level = (*level as u16 * scale).div_ceil(max) as u8;
+        }
+        self.inner.render_levels(&dimmed);
+    }
+
+    fn hold(&mut self, ms: u32) -> bool {
+        self.inner.hold(ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Game;
+    use crate::random::XorShift32;
+    use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+    /// A backend that keeps every image drawn on it
+    #[derive(Default)]
+    struct Recorder(Vec<Levels>);
+
+    impl Renderer for Recorder {
+        fn render_levels(&mut self, levels: &Levels) {
+            self.0.push(*levels);
+        }
+    }
+
+    #[test]
+    fn every_frame_reaches_the_backend_unchanged_at_full_brightness() {
+        let mut game = Game::new(XorShift32::new(0x2545_F491));
+        let mut renderer = Dimmed::new(Rotated::new(Recorder::default()), BRIGHTNESS_LEVELS);
+        let mut boards = Vec::new();
+        for frame in 0..20 {
+            let board = *game.update(frame < 2, false);
+            renderer.render(&board);
+            boards.push(fade::levels(&board));
+        }
+        assert_eq!(renderer.inner_mut().inner.0, boards);
+    }
+
+    #[test]
+    fn a_board_lights_its_live_cells_fully() {
+        let mut board: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+        board[2][3] = 1;
+        let mut renderer = Recorder::default();
+        renderer.render(&board);
+        let mut expected = [[0; COL_COUNT]; ROW_COUNT];
+        expected[2][3] = fade::MAX_BRIGHTNESS;
+        assert_eq!(renderer.0, [expected]);
+    }
+
+    #[test]
+    fn dimming_rounds_up_so_no_lit_cell_goes_dark() {
+        let board: LEDState = [[1; COL_COUNT]; ROW_COUNT];
+        let mut renderer = Dimmed::new(Recorder::default(), 1);
+        renderer.render(&board);
+        renderer.set_setting(BRIGHTNESS_LEVELS);
+        renderer.set_brightness(1);
+        renderer.render(&board);
+        let drawn = &renderer.inner_mut().0;
+        // the dimmest setting scales 9 down to 2 (9 / 5, rounded up)
+        assert_eq!(drawn[0], [[2; COL_COUNT]; ROW_COUNT]);
+        assert_eq!(drawn[1], [[1; COL_COUNT]; ROW_COUNT]);
+    }
+}
@@ -0,0 +1,95 @@
+//! A ring buffer of recent boards for stepping backwards through a game.
+//!
+//! Each board is stored bit-packed by `pack_board` in a single u32, so the last N generations
+//! cost only 4N bytes. Once N boards are held, pushing another overwrites the oldest.
+
+/// The last N boards, packed, most recent last
+pub struct Rewind<const N: usize> {
+    boards: [u32; N],
+    start: usize,
+    len: usize,
+}
+
+impl<const N: usize> Rewind<N> {
+    /// Create an empty buffer.
+    pub const fn new() -> Self {
+        Rewind {
+            boards: [0; N],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// Record the packed board `bits` as the most recent, forgetting the oldest once N are held.
+    pub fn push(&mut self, bits: u32) {
+        if N == 0 {
+            return;
+        }
+        if self.len < N {
+            self.boards[(self.start + self.len) % N] = bits;
+            self.len += 1;
+        } else {
+            self.boards[self.start] = bits;
+            self.start = (self.start + 1) % N;
+        }
+    }
+
+    /// Remove and return the most recent packed board, or `None` once the buffer is empty.
+    pub fn pop(&mut self) -> Option<u32> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(self.boards[(self.start + self.len) % N])
+    }
+
+    /// Forget every board, e.g. when the game starts on a new one.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for Rewind<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boards_pop_most_recent_first() {
+        let mut rewind = Rewind::<4>::new();
+        rewind.push(1);
+        rewind.push(2);
+        rewind.push(3);
+        assert_eq!(rewind.pop(), Some(3));
+        assert_eq!(rewind.pop(), Some(2));
+        rewind.push(4);
+        assert_eq!(rewind.pop(), Some(4));
+        assert_eq!(rewind.pop(), Some(1));
+        assert_eq!(rewind.pop(), None);
+    }
+
+    #[test]
+    fn a_full_buffer_forgets_the_oldest() {
+        let mut rewind = Rewind::<3>::new();
+        for bits in 1..=5 {
+            rewind.push(bits);
+        }
+        assert_eq!(rewind.pop(), Some(5));
+        assert_eq!(rewind.pop(), Some(4));
+        assert_eq!(rewind.pop(), Some(3));
+        assert_eq!(rewind.pop(), None);
+    }
+
+    #[test]
+    fn clear_empties_the_buffer() {
+        let mut rewind = Rewind::<3>::new();
+        rewind.push(1);
+        rewind.clear();
+        assert_eq!(rewind.pop(), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! Hardware-timed row multiplexing of the LED matrix with TIMER4, PPI, and GPIOTE.
+//!
+//! The nonblocking display switches rows from its timer interrupt, so anything delaying that
+//! interrupt (a critical section, a higher priority handler) also stretches how long a row stays
+//! lit, which shows as flicker. Here the row pins are handed to GPIOTE tasks and switched by the
+//! timer through PPI instead: every slot of the scan lights its row exactly when TIMER4 wraps
+//! (COMPARE1, via PPI channel 0) and blanks it exactly its on-time later (COMPARE0, via PPI
+//! channel 1), whatever the CPU is doing. The interrupt raised by the blanking only prepares the
+//! next slot (its column pins and the two PPI task endpoints) while the matrix is dark, so a late
+//! interrupt can at worst show one slot with stale columns, never change how long one is lit.
+//!
+//! Brightness comes from binary code modulation: each row is shown for PLANES slots, the one for
+//! bit p of a cell's 4-bit code lasting UNIT_US << p, so a cell is lit for 0 to 15 units per scan.
+//!
+//! The blocking display (or the WS2812 panel) takes precedence if the `blocking-display` (or the
+//! `neopixel`) feature is enabled as well.
+#![cfg_attr(
+    any(feature = "blocking-display", feature = "neopixel"),
+    allow(dead_code)
+)]
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{Mutex, free};
+use embedded_hal::digital::{OutputPin, PinState};
+use microbit::gpio::DisplayPins;
+use microbit::hal::gpio::{Output, Pin, Port, PushPull};
+use microbit::pac::{self, GPIOTE, NVIC, PPI, TIMER4, interrupt};
+
+use crate::fade::{self, Levels};
+use crate::render::Renderer;
+use crate::{COL_COUNT, ROW_COUNT};
+
+/// NVIC priority of the slot preparation interrupt, the same level 2 as the nonblocking display's
+const SCAN_PRIORITY: u8 = 2 << 5;
+/// Bit planes per row, giving 16 brightness codes
+const PLANES: usize = 4;
+/// On-time of the least significant plane, in microseconds
+const UNIT_US: u32 = 40;
+/// Dark time after the longest plane, left for the interrupt to prepare the next slot in
+const BLANK_US: u32 = 20;
+/// Length of every slot: the longest plane plus the blank. A whole scan of ROW_COUNT * PLANES
+/// slots takes 6.8ms (~147Hz)
+const SLOT_US: u32 = (UNIT_US << (PLANES - 1)) + BLANK_US;
+/// Largest brightness code
+const MAX_CODE: u16 = (1 << PLANES) - 1;
+/// The 16MHz timer clock divided by 2^4 so that the timer counts microseconds
+const PRESCALER_1MHZ: u32 = 4;
+/// PPI channels lighting and blanking the row of the slot
+const PPI_LIGHT: usize = 0;
+const PPI_BLANK: usize = 1;
+
+/// What the interrupt needs to prepare each slot
+struct Scanner {
+    cols: [Pin<Output<PushPull>>; COL_COUNT],
+    /// The brightness code (0..=MAX_CODE) of every cell
+    codes: [[u8; COL_COUNT]; ROW_COUNT],
+    /// The slot to prepare next, row-major by plane
+    next: usize,
+}
+
+/// The state shared with the TIMER4 interrupt handler
+static SCANNER: Mutex<RefCell<Option<Scanner>>> = Mutex::new(RefCell::new(None));
+
+/// The MB2's onboard 5x5 LED matrix scanned in hardware with 16 brightness levels per LED.
+pub struct Scanned;
+
+impl Scanned {
+    /// Hand the display pins to GPIOTE (rows) and the interrupt (columns), wire TIMER4 to the row
+    /// tasks through PPI, and start scanning a blank matrix.
+    pub fn new(
+        timer: TIMER4,
+        gpiote: GPIOTE,
+        ppi: PPI,
+        pins: DisplayPins,
+        nvic: &mut NVIC,
+    ) -> Self {
+        let (mut cols, rows) = pins.degrade();
+        for col in &mut cols {
+            col.set_high().ok();
+        }
+        // each row pin becomes GPIOTE channel `row`, starting dark
+        for (channel, row) in rows.iter().enumerate() {
+            gpiote.config[channel].write(|w| {
+                w.mode().task().outinit().low().polarity().none();
+                w.port().bit(row.port() == Port::Port1);
+                unsafe { w.psel().bits(row.pin()) }
+            });
+        }
+
+        timer.tasks_stop.write(|w| unsafe { w.bits(1) });
+        timer.mode.write(|w| w.mode().timer());
+        timer.bitmode.write(|w| w.bitmode()._32bit());
+        timer.prescaler.write(|w| unsafe { w.bits(PRESCALER_1MHZ) });
+        timer.cc[0].write(|w| unsafe { w.bits(UNIT_US) });
+        timer.cc[1].write(|w| unsafe { w.bits(SLOT_US) });
+        timer.shorts.write(|w| w.compare1_clear().enabled());
+        timer.intenset.write(|w| w.compare0().set());
+
+        ppi.ch[PPI_LIGHT]
+            .eep
+            .write(|w| unsafe { w.bits(timer.events_compare[1].as_ptr() as u32) });
+        ppi.ch[PPI_BLANK]
+            .eep
+            .write(|w| unsafe { w.bits(timer.events_compare[0].as_ptr() as u32) });
+        set_row(&ppi, &gpiote, 0);
+        ppi.chenset
+            .write(|w| unsafe { w.bits(1 << PPI_LIGHT | 1 << PPI_BLANK) });
+
+        let scanner = Scanner {
+            cols,
+            codes: [[0; COL_COUNT]; ROW_COUNT],
+            next: 0,
+        };
+        free(|cs| SCANNER.borrow(cs).replace(Some(scanner)));
+
+        // SAFETY: the handler only touches SCANNER inside a critical section, and SCANNER has been
+        // set up before the interrupt is unmasked
+        unsafe {
+            nvic.set_priority(pac::Interrupt::TIMER4, SCAN_PRIORITY);
+            NVIC::unmask(pac::Interrupt::TIMER4);
+        }
+        timer.tasks_start.write(|w| unsafe { w.bits(1) });
+        Scanned
+    }
+}
+
+impl Renderer for Scanned {
+    fn render_levels(&mut self, levels: &Levels) {
+        let mut codes = [[0; COL_COUNT]; ROW_COUNT];
+        for (code, level) in codes.iter_mut().flatten().zip(levels.iter().flatten()) {
+            *code = (*level as u16 * MAX_CODE).div_ceil(fade::MAX_BRIGHTNESS as u16) as u8;
+        }
+        free(|cs| {
+            if let Some(scanner) = SCANNER.borrow(cs).borrow_mut().as_mut() {
+                scanner.codes = codes;
+            }
+        });
+    }
+}
+
+/// Point the PPI channels at the light and blank tasks of `row`'s GPIOTE channel.
+fn set_row(ppi: &pac::ppi::RegisterBlock, gpiote: &pac::gpiote::RegisterBlock, row: usize) {
+    ppi.ch[PPI_LIGHT]
+        .tep
+        .write(|w| unsafe { w.bits(gpiote.tasks_set[row].as_ptr() as u32) });
+    ppi.ch[PPI_BLANK]
+        .tep
+        .write(|w| unsafe { w.bits(gpiote.tasks_clr[row].as_ptr() as u32) });
+}
+
+/// The slot's row was just blanked by the hardware: prepare the next one before TIMER4 wraps.
+#[interrupt]
+fn TIMER4() {
+    // SAFETY: after Scanned::new only this handler touches these peripherals, and only writes
+    // registers that do not affect the row currently being switched by the hardware
+    let (timer, gpiote, ppi) = unsafe { (&*TIMER4::ptr(), &*GPIOTE::ptr(), &*PPI::ptr()) };
+    timer.events_compare[0].reset();
+
+    free(|cs| {
+        if let Some(scanner) = SCANNER.borrow(cs).borrow_mut().as_mut() {
+            let (row, plane) = (scanner.next / PLANES, scanner.next % PLANES);
+            for (col, code) in scanner.cols.iter_mut().zip(scanner.codes[row]) {
+                // the columns sink current, so a lit cell's column is driven low
+                let lit = code >> plane & 1 == 1;
+                col.set_state(PinState::from(!lit)).ok();
+            }
+            set_row(ppi, gpiote, row);
+            timer.cc[0].write(|w| unsafe { w.bits(UNIT_US << plane) });
+            scanner.next = (scanner.next + 1) % (ROW_COUNT * PLANES);
+        }
+    });
+}
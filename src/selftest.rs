@@ -0,0 +1,85 @@
+//! A hardware self-test of the LED matrix, run at power-up instead of the intro while the A and B
+//! buttons are held (boot.rs).
+//!
+//! A single lit pixel walks through all 25 positions in row-major order, so a dead LED, or a
+//! dead row or column driver (a whole row or column staying dark), is easy to spot. Unlike the
+//! intro it cannot be skipped.
+
+use crate::animation::Animation;
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// How long each pixel is lit. 25 frames take 1.5s, inside the 2s watchdog timeout
+const SELF_TEST_FRAME_MS: u32 = 60;
+/// Number of frames, one per LED
+const SELF_TEST_FRAMES: usize = ROW_COUNT * COL_COUNT;
+
+/// Frame `index` of the self-test: only the LED at (index / COL_COUNT, index % COL_COUNT) lit.
+pub fn frame(index: usize) -> LEDState {
+    let index = index % SELF_TEST_FRAMES;
+    let mut state: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+    state[index / COL_COUNT][index % COL_COUNT] = 1;
+    state
+}
+
+/// The self-test animation, walking the lit pixel across every LED.
+pub const SELF_TEST: Animation = Animation {
+    frames: SELF_TEST_FRAMES,
+    frame_ms: SELF_TEST_FRAME_MS,
+    frame,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fade::{self, Levels};
+    use crate::render::Renderer;
+    use embedded_hal::delay::DelayNs;
+
+    /// A backend that keeps every image drawn on it
+    #[derive(Default)]
+    struct Recorder(Vec<Levels>);
+
+    impl Renderer for Recorder {
+        fn render_levels(&mut self, levels: &Levels) {
+            self.0.push(*levels);
+        }
+    }
+
+    /// A delay that only adds up how long it was asked to wait
+    #[derive(Default)]
+    struct Clock {
+        elapsed_ns: u64,
+    }
+
+    impl DelayNs for Clock {
+        fn delay_ns(&mut self, ns: u32) {
+            self.elapsed_ns += ns as u64;
+        }
+    }
+
+    #[test]
+    fn each_frame_lights_exactly_one_led_in_row_major_order() {
+        let mut expected = (0..ROW_COUNT).flat_map(|row| (0..COL_COUNT).map(move |col| (row, col)));
+        for index in 0..SELF_TEST_FRAMES {
+            let lit: Vec<_> = crate::live_cells(&frame(index)).collect();
+            assert_eq!(lit, [expected.next().unwrap()], "frame {index}");
+        }
+        // every LED was lit once, and indices past the end start over
+        assert!(expected.next().is_none());
+        assert_eq!(frame(SELF_TEST_FRAMES), frame(0));
+    }
+
+    #[test]
+    fn the_self_test_plays_every_frame_inside_the_watchdog_timeout() {
+        let mut renderer = Recorder::default();
+        let mut clock = Clock::default();
+        SELF_TEST.play(&mut renderer, &mut clock, || false);
+
+        let expected: Vec<Levels> = (0..SELF_TEST_FRAMES)
+            .map(|index| fade::levels(&frame(index)))
+            .collect();
+        assert_eq!(renderer.0, expected);
+        // 1.5s, with the watchdog (fed only between frames of the game) resetting after 2s
+        assert_eq!(clock.elapsed_ns, 1_500_000_000);
+    }
+}
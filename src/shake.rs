@@ -0,0 +1,110 @@
+//! Shake detection from the onboard LSM303AGR accelerometer.
+//!
+//! At rest the accelerometer reads roughly 1g (1000 mg) due to gravity. A shake is reported
+//! once the magnitude of the acceleration has exceeded a threshold for a number of
+//! consecutive samples, which filters out single-sample knocks and bumps.
+
+/// Default magnitude (in mg) above which a sample counts towards a shake
+pub const SHAKE_THRESHOLD_MG: u32 = 1_800;
+/// Default number of consecutive over-threshold samples (frames) required to report a shake
+pub const SHAKE_FRAMES: u8 = 2;
+
+/// Tracks consecutive high-acceleration samples to decide when the device is being shaken.
+pub struct ShakeDetector {
+    threshold_sq: u32,
+    required: u8,
+    count: u8,
+}
+
+impl ShakeDetector {
+    /// Create a detector that reports a shake after `required` consecutive samples whose
+    /// magnitude exceeds `threshold_mg`.
+    pub const fn new(threshold_mg: u32, required: u8) -> Self {
+        ShakeDetector {
+            threshold_sq: threshold_mg * threshold_mg,
+            required,
+            count: 0,
+        }
+    }
+
+    /// Feed one (x, y, z) sample in mg, returning `true` while the device is being shaken.
+    ///
+    /// The magnitude is compared in squared form so no square root is needed.
+    pub fn update(&mut self, accel: (i16, i16, i16)) -> bool {
+        let (x, y, z) = accel;
+        let magnitude_sq = x.unsigned_abs() as u32 * x.unsigned_abs() as u32
+            + y.unsigned_abs() as u32 * y.unsigned_abs() as u32
+            + z.unsigned_abs() as u32 * z.unsigned_abs() as u32;
+
+        if magnitude_sq > self.threshold_sq {
+            self.count = self.count.saturating_add(1);
+        } else {
+            self.count = 0;
+        }
+
+        self.count >= self.required
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `samples` into a default detector and collect what it reports for each
+    fn feed(samples: &[(i16, i16, i16)]) -> Vec<bool> {
+        let mut detector = ShakeDetector::new(SHAKE_THRESHOLD_MG, SHAKE_FRAMES);
+        samples
+            .iter()
+            .map(|&accel| detector.update(accel))
+            .collect()
+    }
+
+    const REST: (i16, i16, i16) = (0, 0, 1_000);
+    const SPIKE: (i16, i16, i16) = (2_000, 0, 1_000);
+
+    #[test]
+    fn a_board_at_rest_never_shakes() {
+        assert!(feed(&[REST; 50]).iter().all(|&shaking| !shaking));
+    }
+
+    #[test]
+    fn a_single_spike_is_not_a_shake() {
+        assert_eq!(feed(&[REST, SPIKE, REST, REST]), [false; 4]);
+    }
+
+    #[test]
+    fn shake_frames_high_samples_in_a_row_are_a_shake() {
+        let mut samples = vec![SPIKE; SHAKE_FRAMES as usize];
+        samples.push(SPIKE);
+        let reports = feed(&samples);
+        assert!(
+            reports[..SHAKE_FRAMES as usize - 1]
+                .iter()
+                .all(|&shaking| !shaking)
+        );
+        assert!(
+            reports[SHAKE_FRAMES as usize - 1..]
+                .iter()
+                .all(|&shaking| shaking)
+        );
+    }
+
+    #[test]
+    fn a_low_sample_restarts_the_count() {
+        let mut samples = vec![SPIKE; SHAKE_FRAMES as usize - 1];
+        samples.push(REST);
+        samples.extend([SPIKE; SHAKE_FRAMES as usize - 1]);
+        assert!(feed(&samples).iter().all(|&shaking| !shaking));
+    }
+
+    #[test]
+    fn negative_readings_count_by_their_magnitude() {
+        let mut detector = ShakeDetector::new(SHAKE_THRESHOLD_MG, 1);
+        assert!(detector.update((-2_000, 0, 0)));
+        assert!(detector.update((0, -2_000, 0)));
+        assert!(detector.update((0, 0, -2_000)));
+        // i16::MIN has no positive counterpart but still has a magnitude
+        assert!(detector.update((i16::MIN, i16::MIN, i16::MIN)));
+        assert!(!detector.update((-1_000, 0, -1_000)));
+    }
+}
@@ -0,0 +1,72 @@
+//! Low-power waiting between frames.
+//!
+//! Instead of spinning on the Timer1 clock until a deadline, the main loop puts the CPU to sleep
+//! with `wfi`. A one-shot TIMER3 is armed to fire at the deadline so the CPU wakes on time; the
+//! display's TIMER2 interrupt also wakes it whenever a row is refreshed, after which it simply
+//! goes back to sleep until the deadline has passed. Buttons are still polled once per frame, so
+//! they are handled exactly as promptly as before. The current saved has not been measured; the
+//! README gives an unmeasured estimate.
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{Mutex, free};
+use microbit::hal::timer::{OneShot, Periodic, Timer};
+use microbit::pac::{self, NVIC, TIMER1, TIMER3, interrupt};
+
+/// NVIC priority of the wake-up interrupt: below the display (level 2) since its handler only
+/// clears the compare event and nothing depends on it running promptly once the CPU is awake.
+const WAKE_PRIORITY: u8 = 3 << 5;
+
+/// The wake-up timer, shared with the TIMER3 interrupt handler
+static WAKE_TIMER: Mutex<RefCell<Option<Timer<TIMER3, OneShot>>>> = Mutex::new(RefCell::new(None));
+
+/// Sleeps the CPU until deadlines measured on the free-running Timer1 clock.
+pub struct Sleeper;
+
+impl Sleeper {
+    /// Hand TIMER3 over as the wake-up timer and unmask its interrupt.
+    pub fn new(timer: TIMER3, nvic: &mut NVIC) -> Self {
+        let mut timer = Timer::one_shot(timer);
+        timer.enable_interrupt();
+        free(|cs| WAKE_TIMER.borrow(cs).replace(Some(timer)));
+
+        // SAFETY: the handler only touches WAKE_TIMER inside a critical section, and WAKE_TIMER
+        // has been set up before the interrupt is unmasked
+        unsafe {
+            nvic.set_priority(pac::Interrupt::TIMER3, WAKE_PRIORITY);
+            NVIC::unmask(pac::Interrupt::TIMER3);
+        }
+        Sleeper
+    }
+
+    /// Sleep until `offset_us` microseconds after `start` on `clock`, returning straight away if
+    /// that has already passed.
+    pub fn sleep_until(&mut self, clock: &Timer<TIMER1, Periodic>, start: u32, offset_us: u32) {
+        loop {
+            let left_us = crate::time_left_us(start, clock.read(), offset_us);
+            if left_us == 0 {
+                return;
+            }
+
+            // arming the timer and sleeping inside one critical section means an interrupt
+            // arriving in between is left pending, which wakes wfi immediately instead of
+            // being handled first and leaving the CPU asleep past the deadline
+            free(|cs| {
+                if let Some(timer) = WAKE_TIMER.borrow(cs).borrow_mut().as_mut() {
+                    timer.start(left_us);
+                }
+                cortex_m::asm::wfi();
+            });
+        }
+    }
+}
+
+/// Clear the wake-up compare event; waking the CPU was the whole job.
+#[interrupt]
+fn TIMER3() {
+    free(|cs| {
+        if let Some(timer) = WAKE_TIMER.borrow(cs).borrow_mut().as_ref() {
+            timer.reset_event();
+        }
+    });
+}
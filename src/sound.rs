@@ -0,0 +1,116 @@
+//! Loud sounds (a clap, a whistle) picked up by the MB2's microphone.
+//!
+//! The MEMS microphone is powered through its RUN pin (which also lights the microphone LED next
+//! to the logo) and its amplified output, centred on a bias voltage, is read on AIN3 with the
+//! SAADC. A reading is a short burst of SAMPLES samples taken by the SAADC's own sample timer,
+//! and its level is the peak-to-peak swing of the burst. The main loop reads the microphone at
+//! every sub-frame, as it samples the btns, and the game is handed the loudest level of the
+//! frame, so a clap shorter than a frame is still heard in between the bursts.
+//!
+//! The SAADC is shared with the ambient light sensor and the speed knob (light.rs, pot.rs), so each
+//! reading sets up the channels it needs and leaves the SAADC disabled for the next user.
+
+use core::sync::atomic::{Ordering, compiler_fence};
+
+use embedded_hal::digital::OutputPin;
+use microbit::gpio::MicrophonePins;
+use microbit::hal::gpio::{Floating, Input, OpenDrain, Output, p0};
+use microbit::pac::SAADC;
+
+/// Samples per reading: 2ms of sound at the 16kHz sample rate
+const SAMPLES: usize = 32;
+/// The 16MHz clock divided by this between samples gives the 16kHz sample rate
+const SAMPLE_RATE_DIVIDER: u16 = 1_000;
+
+/// Level (peak-to-peak, in 10-bit SAADC counts) from which a sound spawns cells. A quiet room
+/// reads a few tens of counts; a nearby clap several hundred
+const THRESHOLD: u16 = 150;
+/// Each this much louder than THRESHOLD spawns one more cell
+const LEVEL_PER_CELL: u16 = 50;
+/// Most cells spawned by one frame's sound
+const MAX_CELLS: u8 = 10;
+
+/// The number of live cells a sound of `level` spawns: none below THRESHOLD, then one more per
+/// LEVEL_PER_CELL counts up to MAX_CELLS.
+pub fn burst_cells(level: u16) -> u8 {
+    match level.checked_sub(THRESHOLD) {
+        Some(over) => (over / LEVEL_PER_CELL + 1).min(MAX_CELLS as u16) as u8,
+        None => 0,
+    }
+}
+
+/// The microphone
+pub struct Microphone {
+    _mic_in: p0::P0_05<Input<Floating>>,
+    /// Kept high to power the microphone
+    _mic_run: p0::P0_20<Output<OpenDrain>>,
+    /// Loudest level read since the last take_level()
+    loudest: u16,
+}
+
+impl Microphone {
+    /// Power up the microphone.
+    pub fn new(pins: MicrophonePins) -> Self {
+        let mut mic_run = pins.mic_run;
+        mic_run.set_high().ok();
+        Microphone {
+            _mic_in: pins.mic_in,
+            _mic_run: mic_run,
+            loudest: 0,
+        }
+    }
+
+    /// Take one reading (about 2ms), keeping its level if it is the loudest since take_level().
+    pub fn sample(&mut self, saadc: &SAADC) {
+        let mut results = [0i16; SAMPLES];
+        saadc.resolution.write(|w| w.val()._10bit());
+        for channel in saadc.ch.iter() {
+            channel.pselp.write(|w| w.pselp().nc());
+        }
+        saadc.ch[0].config.write(|w| {
+            w.resp().bypass().resn().bypass();
+            w.gain().gain1_4().refsel().vdd1_4();
+            w.tacq()._3us().mode().se()
+        });
+        saadc.ch[0].pselp.write(|w| w.pselp().analog_input3());
+        saadc
+            .samplerate
+            .write(|w| unsafe { w.cc().bits(SAMPLE_RATE_DIVIDER) }.mode().timers());
+
+        saadc.enable.write(|w| w.enable().enabled());
+        saadc
+            .result
+            .ptr
+            .write(|w| unsafe { w.ptr().bits(results.as_mut_ptr() as u32) });
+        saadc
+            .result
+            .maxcnt
+            .write(|w| unsafe { w.maxcnt().bits(SAMPLES as u16) });
+        saadc.events_end.reset();
+        saadc.events_started.reset();
+        saadc.tasks_start.write(|w| unsafe { w.bits(1) });
+        while saadc.events_started.read().bits() == 0 {}
+        // in timers mode a single sample task starts the SAADC sampling on its own
+        saadc.tasks_sample.write(|w| unsafe { w.bits(1) });
+        while saadc.events_end.read().bits() == 0 {}
+        saadc.events_end.reset();
+        saadc.tasks_stop.write(|w| unsafe { w.bits(1) });
+        // the results were written by EasyDMA behind the compiler's back
+        compiler_fence(Ordering::SeqCst);
+        saadc.samplerate.write(|w| w.mode().task());
+        saadc.ch[0].pselp.write(|w| w.pselp().nc());
+        saadc.enable.write(|w| w.enable().disabled());
+
+        let (low, high) = results
+            .iter()
+            .fold((i16::MAX, i16::MIN), |(low, high), result| {
+                (low.min(*result), high.max(*result))
+            });
+        self.loudest = self.loudest.max(high.abs_diff(low));
+    }
+
+    /// The loudest level (peak-to-peak, in 10-bit SAADC counts) read since the last call.
+    pub fn take_level(&mut self) -> u16 {
+        core::mem::take(&mut self.loudest)
+    }
+}
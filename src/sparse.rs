@@ -0,0 +1,179 @@
+//! A sparse Life board for big, mostly empty worlds. Enabled with the `sparse` cargo feature,
+//! which makes it the board of the Canvas step mode in place of the dense `VirtualBoard`.
+//!
+//! `SparseBoard` keeps only the (row, col) coordinates of its live cells, sorted in row-major
+//! order, in a heapless::Vec of capacity N. A 64x64 torus holding up to 512 live cells then takes
+//! 2KB (the cells and the next generation being built) where a dense board and its scratch copy
+//! would take 8KB, and a step only visits the cells around live ones.
+//!
+//! Each step visits the 3x3 neighborhood of every live cell. A cell near several live cells is
+//! reached several times but only evaluated once: from the first live cell, in row-major order,
+//! of its own 3x3 neighborhood. Cells are looked up by binary search. Rules that give birth to
+//! cells with no live neighbors (B0) cannot be stepped this way, and live cells beyond the
+//! capacity are dropped.
+
+use heapless::Vec;
+
+use crate::life::Rule;
+use crate::random::{RandomSource, XorShift32};
+use crate::{COL_COUNT, LEDState, RANDOM_DENSITY_PERCENT, ROW_COUNT};
+
+/// Side of the square patch in the middle of the board that randomize() fills, so that a big
+/// world starts well within its capacity
+const SEED_SPAN: usize = 16;
+
+/// The live cells of a W x H torus, at most N of them
+pub struct SparseBoard<const W: usize, const H: usize, const N: usize> {
+    cells: Vec<(u8, u8), N>,
+    next: Vec<(u8, u8), N>,
+}
+
+impl<const W: usize, const H: usize, const N: usize> SparseBoard<W, H, N> {
+    /// Create an empty board.
+    pub const fn new() -> Self {
+        const { assert!(W <= 256 && H <= 256, "coordinates are stored as u8") };
+        SparseBoard {
+            cells: Vec::new(),
+            next: Vec::new(),
+        }
+    }
+
+    /// Clear the board and light each cell of the SEED_SPAN x SEED_SPAN patch in its middle with
+    /// a RANDOM_DENSITY_PERCENT chance, as many as fit.
+    ///
+    /// As for the dense board, one u32 is drawn from `random_gen` to seed a XorShift32 for the
+    /// per-cell decisions.
+    pub fn randomize<R: RandomSource>(&mut self, random_gen: &mut R) {
+        let mut rng = XorShift32::new(random_gen.random_u32());
+        let (rows, cols) = (SEED_SPAN.min(H), SEED_SPAN.min(W));
+        let (top, left) = ((H - rows) / 2, (W - cols) / 2);
+
+        self.cells.clear();
+        for row in top..top + rows {
+            for col in left..left + cols {
+                if rng.next_u32() % 100 < RANDOM_DENSITY_PERCENT as u32 {
+                    // visited in row-major order, so the cells stay sorted
+                    let _ = self.cells.push((row as u8, col as u8));
+                }
+            }
+        }
+    }
+
+    /// `true` if no cell is lit.
+    pub fn done(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Advance the whole board by one generation of `rule`, wrapping around its edges.
+    pub fn step(&mut self, rule: Rule) {
+        self.next.clear();
+        for &live in self.cells.iter() {
+            for (row, col) in Self::neighborhood(live) {
+                let mut first = None;
+                let mut neighbors = 0;
+                for cell in Self::neighborhood((row, col)) {
+                    if self.is_alive(cell) {
+                        first = Some(first.map_or(cell, |first: (u8, u8)| first.min(cell)));
+                        neighbors += (cell != (row, col)) as u8;
+                    }
+                }
+                if first != Some(live) {
+                    continue;
+                }
+
+                let next = if self.is_alive((row, col)) {
+                    rule.survives(neighbors)
+                } else {
+                    rule.births(neighbors)
+                };
+                if next {
+                    let _ = self.next.push((row, col));
+                }
+            }
+        }
+        self.next.sort_unstable();
+        core::mem::swap(&mut self.cells, &mut self.next);
+    }
+
+    /// The 5x5 window whose top-left cell is `origin` (row, col), wrapping around the
+    /// right and bottom edges of the board.
+    pub fn view(&self, origin: (usize, usize)) -> LEDState {
+        let mut state: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+        for (row, line) in state.iter_mut().enumerate() {
+            for (col, cell) in line.iter_mut().enumerate() {
+                let (row, col) = ((origin.0 + row) % H, (origin.1 + col) % W);
+                *cell = self.is_alive((row as u8, col as u8)) as u8;
+            }
+        }
+        state
+    }
+
+    /// `true` if `cell` is lit.
+    fn is_alive(&self, cell: (u8, u8)) -> bool {
+        self.cells.binary_search(&cell).is_ok()
+    }
+
+    /// The 3x3 block of cells centered on `cell` (itself included), on the torus.
+    fn neighborhood(cell: (u8, u8)) -> impl Iterator<Item = (u8, u8)> {
+        let (row, col) = (cell.0 as usize, cell.1 as usize);
+        // adding H - 1 / W - 1 rather than subtracting 1 keeps the indices from going negative
+        [H - 1, 0, 1].into_iter().flat_map(move |dr| {
+            [W - 1, 0, 1]
+                .into_iter()
+                .map(move |dc| (((row + dr) % H) as u8, ((col + dc) % W) as u8))
+        })
+    }
+}
+
+impl<const W: usize, const H: usize, const N: usize> Default for SparseBoard<W, H, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::{self, Board, EdgeMode};
+
+    /// The live cells of `board` on a dense board of the same size
+    fn dense<const W: usize, const H: usize, const N: usize>(
+        board: &SparseBoard<W, H, N>,
+    ) -> Board<W, H> {
+        let mut fb = [[0; W]; H];
+        for &(row, col) in board.cells.iter() {
+            fb[row as usize][col as usize] = 1;
+        }
+        fb
+    }
+
+    #[test]
+    fn steps_like_the_dense_torus() {
+        let mut random = XorShift32::new(0x2545_F491);
+        for rule in life::PRESETS {
+            for _ in 0..10 {
+                // wider than it is high, and with room for every cell, so none are dropped
+                let mut board: SparseBoard<16, 12, 192> = SparseBoard::new();
+                board.randomize(&mut random);
+                let mut reference = dense(&board);
+                let mut scratch = [[0; 16]; 12];
+                for generation in 0..30 {
+                    board.step(rule);
+                    life::step_with_rule(&mut reference, &mut scratch, rule, EdgeMode::Toroidal);
+                    assert_eq!(dense(&board), reference, "{rule} generation {generation}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn the_view_wraps_around_the_right_and_bottom_edges() {
+        let mut board: SparseBoard<8, 8, 4> = SparseBoard::new();
+        board.cells.extend_from_slice(&[(0, 0), (7, 7)]).unwrap();
+        let view = board.view((6, 6));
+        let mut expected = [[0; COL_COUNT]; ROW_COUNT];
+        expected[1][1] = 1;
+        expected[2][2] = 1;
+        assert_eq!(view, expected);
+    }
+}
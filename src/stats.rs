@@ -0,0 +1,127 @@
+//! Per-generation statistics: population, births, and deaths.
+//!
+//! The Game records a sample every frame a step is taken, comparing the board before the step
+//! with the one after it, so these work the same for every step mode (a cell that lit up counts
+//! as a birth and one that went dark as a death, even when no Life rule was involved). The last
+//! WINDOW samples are kept for rolling averages. Frames replacing the board without a step (a
+//! randomization, complement, or load) update the population but record no births or deaths.
+
+use crate::{LEDState, live_cells};
+
+/// Number of recent steps the rolling averages are taken over
+pub const WINDOW: usize = 16;
+
+/// The statistics of one generation
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Sample {
+    /// The generation number (see Game::generation)
+    pub generation: u32,
+    /// Live cells
+    pub population: u8,
+    /// Cells lit by the step to this generation
+    pub births: u8,
+    /// Cells turned dark by the step to this generation
+    pub deaths: u8,
+}
+
+/// Rolling averages of the samples in the window
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Averages {
+    pub population: f32,
+    pub births: f32,
+    pub deaths: f32,
+}
+
+/// The current sample and a ring of the last WINDOW stepped ones
+pub struct Stats {
+    current: Sample,
+    window: [Sample; WINDOW],
+    len: usize,
+    next: usize,
+}
+
+impl Stats {
+    /// Statistics of an empty board at generation 0, with no steps recorded.
+    pub const fn new() -> Self {
+        Stats {
+            current: Sample {
+                generation: 0,
+                population: 0,
+                births: 0,
+                deaths: 0,
+            },
+            window: [Sample {
+                generation: 0,
+                population: 0,
+                births: 0,
+                deaths: 0,
+            }; WINDOW],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// Record the step from `before` to `after`, which is generation `generation`.
+    pub fn record_step(&mut self, before: &LEDState, after: &LEDState, generation: u32) {
+        let (mut births, mut deaths) = (0, 0);
+        for (old, new) in before.iter().flatten().zip(after.iter().flatten()) {
+            match (*old != 0, *new != 0) {
+                (false, true) => births += 1,
+                (true, false) => deaths += 1,
+                _ => {}
+            }
+        }
+        self.current = Sample {
+            generation,
+            population: live_cells(after).count() as u8,
+            births,
+            deaths,
+        };
+
+        self.window[self.next] = self.current;
+        self.next = (self.next + 1) % WINDOW;
+        self.len = (self.len + 1).min(WINDOW);
+    }
+
+    /// Record a board that replaced the last one without a step.
+    pub fn record_board(&mut self, state: &LEDState, generation: u32) {
+        self.current = Sample {
+            generation,
+            population: live_cells(state).count() as u8,
+            births: 0,
+            deaths: 0,
+        };
+    }
+
+    /// The statistics of the board shown now.
+    pub fn current(&self) -> Sample {
+        self.current
+    }
+
+    /// The averages over the last WINDOW steps (or fewer, if fewer have been taken), all 0
+    /// before the first step.
+    pub fn averages(&self) -> Averages {
+        if self.len == 0 {
+            return Averages::default();
+        }
+        let samples = &self.window[..self.len];
+        let mean = |value: fn(&Sample) -> u8| {
+            samples
+                .iter()
+                .map(|sample| value(sample) as u32)
+                .sum::<u32>() as f32
+                / self.len as f32
+        };
+        Averages {
+            population: mean(|sample| sample.population),
+            births: mean(|sample| sample.births),
+            deaths: mean(|sample| sample.deaths),
+        }
+    }
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
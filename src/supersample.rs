@@ -0,0 +1,53 @@
+//! A supersampled view of Life: the board is stepped at SUPERSAMPLE_SCALE times the LED
+//! matrix's resolution in each direction (10x10 for a scale of 2) and each SCALE x SCALE block of
+//! cells is shown as one LED, lit as brightly as the block is full. This renders much richer
+//! dynamics than the 5x5 board itself can hold, with gliders showing up as moving smudges of
+//! light.
+//!
+//! The fine board is a `VirtualBoard` (canvas.rs), also a torus. `downsample` is the render stage
+//! between it and the display.
+
+use crate::canvas::VirtualBoard;
+use crate::fade::{Levels, MAX_BRIGHTNESS};
+use crate::{COL_COUNT, LEDState, ROW_COUNT, SUPERSAMPLE_SCALE};
+
+/// Cells of the fine board per LED
+const BLOCK_CELLS: usize = SUPERSAMPLE_SCALE * SUPERSAMPLE_SCALE;
+
+/// The fine board stepped in place of the LED matrix
+pub type Fine = VirtualBoard<{ COL_COUNT * SUPERSAMPLE_SCALE }, { ROW_COUNT * SUPERSAMPLE_SCALE }>;
+
+/// The number of live cells of `fine` in each LED's block.
+fn block_counts(fine: &Fine) -> [[usize; COL_COUNT]; ROW_COUNT] {
+    let mut counts = [[0; COL_COUNT]; ROW_COUNT];
+    for (row, line) in fine.cells().iter().enumerate() {
+        for (col, cell) in line.iter().enumerate() {
+            counts[row / SUPERSAMPLE_SCALE][col / SUPERSAMPLE_SCALE] += *cell as usize;
+        }
+    }
+    counts
+}
+
+/// Brightness levels with each LED as bright as its block is full, rounded up so that a block
+/// holding a single live cell is still visible.
+pub fn downsample(fine: &Fine) -> Levels {
+    let mut levels = [[0u8; COL_COUNT]; ROW_COUNT];
+    for (line, count_line) in levels.iter_mut().zip(block_counts(fine).iter()) {
+        for (level, count) in line.iter_mut().zip(count_line.iter()) {
+            *level = (count * MAX_BRIGHTNESS as usize).div_ceil(BLOCK_CELLS) as u8;
+        }
+    }
+    levels
+}
+
+/// The board with each LED lit if any cell of its block is, for everything that needs a plain
+/// 5x5 board (the population HUD, logging, and saving).
+pub fn coarse(fine: &Fine) -> LEDState {
+    let mut state: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+    for (line, count_line) in state.iter_mut().zip(block_counts(fine).iter()) {
+        for (cell, count) in line.iter_mut().zip(count_line.iter()) {
+            *cell = (*count > 0) as u8;
+        }
+    }
+    state
+}
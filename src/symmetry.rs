@@ -0,0 +1,90 @@
+//! Mirror and rotational symmetry for randomized boards.
+//!
+//! A symmetric board is made by keeping one region of a random board (the "source") and
+//! copying it onto the rest of the grid. On the odd sized 5x5 grid the middle row and/or
+//! column lie on the mirror axes and map onto themselves, and the center cell is left as
+//! drawn under both quad and rotational symmetry since it is its own image.
+
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// Index of the last row
+const LAST_ROW: usize = ROW_COUNT - 1;
+/// Index of the last column
+const LAST_COL: usize = COL_COUNT - 1;
+
+/// The kind of symmetry applied to randomized boards
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SymmetryMode {
+    /// Plain random board
+    None,
+    /// Left half mirrored onto the right half (symmetric about the middle column)
+    Horizontal,
+    /// Top half mirrored onto the bottom half (symmetric about the middle row)
+    Vertical,
+    /// Top-left quadrant (including the middle row and column) mirrored both ways
+    Quad,
+    /// 90 degree rotational symmetry about the center cell (quad mirroring on a non-square grid)
+    Rotational,
+}
+
+impl SymmetryMode {
+    /// The mode that follows this one when cycling through all modes.
+    pub fn next(self) -> Self {
+        match self {
+            SymmetryMode::None => SymmetryMode::Horizontal,
+            SymmetryMode::Horizontal => SymmetryMode::Vertical,
+            SymmetryMode::Vertical => SymmetryMode::Quad,
+            SymmetryMode::Quad => SymmetryMode::Rotational,
+            SymmetryMode::Rotational => SymmetryMode::None,
+        }
+    }
+}
+
+/// Overwrite the board outside the source region of `mode` with mirrored/rotated copies.
+pub fn symmetrize(state: &mut LEDState, mode: SymmetryMode) {
+    match mode {
+        SymmetryMode::None => (),
+        SymmetryMode::Horizontal => mirror_columns(state),
+        SymmetryMode::Vertical => mirror_rows(state),
+        SymmetryMode::Quad => {
+            mirror_columns(state);
+            mirror_rows(state);
+        }
+        SymmetryMode::Rotational if ROW_COUNT != COL_COUNT => {
+            // a quarter turn only maps a square grid onto itself, so fall back to quad mirroring
+            mirror_columns(state);
+            mirror_rows(state);
+        }
+        SymmetryMode::Rotational => {
+            // rows 0..2 x cols 0..3 is a fundamental domain: its three rotations cover every
+            // other cell exactly once, leaving only the center
+            for row in 0..ROW_COUNT / 2 {
+                for col in 0..COL_COUNT.div_ceil(2) {
+                    let value = state[row][col];
+                    let (mut r, mut c) = (row, col);
+                    for _ in 0..3 {
+                        // rotate 90 degrees clockwise
+                        (r, c) = (c, LAST_ROW - r);
+                        state[r][c] = value;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Copy the left columns onto the right columns.
+fn mirror_columns(state: &mut LEDState) {
+    for line in state.iter_mut() {
+        for col in 0..COL_COUNT / 2 {
+            line[LAST_COL - col] = line[col];
+        }
+    }
+}
+
+/// Copy the top rows onto the bottom rows.
+fn mirror_rows(state: &mut LEDState) {
+    for row in 0..ROW_COUNT / 2 {
+        state[LAST_ROW - row] = state[row];
+    }
+}
@@ -0,0 +1,193 @@
+//! A two-player "teams" variant of Life, also known as the Immigration game.
+//!
+//! Every live cell belongs to one of two teams. Player A seeds the left side of the board and
+//! player B the right side, then the board is stepped by the usual Life rule: survivors keep
+//! their team and a newborn cell joins the team holding the majority of its live neighbors, so
+//! the players can watch whose cells come to dominate. Ties (possible under rules with even
+//! birth counts, e.g. B36 or B2) go to the team whose side of a checkerboard the cell is on, which
+//! is deterministic without favouring one team across the board. Team 0 is shown at full
+//! brightness and team 1 dimmed.
+
+use crate::fade::{Levels, MAX_BRIGHTNESS};
+use crate::life::{self, EdgeMode, Rule};
+use crate::random::RandomSource;
+use crate::{COL_COUNT, LEDState, ROW_COUNT, randomize_state};
+
+/// Brightness of team 1's cells, distinguishable from team 0's at MAX_BRIGHTNESS
+const TEAM1_BRIGHTNESS: u8 = 3;
+
+/// The state of one cell
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Team {
+    Dead,
+    Team0,
+    Team1,
+}
+
+/// A board of team cells
+pub type Teams = [[Team; COL_COUNT]; ROW_COUNT];
+
+/// Half of the board seeded by each player
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    /// The columns left of the middle, seeded by player A with team 0
+    Left,
+    /// The columns right of the middle, seeded by player B with team 1
+    Right,
+}
+
+/// Replace `side` of `teams` with a random seeding of its team, leaving the rest alone. The
+/// middle column of an odd-width board belongs to neither side and is cleared.
+pub fn seed<R: RandomSource>(random_gen: &mut R, teams: &mut Teams, side: Side) {
+    let mut random: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+    randomize_state(random_gen, &mut random);
+
+    for (line, random_line) in teams.iter_mut().zip(random.iter()) {
+        for (col, (cell, random)) in line.iter_mut().zip(random_line.iter()).enumerate() {
+            let (on_side, team) = match side {
+                Side::Left => (col < COL_COUNT / 2, Team::Team0),
+                Side::Right => (col >= COL_COUNT - COL_COUNT / 2, Team::Team1),
+            };
+            if on_side {
+                *cell = if *random != 0 { team } else { Team::Dead };
+            } else if col * 2 + 1 == COL_COUNT {
+                *cell = Team::Dead;
+            }
+        }
+    }
+}
+
+/// Replace the whole of `teams` with a random board whose live cells are each randomly given to
+/// one of the two teams, the usual start of the Immigration game.
+pub fn seed_mixed<R: RandomSource>(random_gen: &mut R, teams: &mut Teams) {
+    let mut random: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+    randomize_state(random_gen, &mut random);
+    let species = random_gen.random_u32();
+
+    for (row, (line, random_line)) in teams.iter_mut().zip(random.iter()).enumerate() {
+        for (col, (cell, random)) in line.iter_mut().zip(random_line.iter()).enumerate() {
+            *cell = match (*random != 0, (species >> (row * COL_COUNT + col)) & 1) {
+                (false, _) => Team::Dead,
+                (true, 0) => Team::Team0,
+                (true, _) => Team::Team1,
+            };
+        }
+    }
+}
+
+/// The live cells of `teams`, whatever their team.
+pub fn alive(teams: &Teams) -> LEDState {
+    let mut state: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+    for (line, team_line) in state.iter_mut().zip(teams.iter()) {
+        for (cell, team) in line.iter_mut().zip(team_line.iter()) {
+            *cell = (*team != Team::Dead) as u8;
+        }
+    }
+    state
+}
+
+/// The generation following `teams` under `rule` and `edges`, with each newborn cell joining
+/// the majority team of its live neighbors.
+pub fn step(teams: &Teams, rule: Rule, edges: EdgeMode) -> Teams {
+    let mut next = [[Team::Dead; COL_COUNT]; ROW_COUNT];
+
+    for (row, line) in next.iter_mut().enumerate() {
+        for (col, cell) in line.iter_mut().enumerate() {
+            let (mut team0, mut team1) = (0u8, 0u8);
+            for (r, c) in life::neighbors::<COL_COUNT, ROW_COUNT>(row, col, edges) {
+                match teams[r][c] {
+                    Team::Dead => {}
+                    Team::Team0 => team0 += 1,
+                    Team::Team1 => team1 += 1,
+                }
+            }
+
+            *cell = match teams[row][col] {
+                Team::Dead if rule.births(team0 + team1) => {
+                    if team0 > team1 || (team0 == team1 && (row + col) % 2 == 0) {
+                        Team::Team0
+                    } else {
+                        Team::Team1
+                    }
+                }
+                Team::Dead => Team::Dead,
+                team if rule.survives(team0 + team1) => team,
+                _ => Team::Dead,
+            };
+        }
+    }
+    next
+}
+
+/// Brightness levels: team 0 at MAX_BRIGHTNESS, team 1 at TEAM1_BRIGHTNESS.
+pub fn levels(teams: &Teams) -> Levels {
+    let mut levels = [[0u8; COL_COUNT]; ROW_COUNT];
+    for (line, team_line) in levels.iter_mut().zip(teams.iter()) {
+        for (level, team) in line.iter_mut().zip(team_line.iter()) {
+            *level = match team {
+                Team::Dead => 0,
+                Team::Team0 => MAX_BRIGHTNESS,
+                Team::Team1 => TEAM1_BRIGHTNESS,
+            };
+        }
+    }
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Team::{Team0 as A, Team1 as B};
+
+    const EMPTY: Teams = [[Team::Dead; COL_COUNT]; ROW_COUNT];
+
+    /// A board with `cells` (row, col, team) alive
+    fn board(cells: &[(usize, usize, Team)]) -> Teams {
+        let mut teams = EMPTY;
+        for &(row, col, team) in cells {
+            teams[row][col] = team;
+        }
+        teams
+    }
+
+    #[test]
+    fn a_newborn_joins_the_majority_of_its_neighbors() {
+        // (2, 2) has three live neighbors above it, two of one team and one of the other
+        let two_to_one = board(&[(1, 1, A), (1, 2, A), (1, 3, B)]);
+        assert_eq!(
+            step(&two_to_one, Rule::conway(), EdgeMode::Toroidal)[2][2],
+            A
+        );
+        let one_to_two = board(&[(1, 1, B), (1, 2, A), (1, 3, B)]);
+        assert_eq!(
+            step(&one_to_two, Rule::conway(), EdgeMode::Toroidal)[2][2],
+            B
+        );
+    }
+
+    #[test]
+    fn a_tie_goes_to_the_team_of_the_checkerboard_square() {
+        // under Seeds (B2) a cell between one live cell of each team is born on a tie
+        let tie = board(&[(1, 2, A), (3, 2, B)]);
+        let next = step(&tie, Rule::seeds(), EdgeMode::Bounded);
+        // (2, 2) is an even square and goes to team 0, (2, 1) and (2, 3) odd ones to team 1
+        assert_eq!(next[2][2], A);
+        assert_eq!(next[2][1], B);
+        assert_eq!(next[2][3], B);
+
+        // the same tie with the teams swapped gives the same result
+        let swapped = board(&[(1, 2, B), (3, 2, A)]);
+        assert_eq!(step(&swapped, Rule::seeds(), EdgeMode::Bounded), next);
+    }
+
+    #[test]
+    fn survivors_keep_their_team() {
+        // a block of mixed teams is a still life, each cell staying on its own team
+        let block = board(&[(1, 1, A), (1, 2, B), (2, 1, B), (2, 2, A)]);
+        assert_eq!(step(&block, Rule::conway(), EdgeMode::Toroidal), block);
+        assert_eq!(
+            levels(&block)[1][..3],
+            [0, MAX_BRIGHTNESS, TEAM1_BRIGHTNESS]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Scrolling text on the LED matrix.
+//!
+//! Text is drawn in a font of glyphs 5 pixels tall and 1 to 5 wide, and scrolled across the grid
+//! from right to left, one column per frame, starting and ending on a blank screen. The font holds
+//! the digits, the letters A-Z (lower case letters are drawn as upper case), and the punctuation
+//! ` /-+.:!?`, enough for mode names, rules in B/S notation, numbers, and short messages. Any other
+//! character is drawn as a space.
+//!
+//! A `Scroller` scrolls one message. A `Queue` holds several and scrolls them one after the
+//! other, so one feature can announce something without cutting off another's message.
+
+use core::fmt::{self, Write};
+
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// Most columns of text (glyphs and the blank columns between them) in one message, long enough
+/// for the rule "B12345678/S012345678"; anything longer is cut short
+const MAX_TEXT_COLUMNS: usize = 80;
+/// Columns of the longest text with a screen of blank columns on either side
+const MAX_COLUMNS: usize = 2 * COL_COUNT + MAX_TEXT_COLUMNS;
+
+/// A glyph: its width in columns and one row of pixels per u8 from the top (bit width - 1 is the
+/// leftmost pixel)
+type Glyph = (usize, [u8; 5]);
+
+/// The digits 0-9
+const DIGITS: [Glyph; 10] = [
+    (3, [0b111, 0b101, 0b101, 0b101, 0b111]),
+    (3, [0b010, 0b110, 0b010, 0b010, 0b111]),
+    (3, [0b111, 0b001, 0b111, 0b100, 0b111]),
+    (3, [0b111, 0b001, 0b111, 0b001, 0b111]),
+    (3, [0b101, 0b101, 0b111, 0b001, 0b001]),
+    (3, [0b111, 0b100, 0b111, 0b001, 0b111]),
+    (3, [0b111, 0b100, 0b111, 0b101, 0b111]),
+    (3, [0b111, 0b001, 0b001, 0b001, 0b001]),
+    (3, [0b111, 0b101, 0b111, 0b101, 0b111]),
+    (3, [0b111, 0b101, 0b111, 0b001, 0b111]),
+];
+/// The letters A-Z
+const LETTERS: [Glyph; 26] = [
+    (3, [0b010, 0b101, 0b111, 0b101, 0b101]),
+    (3, [0b110, 0b101, 0b110, 0b101, 0b110]),
+    (3, [0b011, 0b100, 0b100, 0b100, 0b011]),
+    (3, [0b110, 0b101, 0b101, 0b101, 0b110]),
+    (3, [0b111, 0b100, 0b110, 0b100, 0b111]),
+    (3, [0b111, 0b100, 0b110, 0b100, 0b100]),
+    (4, [0b0111, 0b1000, 0b1011, 0b1001, 0b0111]),
+    (3, [0b101, 0b101, 0b111, 0b101, 0b101]),
+    (3, [0b111, 0b010, 0b010, 0b010, 0b111]),
+    (3, [0b001, 0b001, 0b001, 0b101, 0b010]),
+    (3, [0b101, 0b101, 0b110, 0b101, 0b101]),
+    (3, [0b100, 0b100, 0b100, 0b100, 0b111]),
+    (5, [0b10001, 0b11011, 0b10101, 0b10001, 0b10001]),
+    (4, [0b1001, 0b1101, 0b1011, 0b1001, 0b1001]),
+    (4, [0b0110, 0b1001, 0b1001, 0b1001, 0b0110]),
+    (3, [0b110, 0b101, 0b110, 0b100, 0b100]),
+    (4, [0b0110, 0b1001, 0b1001, 0b1011, 0b0111]),
+    (3, [0b110, 0b101, 0b110, 0b101, 0b101]),
+    (3, [0b011, 0b100, 0b010, 0b001, 0b110]),
+    (3, [0b111, 0b010, 0b010, 0b010, 0b010]),
+    (3, [0b101, 0b101, 0b101, 0b101, 0b111]),
+    (3, [0b101, 0b101, 0b101, 0b101, 0b010]),
+    (5, [0b10001, 0b10001, 0b10101, 0b11011, 0b10001]),
+    (3, [0b101, 0b101, 0b010, 0b101, 0b101]),
+    (3, [0b101, 0b101, 0b010, 0b010, 0b010]),
+    (3, [0b111, 0b001, 0b010, 0b100, 0b111]),
+];
+/// The punctuation of the font
+const SLASH: Glyph = (3, [0b001, 0b001, 0b010, 0b100, 0b100]);
+const MINUS: Glyph = (3, [0b000, 0b000, 0b111, 0b000, 0b000]);
+const PLUS: Glyph = (3, [0b000, 0b010, 0b111, 0b010, 0b000]);
+const PERIOD: Glyph = (1, [0, 0, 0, 0, 1]);
+const COLON: Glyph = (1, [0, 1, 0, 1, 0]);
+const EXCLAMATION: Glyph = (1, [1, 1, 1, 0, 1]);
+const QUESTION: Glyph = (3, [0b110, 0b001, 0b010, 0b000, 0b010]);
+const SPACE: Glyph = (2, [0; 5]);
+
+/// The glyph drawn for `c`.
+fn glyph(c: char) -> Glyph {
+    match c.to_ascii_uppercase() {
+        c @ '0'..='9' => DIGITS[c as usize - '0' as usize],
+        c @ 'A'..='Z' => LETTERS[c as usize - 'A' as usize],
+        '/' => SLASH,
+        '-' => MINUS,
+        '+' => PLUS,
+        '.' => PERIOD,
+        ':' => COLON,
+        '!' => EXCLAMATION,
+        '?' => QUESTION,
+        _ => SPACE,
+    }
+}
+
+/// Text being scrolled across the grid
+pub struct Scroller {
+    /// One bit per row (bit 0 at the top) for every column of the text, blank columns included
+    columns: [u8; MAX_COLUMNS],
+    len: usize,
+    offset: usize,
+}
+
+impl Scroller {
+    /// Scroll the decimal digits of `number`.
+    pub fn number(number: u32) -> Self {
+        Self::text(format_args!("{number}"))
+    }
+
+    /// Scroll formatted text, e.g. `Scroller::text(format_args!("RULE {rule}"))`.
+    pub fn text(text: fmt::Arguments) -> Self {
+        let mut scroller = Scroller {
+            columns: [0; MAX_COLUMNS],
+            len: COL_COUNT,
+            offset: 0,
+        };
+        // text past MAX_TEXT_COLUMNS is dropped rather than failing the whole write
+        let _ = scroller.write_fmt(text);
+        scroller.len += COL_COUNT;
+        scroller
+    }
+
+    /// The next frame of the scroll, or `None` once the text has scrolled off the grid.
+    pub fn next_frame(&mut self) -> Option<LEDState> {
+        if self.offset + COL_COUNT > self.len {
+            return None;
+        }
+
+        let mut state: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+        for (row, line) in state.iter_mut().enumerate() {
+            for (col, cell) in line.iter_mut().enumerate() {
+                *cell = (self.columns[self.offset + col] >> row) & 1;
+            }
+        }
+        self.offset += 1;
+        Some(state)
+    }
+}
+
+impl Write for Scroller {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        for c in text.chars() {
+            let (width, rows) = glyph(c);
+            // leave room for the glyph, its blank column, and the blank screen after the text
+            if self.len + width + 1 + COL_COUNT > MAX_COLUMNS {
+                return Err(fmt::Error);
+            }
+            for col in 0..width {
+                let shift = width - 1 - col;
+                self.columns[self.len] = rows.iter().enumerate().fold(0, |column, (row, bits)| {
+                    column | ((bits >> shift) & 1) << row
+                });
+                self.len += 1;
+            }
+            // the blank column between glyphs
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Up to N messages, scrolled one after the other in the order they were pushed
+pub struct Queue<const N: usize> {
+    messages: [Option<Scroller>; N],
+    /// Index of the message being scrolled
+    start: usize,
+    len: usize,
+}
+
+impl<const N: usize> Queue<N> {
+    /// Create an empty queue.
+    pub const fn new() -> Self {
+        Queue {
+            messages: [const { None }; N],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// Scroll `message` once every message already queued has scrolled, dropping it if N are
+    /// already queued.
+    pub fn push(&mut self, message: Scroller) {
+        if self.len < N {
+            self.messages[(self.start + self.len) % N] = Some(message);
+            self.len += 1;
+        }
+    }
+
+    /// Scroll `message` straight away in place of everything queued.
+    pub fn show(&mut self, message: Scroller) {
+        self.clear();
+        self.push(message);
+    }
+
+    /// Drop every queued message, including the one scrolling now.
+    pub fn clear(&mut self) {
+        self.messages = [const { None }; N];
+        self.len = 0;
+    }
+
+    /// `true` while no message is queued or scrolling.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The next frame of the message being scrolled, moving on to the next message once it has
+    /// scrolled off, or `None` once the last one has.
+    pub fn next_frame(&mut self) -> Option<LEDState> {
+        while self.len > 0 {
+            if let Some(frame) = self.messages[self.start]
+                .as_mut()
+                .and_then(Scroller::next_frame)
+            {
+                return Some(frame);
+            }
+            self.messages[self.start] = None;
+            self.start = (self.start + 1) % N;
+            self.len -= 1;
+        }
+        None
+    }
+}
+
+impl<const N: usize> Default for Queue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
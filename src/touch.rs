@@ -0,0 +1,86 @@
+//! The MB2's gold logo as a third btn.
+//!
+//! The logo is a capacitive touch pad on P1.04. It is read the way the micro:bit runtime reads
+//! it: the pad is driven high to charge it, then left floating, and the time it takes to
+//! discharge back to a low reading is counted in polling loops. A finger on the logo adds its
+//! capacitance to the pad's, so a touch takes noticeably longer. The untouched time is measured
+//! when the logo is set up (so the logo must not be touched at power-up), and a reading counts as
+//! touched once it is TOUCH_MARGIN_PERCENT longer than that.
+//!
+//! `TouchLogo` reads as an `InputPin` that is low while touched, like the A and B btns, so it is
+//! debounced by input::Debounced and queued as events (events.rs) in the same way.
+
+use core::convert::Infallible;
+
+use embedded_hal::digital::{ErrorType, InputPin};
+use microbit::hal::gpio::{Disconnected, p1::P1_04};
+use microbit::pac;
+
+/// The logo's pin on P1
+const LOGO_PIN: usize = 4;
+/// How long the pad is driven high to charge it, in polling loops
+const CHARGE_LOOPS: u32 = 100;
+/// Polling loops after which a discharge is cut short, so a reading never takes long
+const MAX_LOOPS: u32 = 4_000;
+/// Readings averaged into the untouched discharge time
+const CALIBRATION_READINGS: u32 = 8;
+/// How much longer than untouched a discharge has to take to count as a touch
+const TOUCH_MARGIN_PERCENT: u32 = 50;
+
+/// The capacitive touch logo
+pub struct TouchLogo {
+    /// Owned so that nothing else configures the logo's pin
+    _pin: P1_04<Disconnected>,
+    /// Discharges in at least this many polling loops are touches
+    threshold: u32,
+}
+
+impl TouchLogo {
+    /// Take over the logo's pin and measure its untouched discharge time.
+    pub fn new(pin: P1_04<Disconnected>) -> Self {
+        let mut logo = TouchLogo {
+            _pin: pin,
+            threshold: MAX_LOOPS,
+        };
+        let untouched = (0..CALIBRATION_READINGS)
+            .map(|_| logo.discharge_loops())
+            .sum::<u32>()
+            / CALIBRATION_READINGS;
+        logo.threshold = (untouched + untouched * TOUCH_MARGIN_PERCENT / 100).max(1);
+        logo
+    }
+
+    /// Charge the pad and count the polling loops it takes to read low again, at most MAX_LOOPS.
+    fn discharge_loops(&mut self) -> u32 {
+        // SAFETY: only the logo pin's own configuration and output bits are written, and the pin
+        // is owned by self
+        let p1 = unsafe { &*pac::P1::ptr() };
+        p1.outset.write(|w| unsafe { w.bits(1 << LOGO_PIN) });
+        p1.pin_cnf[LOGO_PIN].write(|w| w.dir().output().input().connect());
+        for _ in 0..CHARGE_LOOPS {
+            cortex_m::asm::nop();
+        }
+
+        p1.pin_cnf[LOGO_PIN].write(|w| w.dir().input().input().connect().pull().disabled());
+        let mut loops = 0;
+        while loops < MAX_LOOPS && p1.in_.read().bits() & 1 << LOGO_PIN != 0 {
+            loops += 1;
+        }
+        loops
+    }
+}
+
+impl ErrorType for TouchLogo {
+    type Error = Infallible;
+}
+
+/// Reads low (pressed) while the logo is touched
+impl InputPin for TouchLogo {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.is_low()?)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.discharge_loops() >= self.threshold)
+    }
+}
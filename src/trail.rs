@@ -0,0 +1,93 @@
+//! Motion trails: recently dead cells stay dimly lit for a couple of frames.
+//!
+//! A decay counter is kept for every cell. Live cells hold the counter at its maximum and
+//! dead cells count it down by one each frame, so a moving pattern such as a glider leaves a
+//! short fading tail that makes its direction of travel easy to follow.
+
+use crate::fade::MAX_BRIGHTNESS;
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// Number of frames a dead cell keeps glowing after it was last alive
+const TRAIL_FRAMES: u8 = 2;
+/// Brightness contributed per remaining trail frame
+const TRAIL_STEP: u8 = 2;
+
+/// Per-cell decay counters parallel to the board.
+pub struct Trail {
+    decay: [[u8; COL_COUNT]; ROW_COUNT],
+}
+
+impl Trail {
+    /// Create a trail with no glowing cells.
+    pub const fn new() -> Self {
+        Trail {
+            decay: [[0; COL_COUNT]; ROW_COUNT],
+        }
+    }
+
+    /// Advance the trail by one frame: live cells reset their counter, dead cells decay.
+    pub fn update(&mut self, state: &LEDState) {
+        for (decay_row, state_row) in self.decay.iter_mut().zip(state.iter()) {
+            for (decay, cell) in decay_row.iter_mut().zip(state_row.iter()) {
+                *decay = if *cell != 0 {
+                    TRAIL_FRAMES
+                } else {
+                    decay.saturating_sub(1)
+                };
+            }
+        }
+    }
+
+    /// Combined brightness levels: live cells at full brightness, trails dimmer as they fade.
+    pub fn levels(&self, state: &LEDState) -> [[u8; COL_COUNT]; ROW_COUNT] {
+        let mut levels = [[0u8; COL_COUNT]; ROW_COUNT];
+
+        for (row, line) in levels.iter_mut().enumerate() {
+            for (col, level) in line.iter_mut().enumerate() {
+                *level = if state[row][col] != 0 {
+                    MAX_BRIGHTNESS
+                } else {
+                    self.decay[row][col] * TRAIL_STEP
+                };
+            }
+        }
+        levels
+    }
+}
+
+impl Default for Trail {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_moving_cell_leaves_a_decaying_tail() {
+        let mut trail = Trail::new();
+        let mut state = [[0; COL_COUNT]; ROW_COUNT];
+
+        // a single cell moving one column right per frame
+        for col in 0..3 {
+            state[2] = [0; COL_COUNT];
+            state[2][col] = 1;
+            trail.update(&state);
+        }
+
+        let levels = trail.levels(&state);
+        assert_eq!(
+            levels[2],
+            [0, (TRAIL_FRAMES - 1) * TRAIL_STEP, MAX_BRIGHTNESS, 0, 0]
+        );
+
+        // once the cell is gone its tail fades out completely
+        state[2] = [0; COL_COUNT];
+        trail.update(&state);
+        assert_eq!(trail.levels(&state)[2], [0, 0, TRAIL_STEP, 0, 0]);
+        trail.update(&state);
+        assert_eq!(trail.levels(&state), [[0; COL_COUNT]; ROW_COUNT]);
+    }
+}
@@ -0,0 +1,50 @@
+//! Recovery from a hung main loop with the nRF52833 watchdog timer (WDT).
+//!
+//! The WDT resets the MB2 (which restarts the game on a fresh random board) unless it is fed
+//! within WATCHDOG_TIMEOUT_TICKS. The timeout of 2s is 20 frames at the 100ms refresh rate,
+//! comfortably longer than the slowest legitimate frame (one that erases and writes a page of
+//! flash, well under 200ms), while a display or I2C hang is still recovered from quickly.
+//!
+//! The dog is fed once per frame from the top of the main loop, never from inside the display,
+//! accelerometer, or flash calls, so a hang inside any of them cannot keep feeding it. The only
+//! other feeding is while the boot menu (boot.rs) waits for the btns held at power-up to be released.
+
+use microbit::hal::wdt::{self, WatchdogHandle, count, handles::Hdl0};
+use microbit::pac::WDT;
+
+/// Watchdog timeout in 32.768kHz LFCLK ticks (2s)
+const WATCHDOG_TIMEOUT_TICKS: u32 = 2 * 32_768;
+
+/// The single feed handle of the running watchdog.
+pub struct Watchdog {
+    handle: WatchdogHandle<Hdl0>,
+}
+
+impl Watchdog {
+    /// Start the watchdog with WATCHDOG_TIMEOUT_TICKS, or take over the one already running.
+    ///
+    /// A soft reset (e.g. from the debugger) leaves the WDT running with its configuration
+    /// intact, in which case it cannot be reconfigured and its handle is recovered instead.
+    pub fn start(wdt: WDT) -> Self {
+        let (handle,) = match wdt::Watchdog::try_new(wdt) {
+            Ok(mut watchdog) => {
+                watchdog.set_lfosc_ticks(WATCHDOG_TIMEOUT_TICKS);
+                // halting at a breakpoint should not reset the board under the debugger
+                watchdog.run_during_debug_halt(false);
+                watchdog.activate::<count::One>().handles
+            }
+            Err(wdt) => match wdt::Watchdog::try_recover::<count::One>(wdt) {
+                Ok(parts) => parts.handles,
+                // started with a different number of handles, so this one can never be fed;
+                // let it expire now rather than run with no protection
+                Err(_) => panic!("watchdog already running with unexpected handles"),
+            },
+        };
+        Watchdog { handle }
+    }
+
+    /// Restart the watchdog countdown. Called once per frame from the main loop.
+    pub fn feed(&mut self) {
+        self.handle.pet();
+    }
+}
@@ -0,0 +1,197 @@
+//! Wireworld: a cellular automaton for simulating circuits, with an on-device editor.
+//!
+//! Every cell is empty, a conductor (wire), an electron head, or an electron tail. Each
+//! generation a head becomes a tail, a tail becomes a conductor again, and a conductor becomes
+//! a head if exactly 1 or 2 of its 8 neighbors are heads, so electrons travel along wires,
+//! leaving their tail behind them to keep them moving forwards. Empty cells never change.
+//! Circuits do not wrap round the edges of the grid.
+//!
+//! The `Wireworld` struct pairs the circuit with a cursor for laying out conductors and
+//! electrons on the LED matrix (see the Wireworld step mode in game.rs), and starts out on a
+//! clock: a loop of wire around the edge of the grid with one electron circulating on it.
+
+use crate::fade::{Levels, MAX_BRIGHTNESS};
+use crate::life::{self, EdgeMode};
+use crate::{COL_COUNT, LEDState, ROW_COUNT};
+
+/// Brightness of a conductor, dim enough for the electrons to stand out along it
+const CONDUCTOR_BRIGHTNESS: u8 = 2;
+/// Brightness of an electron tail
+const TAIL_BRIGHTNESS: u8 = 5;
+/// The editing cursor blinks on and off every this many frames
+const CURSOR_BLINK_FRAMES: u32 = 3;
+
+/// The state of one cell
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Cell {
+    Empty,
+    Conductor,
+    Head,
+    Tail,
+}
+
+impl Cell {
+    /// The cell an edit at the cursor turns this one into: empty, then conductor, then an
+    /// electron head, then back to empty. A tail is edited as if it were a conductor.
+    fn next(self) -> Self {
+        match self {
+            Cell::Empty => Cell::Conductor,
+            Cell::Conductor | Cell::Tail => Cell::Head,
+            Cell::Head => Cell::Empty,
+        }
+    }
+}
+
+/// A board of Wireworld cells
+pub type Circuit = [[Cell; COL_COUNT]; ROW_COUNT];
+
+/// A loop of wire around the edge of the grid (its corners cut, since wires connect
+/// diagonally) with one electron on the top edge travelling clockwise.
+pub fn clock() -> Circuit {
+    let mut circuit = [[Cell::Empty; COL_COUNT]; ROW_COUNT];
+    for (row, line) in circuit.iter_mut().enumerate() {
+        for (col, cell) in line.iter_mut().enumerate() {
+            let on_edge = row == 0 || col == 0 || row == ROW_COUNT - 1 || col == COL_COUNT - 1;
+            let on_corner =
+                (row == 0 || row == ROW_COUNT - 1) && (col == 0 || col == COL_COUNT - 1);
+            if on_edge && !on_corner {
+                *cell = Cell::Conductor;
+            }
+        }
+    }
+    circuit[0][1] = Cell::Tail;
+    circuit[0][2] = Cell::Head;
+    circuit
+}
+
+/// The generation following `circuit`.
+pub fn step(circuit: &Circuit) -> Circuit {
+    let mut next = *circuit;
+    for (row, line) in next.iter_mut().enumerate() {
+        for (col, cell) in line.iter_mut().enumerate() {
+            *cell = match circuit[row][col] {
+                Cell::Empty => Cell::Empty,
+                Cell::Head => Cell::Tail,
+                Cell::Tail => Cell::Conductor,
+                Cell::Conductor => {
+                    let heads =
+                        life::neighbors::<COL_COUNT, ROW_COUNT>(row, col, EdgeMode::Bounded)
+                            .filter(|&(r, c)| circuit[r][c] == Cell::Head)
+                            .count();
+                    if heads == 1 || heads == 2 {
+                        Cell::Head
+                    } else {
+                        Cell::Conductor
+                    }
+                }
+            };
+        }
+    }
+    next
+}
+
+/// A circuit being edited or run
+pub struct Wireworld {
+    circuit: Circuit,
+    cursor: (usize, usize),
+    running: bool,
+    frames: u32,
+}
+
+impl Wireworld {
+    /// The running clock circuit, with the cursor in the top-left corner.
+    pub fn new() -> Self {
+        Wireworld {
+            circuit: clock(),
+            cursor: (0, 0),
+            running: true,
+            frames: 0,
+        }
+    }
+
+    /// `true` while the circuit is being stepped, `false` while it is being edited.
+    pub fn running(&self) -> bool {
+        self.running
+    }
+
+    /// Switch between running and editing the circuit.
+    pub fn toggle_running(&mut self) {
+        self.running = !self.running;
+    }
+
+    /// Move the cursor to the next cell in reading order, wrapping from the bottom-right
+    /// corner back to the top-left.
+    pub fn move_cursor(&mut self) {
+        let index = (self.cursor.0 * COL_COUNT + self.cursor.1 + 1) % (ROW_COUNT * COL_COUNT);
+        self.cursor = (index / COL_COUNT, index % COL_COUNT);
+    }
+
+    /// Turn the cell under the cursor into the next kind of cell (see Cell::next).
+    pub fn edit(&mut self) {
+        let (row, col) = self.cursor;
+        self.circuit[row][col] = self.circuit[row][col].next();
+    }
+
+    /// Put an electron head on the cell under the cursor if it is a conductor, to send a
+    /// signal along the wire while the circuit runs.
+    pub fn fire(&mut self) {
+        let (row, col) = self.cursor;
+        if self.circuit[row][col] == Cell::Conductor {
+            self.circuit[row][col] = Cell::Head;
+        }
+    }
+
+    /// Advance the circuit by one generation.
+    pub fn step(&mut self) {
+        self.circuit = step(&self.circuit);
+    }
+
+    /// Count a frame, for the blinking of the cursor.
+    pub fn tick(&mut self) {
+        self.frames = self.frames.wrapping_add(1);
+    }
+
+    /// The cells that are not empty.
+    pub fn occupied(&self) -> LEDState {
+        let mut state: LEDState = [[0; COL_COUNT]; ROW_COUNT];
+        for (line, circuit_line) in state.iter_mut().zip(self.circuit.iter()) {
+            for (lit, cell) in line.iter_mut().zip(circuit_line.iter()) {
+                *lit = (*cell != Cell::Empty) as u8;
+            }
+        }
+        state
+    }
+
+    /// Brightness levels: heads at MAX_BRIGHTNESS, tails dimmer, and conductors dimmer still.
+    /// While editing, the cell under the cursor blinks between its own level and full
+    /// brightness (or off, for a head).
+    pub fn levels(&self) -> Levels {
+        let mut levels = [[0u8; COL_COUNT]; ROW_COUNT];
+        for (line, circuit_line) in levels.iter_mut().zip(self.circuit.iter()) {
+            for (level, cell) in line.iter_mut().zip(circuit_line.iter()) {
+                *level = match cell {
+                    Cell::Empty => 0,
+                    Cell::Conductor => CONDUCTOR_BRIGHTNESS,
+                    Cell::Head => MAX_BRIGHTNESS,
+                    Cell::Tail => TAIL_BRIGHTNESS,
+                };
+            }
+        }
+
+        if !self.running && (self.frames / CURSOR_BLINK_FRAMES).is_multiple_of(2) {
+            let level = &mut levels[self.cursor.0][self.cursor.1];
+            *level = if *level == MAX_BRIGHTNESS {
+                0
+            } else {
+                MAX_BRIGHTNESS
+            };
+        }
+        levels
+    }
+}
+
+impl Default for Wireworld {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,197 @@
+//! An external 5x5 WS2812 ("NeoPixel") panel as a render::Renderer.
+//!
+//! Each cell's brightness level is shown as a color: the dimmest lit level is a deep blue, the
+//! brightest a warm amber, and the levels in between blend from one to the other. The age shading
+//! (age.rs) thus shows young cells warm and old ones cool, and a crossfade or trail cools as it
+//! fades out. The panel is assumed to be wired row-major from the top left, like the onboard
+//! matrix, with the colors sent green first (GRB) as the WS2812 expects.
+//!
+//! A WS2812 reads one bit per 1.25us period from the length of a high pulse. The encoding here
+//! turns a frame into one PWM duty word per bit, for a PWM peripheral counting at 16MHz up to
+//! PERIOD_TICKS, followed by RESET_WORDS periods of low to latch the frame. Sending the words is
+//! left to a `Transmit` implementation, so the encoding can be tested off target; the firmware
+//! sends them with the nRF52833 PWM0 and EasyDMA (neopixel.rs).
+
+use crate::fade::{self, Levels};
+use crate::render::Renderer;
+use crate::{COL_COUNT, ROW_COUNT};
+
+/// Number of pixels on the panel
+pub const PIXEL_COUNT: usize = ROW_COUNT * COL_COUNT;
+/// Bits sent per pixel, 8 per color channel
+const BITS_PER_PIXEL: usize = 24;
+/// One 1.25us bit period in 16MHz PWM ticks
+pub const PERIOD_TICKS: u16 = 20;
+/// Duty words of a 0 bit (high for 0.375us) and a 1 bit (high for 0.8125us). Bit 15 selects the
+/// polarity that starts each period high
+const ZERO: u16 = 0x8000 | 6;
+const ONE: u16 = 0x8000 | 13;
+/// Duty word holding the line low for a whole period
+const LOW: u16 = 0x8000;
+/// Periods of low after the pixels (50us) that latch the frame
+const RESET_WORDS: usize = 40;
+/// Duty words in one encoded frame
+pub const FRAME_WORDS: usize = PIXEL_COUNT * BITS_PER_PIXEL + RESET_WORDS;
+
+/// Color (red, green, blue) of the dimmest lit level
+const COOL: [u8; 3] = [0, 2, 24];
+/// Color (red, green, blue) of the brightest level, kept well below full so the panel is not
+/// blinding
+const WARM: [u8; 3] = [64, 24, 0];
+
+/// The color (red, green, blue) of a cell at brightness `level` (0..=fade::MAX_BRIGHTNESS).
+pub fn color(level: u8) -> [u8; 3] {
+    if level == 0 {
+        return [0; 3];
+    }
+    let level = level.min(fade::MAX_BRIGHTNESS) as u16;
+    let span = fade::MAX_BRIGHTNESS as u16 - 1;
+    let mut rgb = [0; 3];
+    for ((channel, cool), warm) in rgb.iter_mut().zip(COOL).zip(WARM) {
+        *channel = ((cool as u16 * (span - (level - 1)) + warm as u16 * (level - 1)) / span) as u8;
+    }
+    rgb
+}
+
+/// Encode `levels` as the PWM duty words of one frame: every pixel's green, red, and blue bytes,
+/// most significant bit first, and then the reset.
+pub fn encode(levels: &Levels, words: &mut [u16; FRAME_WORDS]) {
+    let (bits, reset) = words.split_at_mut(PIXEL_COUNT * BITS_PER_PIXEL);
+    for (pixel, level) in bits
+        .chunks_exact_mut(BITS_PER_PIXEL)
+        .zip(levels.iter().flatten())
+    {
+        let [red, green, blue] = color(*level);
+        let grb = (green as u32) << 16 | (red as u32) << 8 | blue as u32;
+        for (bit, word) in pixel.iter_mut().enumerate() {
+            *word = if grb >> (BITS_PER_PIXEL - 1 - bit) & 1 == 1 {
+                ONE
+            } else {
+                ZERO
+            };
+        }
+    }
+    reset.fill(LOW);
+}
+
+/// Something that can send an encoded frame down the panel's data line.
+pub trait Transmit {
+    /// Send `words` once, waiting for any frame still being sent to finish first.
+    fn transmit(&mut self, words: &[u16; FRAME_WORDS]);
+}
+
+/// The WS2812 panel, drawn on by sending each frame through `link`.
+pub struct Panel<T> {
+    link: T,
+}
+
+impl<T: Transmit> Panel<T> {
+    /// Draw on the panel behind `link`.
+    pub fn new(link: T) -> Self {
+        Panel { link }
+    }
+}
+
+impl<T: Transmit> Renderer for Panel<T> {
+    fn render_levels(&mut self, levels: &Levels) {
+        let mut words = [LOW; FRAME_WORDS];
+        encode(levels, &mut words);
+        self.link.transmit(&words);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A data line that keeps every frame sent down it
+    #[derive(Default)]
+    struct Recorder(Vec<[u16; FRAME_WORDS]>);
+
+    impl Transmit for Recorder {
+        fn transmit(&mut self, words: &[u16; FRAME_WORDS]) {
+            self.0.push(*words);
+        }
+    }
+
+    /// The GRB value of pixel `index` in an encoded frame
+    fn decode(words: &[u16; FRAME_WORDS], index: usize) -> u32 {
+        words[index * BITS_PER_PIXEL..(index + 1) * BITS_PER_PIXEL]
+            .iter()
+            .fold(0, |grb, word| {
+                assert!(*word == ZERO || *word == ONE);
+                grb << 1 | (*word == ONE) as u32
+            })
+    }
+
+    #[test]
+    fn colors_blend_from_cool_to_warm() {
+        assert_eq!(color(0), [0; 3]);
+        assert_eq!(color(1), COOL);
+        assert_eq!(color(fade::MAX_BRIGHTNESS), WARM);
+        assert_eq!(color(u8::MAX), WARM);
+        // red only rises and blue only falls with the level
+        for level in 1..fade::MAX_BRIGHTNESS {
+            let (dim, bright) = (color(level), color(level + 1));
+            assert!(bright[0] >= dim[0] && bright[2] <= dim[2]);
+        }
+    }
+
+    #[test]
+    fn pixels_are_encoded_green_first_and_msb_first() {
+        let mut levels = [[0; COL_COUNT]; ROW_COUNT];
+        levels[0][1] = fade::MAX_BRIGHTNESS;
+        levels[4][4] = 1;
+        let mut words = [0; FRAME_WORDS];
+        encode(&levels, &mut words);
+
+        let [red, green, blue] = WARM;
+        assert_eq!(
+            decode(&words, 1),
+            (green as u32) << 16 | (red as u32) << 8 | blue as u32
+        );
+        // green 24 is 0b0001_1000: the bits of the top byte go out most significant first
+        assert_eq!(
+            &words[BITS_PER_PIXEL..BITS_PER_PIXEL + 8],
+            &[ZERO, ZERO, ZERO, ONE, ONE, ZERO, ZERO, ZERO]
+        );
+        let [red, green, blue] = COOL;
+        assert_eq!(
+            decode(&words, PIXEL_COUNT - 1),
+            (green as u32) << 16 | (red as u32) << 8 | blue as u32
+        );
+        for pixel in (0..PIXEL_COUNT).filter(|pixel| ![1, PIXEL_COUNT - 1].contains(pixel)) {
+            assert_eq!(decode(&words, pixel), 0);
+        }
+    }
+
+    #[test]
+    fn every_frame_ends_with_the_reset() {
+        let mut words = [0; FRAME_WORDS];
+        encode(&[[fade::MAX_BRIGHTNESS; COL_COUNT]; ROW_COUNT], &mut words);
+        assert!(
+            words[PIXEL_COUNT * BITS_PER_PIXEL..]
+                .iter()
+                .all(|word| *word == LOW)
+        );
+        // every pulse fits inside its period
+        assert!(words.iter().all(|word| word & 0x7FFF < PERIOD_TICKS));
+    }
+
+    #[test]
+    fn the_panel_sends_every_frame_rendered() {
+        let mut panel = Panel::new(Recorder::default());
+        let mut board = [[0; COL_COUNT]; ROW_COUNT];
+        board[2] = [1; COL_COUNT];
+        panel.render(&board);
+        panel.render(&[[0; COL_COUNT]; ROW_COUNT]);
+
+        let frames = &panel.link.0;
+        assert_eq!(frames.len(), 2);
+        for pixel in 0..PIXEL_COUNT {
+            let lit = pixel / COL_COUNT == 2;
+            assert_eq!(decode(&frames[0], pixel) != 0, lit);
+            assert_eq!(decode(&frames[1], pixel), 0);
+        }
+    }
+}